@@ -0,0 +1,129 @@
+settings-updated = Einstellungen gespeichert
+admins-only = Nur Admins dürfen die Einstellungen ändern.
+setting-unavailable = Diese Einstellung gibt es auf { $platform } nicht.
+story-title = Die Geschichte bisher
+story-continued = Fortsetzung
+story-summary = Zusammenfassung
+title-vote = Stimmt über den Titel dieser Geschichte ab:
+daily-prompt = 📝 Tägliche Anregung: { $prompt }
+narration-unavailable = Vorlesen ist auf diesem Server nicht eingerichtet.
+story-not-found = Geschichte nicht gefunden.
+narration-failed = Die Geschichte konnte nicht vorgelesen werden.
+reason-too-many-words = Erlaubt ist nur ein Wort, oder ein kurzes Wort und ein weiteres.
+reason-banned-word = Dieses Wort ist hier verboten.
+reason-toxicity = Der Beitrag wurde als toxisch eingestuft.
+reason-banned-phrase = Dieser Ausdruck ist hier verboten.
+grace-notice = ❌ { $reason } Bearbeite deine Nachricht, sonst wird sie { $deadline } entfernt.
+appeal-button = Einspruch einlegen
+appeal-sent = Dein Einspruch wurde an die Moderatoren geschickt.
+reason-emoji = Emojis zählen hier nicht als Wörter.
+reason-cooldown = Warte ein wenig, bevor du dein nächstes Wort beiträgst.
+compile-cooldown = ⏳ Die Geschichte wurde gerade erst zusammengestellt. Schreibt weiter, das nächste Mal geht es { $deadline }.
+backup-sent = Die Sicherung wurde dir per DM geschickt.
+backup-failed = Die Sicherung konnte dir nicht geschickt werden. Erlaubst du DMs von diesem Server?
+backup-missing = Hänge die Sicherungsdatei an den restore-Befehl an.
+backup-invalid = Das ist keine Sicherungsdatei.
+backup-too-new = Diese Sicherung stammt von einer neueren Version des Bots.
+backup-other-guild = Diese Sicherung gehört zu einem anderen Server.
+audit-empty = Es wurden noch keine Einstellungen geändert.
+owner-only = Nur der Serverbesitzer darf diese Einstellung ändern.
+mods-only = Nur Moderatoren dürfen das.
+setup-intro = Richten wir das Geschichtenspiel ein. Wähle den Geschichtenkanal und wie verbotene Wörter gefunden werden, ob gängige Schimpfwörter gefiltert und fertige Geschichten angeheftet werden, und speichere dann.
+setup-cancelled = Einrichtung abgebrochen, nichts wurde geändert.
+setup-not-yours = Nur wer diese Einrichtung gestartet hat, kann sie benutzen.
+setup-expired = Diese Einrichtung ist abgelaufen. Starte eine neue mit `one-word setup`.
+digest-title = 📰 Die Geschichten dieser Woche
+digest-stories = Fertige Geschichten
+digest-words = Neue Wörter
+digest-top = Fleißigste Mitschreiber
+digest-longest = Längstes Wort
+subscribed = Du bekommst jede hier fertige Geschichte per DM. Beenden mit `one-word unsubscribe`.
+unsubscribed = Du bekommst keine fertigen Geschichten mehr per DM.
+subscription-story = 📖 In { $guild } wurde eine Geschichte fertig: { $link }
+nudge = 💤 Die Geschichte ist eingeschlafen… { $words } Wie geht es weiter?
+nudge-new = 💤 Hier ist es still. Beginnt eine neue Geschichte mit einem Wort!
+sprint-start = 🏃 Sprint! Schreibt in den nächsten { $minutes } Minuten so viele Wörter wie möglich, ohne Wartezeit.
+sprint-remaining = ⏱️ Noch { $minutes } Minuten im Sprint!
+sprint-over = 🏁 Sprint vorbei! { $words } Wörter kamen dazu, die meisten von { $top } ({ $top_words }).
+sprint-over-empty = 🏁 Sprint vorbei! Diesmal kamen keine Wörter dazu.
+sprint-running = Hier läuft schon ein Sprint.
+event-start = 🎉 Das Event { $event } hat begonnen!
+event-required = (Baut ein: { $words })
+event-not-found = Es gibt kein Event mit diesem Namen.
+challenge-complete = 🏆 { $user } hat das Challenge-Wort „{ $word }“ in die Geschichte eingebaut!
+challenges = Offene Challenges: { $words }
+challenges-empty = Es gibt keine offenen Challenges.
+challenge-not-found = Für dieses Wort gibt es keine offene Challenge.
+story-trivia = Wissenswertes
+trivia-longest = Längstes Wort: **{ $word }** von { $user }
+trivia-new = Zum ersten Mal hier: { $words }
+stats-title = 📊 Serverstatistik
+stats-stories = Geschichten
+stats-words = Wörter
+stats-contributors = Mitwirkende
+stats-vocabulary = Verschiedene Wörter
+stats-challenges = Gemeisterte Herausforderungen
+stats-longest = Längstes Wort
+stats-longest-by = **{ $word }** von { $user }
+xp-level-up = 🎉 { $user } hat Level { $level } erreicht!
+xp-off = XP sind auf diesem Server nicht aktiviert.
+rank-empty = Es gibt noch keine Wörter für eine Rangliste.
+story-needs-contributors = Diese Geschichte braucht { $needed } verschiedene Mitwirkende, bevor sie enden kann. Bisher sind es { $count }.
+status-title = 📖 Laufende Geschichte
+status-channel = Kanal
+reason-story-share = Du hast so viele Wörter zu dieser Geschichte beigetragen, wie eine Person darf. Lass die anderen sie beenden.
+reason-quiet-hours = Hier ist gerade Ruhezeit. Die Geschichte geht danach weiter.
+archive-not-found = Eine solche archivierte Geschichte gibt es nicht.
+archive-started = Begonnen
+archive-ended = Beendet
+archive-compiled = Zusammengestellt
+archive-starter = Begonnen von
+archive-ender = Beendet von
+archive-rules = Regeln
+content-warning = ⚠️ Inhaltswarnung: { $topics }
+severity-warning = ⚠️ { $user }, dieses Wort ist hier unerwünscht. Die Moderatoren wurden informiert.
+reason-unknown-word = Dieses Wort steht nicht im Wörterbuch.
+reason-compound = Wörter mit Bindestrich oder Apostroph sind hier nicht erlaubt.
+reason-markup = Bitte schreib einfache Wörter, ohne Spoiler oder Code.
+story-download = Als .txt herunterladen
+not-story-channel = Das ist kein Geschichtenkanal.
+compile-started = Die Geschichte wird bis zu dieser Nachricht zusammengestellt.
+rules-title = 📜 So wird gespielt
+rules-intro = Schreibt die Geschichte gemeinsam, ein Wort pro Nachricht.
+rules-words = Wörter pro Nachricht
+rules-per-user = Wörter pro Person in einer Geschichte
+rules-contributors = Personen bis zum Ende einer Geschichte
+rules-cooldown = Pause zwischen deinen Wörtern
+rules-censor-mode = Verbotene Wörter gelten als
+rules-end = Die Geschichte beenden
+rules-end-marker = Schreib `.` nach dem letzten Wort.
+rules-end-reaction = Oder reagiere mit { $emoji } auf das letzte Wort.
+rules-none = Keine
+welcome-dm = 👋 Schön, dass du bei der Geschichte in { $guild } mitmachst! So wird gespielt.
+welcome-channel = 👋 Willkommen bei der Geschichte, { $user }! So wird gespielt.
+prefs = Deine DMs: Löschbegründungen { $deletion }, fertige Geschichten { $stories }. Ändere sie mit `one-word prefs <deletion-dms|story-dms> <on|off>`.
+prefs-on = an
+prefs-off = aus
+export-sent = Die Statistiken wurden dir per DM geschickt.
+export-failed = Die Statistiken konnten dir nicht geschickt werden. Erlaubst du DMs von diesem Server?
+modstats = Seit dem Start des Bots gelöschte Wörter, nach Grund:
+modstats-empty = Seit dem Start des Bots wurden keine Wörter gelöscht.
+missing-permissions = Mir fehlen diese Berechtigungen in { $channel }, daher kann ich dort nichts löschen oder anheften: { $permissions }
+guild-welcome = Danke, dass ihr mich hinzugefügt habt! Mit `one-word setup` wählt ihr den Geschichtenkanal und seine Regeln. Gängige Schimpfwörter werden gefiltert, bis ihr das abschaltet.
+story-numbered = { $title } #{ $number }
+cleanup-pins = { $count } doppelte oder veraltete Pins entfernt.
+recompile-done = Die Geschichte wurde an den aktuellen Stil angepasst.
+recompile-too-long = Die Geschichte passt nicht mehr in die Nachrichten, in denen sie gepostet wurde.
+archive-edited = Die Geschichte wurde bearbeitet. Ihr früherer Text bleibt als Revision erhalten.
+archive-edit-no-match = Kein Wort der Geschichte passt.
+archive-edits = Bearbeitungen
+archive-revision = Revision { $revision } von { $revisions }
+archive-revision-not-found = Die Geschichte hat keine solche Revision.
+merge-done = Die Geschichten wurden zu einer zusammengeführt.
+merge-same-story = Das ist dieselbe Geschichte.
+merge-other-channel = Nur Geschichten desselben Kanals können zusammengeführt werden.
+topic-one-word = Ein Wort pro Nachricht · bisher { $count } Wörter
+topic-words = Bis zu { $max } Wörter pro Nachricht · bisher { $count } Wörter
+reason-paused = Das Spiel ist pausiert, während die Mods etwas klären. Es geht weiter, sobald sie es fortsetzen.
+new-account-dm = Dein Konto ist noch zu neu, um auf diesem Server an der Geschichte mitzuschreiben. Du kannst { $time } mitmachen.
+reason-new-account = Das Konto ist noch zu neu, um mitzuschreiben.
@@ -0,0 +1,132 @@
+# Replies of the bot, in Fluent syntax. Only simple messages and
+# `{ $variable }` placeables are supported.
+
+settings-updated = Settings updated
+admins-only = Only admins are allowed to update settings.
+setting-unavailable = This setting is not available on { $platform }.
+story-title = Story so far
+story-continued = continued
+story-summary = Summary
+title-vote = Vote for this story's title:
+daily-prompt = 📝 Daily prompt: { $prompt }
+narration-unavailable = Narration is not set up on this server.
+story-not-found = Story not found.
+narration-failed = Could not narrate the story.
+reason-too-many-words = Only one word, or a short word and one more, is allowed.
+reason-banned-word = That word is banned here.
+reason-toxicity = The message was flagged as toxic.
+reason-banned-phrase = That phrase is banned here.
+grace-notice = ❌ { $reason } Edit your message to fix it, or it will be removed { $deadline }.
+appeal-button = Appeal
+appeal-sent = Your appeal was sent to the moderators.
+reason-emoji = Emoji are not allowed as words here.
+reason-cooldown = Wait a little before adding your next word.
+compile-cooldown = ⏳ The story was just compiled. Keep writing, it can be compiled again { $deadline }.
+backup-sent = The backup was sent to your DMs.
+backup-failed = Could not send you the backup. Do you allow DMs from this server?
+backup-missing = Attach the backup file to the restore command.
+backup-invalid = That is not a backup file.
+backup-too-new = That backup was made by a newer version of the bot.
+backup-other-guild = That backup is of another server.
+audit-empty = No settings were changed yet.
+owner-only = Only the server owner is allowed to change this setting.
+mods-only = Only moderators are allowed to do this.
+setup-intro = Let's set up the story game. Pick the story channel and how banned words are found, choose whether to filter common profanity and pin compiled stories, then save.
+setup-cancelled = Setup cancelled, nothing was changed.
+setup-not-yours = Only the person who started this setup can use it.
+setup-expired = This setup has expired. Start a new one with `one-word setup`.
+digest-title = 📰 This week's stories
+digest-stories = Stories completed
+digest-words = Words added
+digest-top = Top contributors
+digest-longest = Longest word
+subscribed = You'll get every story completed here by DM. Stop with `one-word unsubscribe`.
+unsubscribed = You won't get completed stories by DM anymore.
+subscription-story = 📖 A story was completed in { $guild }: { $link }
+nudge = 💤 The story has gone quiet… { $words } What comes next?
+nudge-new = 💤 It's quiet here. Start a new story with a word!
+sprint-start = 🏃 Sprint! Add as many words as you can in the next { $minutes } minutes, no cooldown.
+sprint-remaining = ⏱️ { $minutes } minutes left in the sprint!
+sprint-over = 🏁 Sprint over! { $words } words added, most by { $top } ({ $top_words }).
+sprint-over-empty = 🏁 Sprint over! No words were added this time.
+sprint-running = A sprint is already running here.
+event-start = 🎉 The { $event } event has started!
+event-required = (Work in: { $words })
+event-not-found = There is no event with that name.
+challenge-complete = 🏆 { $user } worked the challenge word "{ $word }" into the story!
+challenges = Open challenges: { $words }
+challenges-empty = There are no open challenges.
+challenge-not-found = There is no open challenge for that word.
+story-trivia = Trivia
+trivia-longest = Longest word: **{ $word }** by { $user }
+trivia-new = First seen here: { $words }
+stats-title = 📊 Server stats
+stats-stories = Stories
+stats-words = Words
+stats-contributors = Contributors
+stats-vocabulary = Different words
+stats-challenges = Challenges completed
+stats-longest = Longest word
+stats-longest-by = **{ $word }** by { $user }
+xp-level-up = 🎉 { $user } reached level { $level }!
+xp-off = XP isn't turned on in this server.
+rank-empty = There are no words to rank yet.
+story-needs-contributors = This story needs { $needed } different contributors before it can end. It has { $count } so far.
+status-title = 📖 Story in progress
+status-channel = Channel
+reason-story-share = You have added as many words to this story as one person may. Let the others finish it.
+reason-quiet-hours = It's quiet hours here. The story continues when they're over.
+archive-not-found = There is no archived story like that.
+archive-started = Started
+archive-ended = Ended
+archive-compiled = Compiled
+archive-starter = Started by
+archive-ender = Ended by
+archive-rules = Rules
+content-warning = ⚠️ Content warning: { $topics }
+severity-warning = ⚠️ { $user }, that word isn't welcome here. The moderators have been told.
+reason-unknown-word = That word isn't in the dictionary.
+reason-compound = Hyphenated words and contractions aren't allowed here.
+reason-markup = Please contribute plain words, without spoilers or code.
+story-download = Download .txt
+not-story-channel = This isn't a story channel.
+compile-started = Compiling the story up to that message.
+rules-title = 📜 How to play
+rules-intro = Write the story together, one word per message.
+rules-words = Words per message
+rules-per-user = Words per person in a story
+rules-contributors = People needed to end a story
+rules-cooldown = Wait between your words
+rules-censor-mode = Banned words match
+rules-end = Ending the story
+rules-end-marker = Post `.` after the last word.
+rules-end-reaction = Or react with { $emoji } to the last word.
+rules-none = None
+welcome-dm = 👋 Thanks for joining the story in { $guild }! Here's how to play.
+welcome-channel = 👋 Welcome to the story, { $user }! Here's how to play.
+prefs = Your DMs: deletion explanations { $deletion }, completed stories { $stories }. Change them with `one-word prefs <deletion-dms|story-dms> <on|off>`.
+prefs-on = on
+prefs-off = off
+export-sent = The stats were sent to your DMs.
+export-failed = Could not send you the stats. Do you allow DMs from this server?
+modstats = Words deleted since the bot started, by reason:
+modstats-empty = No words were deleted since the bot started.
+missing-permissions = I'm missing these permissions in { $channel }, so I can't delete or pin there: { $permissions }
+guild-welcome = Thanks for adding me! Run `one-word setup` to pick the story channel and its rules. Common profanity is filtered until you turn it off.
+story-numbered = { $title } #{ $number }
+cleanup-pins = Removed { $count } duplicate or outdated pins.
+recompile-done = The story was updated to the current style.
+recompile-too-long = The story no longer fits in the messages it was posted in.
+archive-edited = The story was edited. Its earlier text is kept as a revision.
+archive-edit-no-match = No word of the story matches.
+archive-edits = Edits
+archive-revision = Revision { $revision } of { $revisions }
+archive-revision-not-found = The story has no revision like that.
+merge-done = The stories were merged into one.
+merge-same-story = Those are the same story.
+merge-other-channel = Only stories of the same channel can be merged.
+topic-one-word = One word per message · { $count } words so far
+topic-words = Up to { $max } words per message · { $count } words so far
+reason-paused = The game is paused while the mods sort something out. It continues once they resume it.
+new-account-dm = Your account is too new to add to the story on this server yet. You can join in { $time }.
+reason-new-account = The account is too new to contribute yet.
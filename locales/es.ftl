@@ -0,0 +1,129 @@
+settings-updated = Ajustes actualizados
+admins-only = Solo los admins pueden cambiar los ajustes.
+setting-unavailable = Este ajuste no está disponible en { $platform }.
+story-title = La historia hasta ahora
+story-continued = continuación
+story-summary = Resumen
+title-vote = Vota el título de esta historia:
+daily-prompt = 📝 Idea del día: { $prompt }
+narration-unavailable = La narración no está configurada en este servidor.
+story-not-found = Historia no encontrada.
+narration-failed = No se pudo narrar la historia.
+reason-too-many-words = Solo se permite una palabra, o una palabra corta y otra más.
+reason-banned-word = Esa palabra está prohibida aquí.
+reason-toxicity = El mensaje se consideró tóxico.
+reason-banned-phrase = Esa frase está prohibida aquí.
+grace-notice = ❌ { $reason } Edita tu mensaje para corregirlo o se eliminará { $deadline }.
+appeal-button = Apelar
+appeal-sent = Tu apelación se envió a los moderadores.
+reason-emoji = Aquí no se permiten emojis como palabras.
+reason-cooldown = Espera un poco antes de añadir tu siguiente palabra.
+compile-cooldown = ⏳ La historia se acaba de compilar. Seguid escribiendo, se podrá compilar de nuevo { $deadline }.
+backup-sent = La copia de seguridad se te envió por mensaje privado.
+backup-failed = No se pudo enviarte la copia de seguridad. ¿Permites mensajes privados de este servidor?
+backup-missing = Adjunta el archivo de la copia de seguridad al comando restore.
+backup-invalid = Eso no es un archivo de copia de seguridad.
+backup-too-new = Esa copia de seguridad es de una versión más reciente del bot.
+backup-other-guild = Esa copia de seguridad es de otro servidor.
+audit-empty = Todavía no se ha cambiado ningún ajuste.
+owner-only = Solo el propietario del servidor puede cambiar este ajuste.
+mods-only = Solo los moderadores pueden hacer esto.
+setup-intro = Configuremos el juego de historias. Elige el canal de la historia y cómo se detectan las palabras prohibidas, decide si filtrar las groserías comunes y fijar las historias compiladas, y luego guarda.
+setup-cancelled = Configuración cancelada, no se cambió nada.
+setup-not-yours = Solo quien inició esta configuración puede usarla.
+setup-expired = Esta configuración ha caducado. Inicia una nueva con `one-word setup`.
+digest-title = 📰 Las historias de la semana
+digest-stories = Historias completadas
+digest-words = Palabras añadidas
+digest-top = Mejores colaboradores
+digest-longest = Palabra más larga
+subscribed = Recibirás por MD cada historia completada aquí. Para dejarlo, usa `one-word unsubscribe`.
+unsubscribed = Ya no recibirás las historias completadas por MD.
+subscription-story = 📖 Se completó una historia en { $guild }: { $link }
+nudge = 💤 La historia se ha quedado dormida… { $words } ¿Qué viene después?
+nudge-new = 💤 Está muy tranquilo por aquí. ¡Empezad una nueva historia con una palabra!
+sprint-start = 🏃 ¡Sprint! Añadid tantas palabras como podáis en los próximos { $minutes } minutos, sin espera.
+sprint-remaining = ⏱️ ¡Quedan { $minutes } minutos de sprint!
+sprint-over = 🏁 ¡Sprint terminado! Se añadieron { $words } palabras, la mayoría de { $top } ({ $top_words }).
+sprint-over-empty = 🏁 ¡Sprint terminado! Esta vez no se añadieron palabras.
+sprint-running = Ya hay un sprint en marcha aquí.
+event-start = 🎉 ¡Ha empezado el evento { $event }!
+event-required = (Incluid: { $words })
+event-not-found = No hay ningún evento con ese nombre.
+challenge-complete = 🏆 ¡{ $user } ha metido la palabra del reto «{ $word }» en la historia!
+challenges = Retos abiertos: { $words }
+challenges-empty = No hay retos abiertos.
+challenge-not-found = No hay ningún reto abierto para esa palabra.
+story-trivia = Curiosidades
+trivia-longest = Palabra más larga: **{ $word }** de { $user }
+trivia-new = Vistas aquí por primera vez: { $words }
+stats-title = 📊 Estadísticas del servidor
+stats-stories = Historias
+stats-words = Palabras
+stats-contributors = Colaboradores
+stats-vocabulary = Palabras distintas
+stats-challenges = Desafíos completados
+stats-longest = Palabra más larga
+stats-longest-by = **{ $word }** de { $user }
+xp-level-up = 🎉 ¡{ $user } alcanzó el nivel { $level }!
+xp-off = La XP no está activada en este servidor.
+rank-empty = Todavía no hay palabras que clasificar.
+story-needs-contributors = Esta historia necesita { $needed } colaboradores distintos antes de poder terminar. Por ahora tiene { $count }.
+status-title = 📖 Historia en curso
+status-channel = Canal
+reason-story-share = Ya añadiste tantas palabras a esta historia como una persona puede. Deja que los demás la terminen.
+reason-quiet-hours = Ahora son horas de silencio aquí. La historia sigue cuando terminen.
+archive-not-found = No hay ninguna historia archivada así.
+archive-started = Empezada
+archive-ended = Terminada
+archive-compiled = Compilada
+archive-starter = Empezada por
+archive-ender = Terminada por
+archive-rules = Reglas
+content-warning = ⚠️ Advertencia de contenido: { $topics }
+severity-warning = ⚠️ { $user }, esa palabra no es bienvenida aquí. Se ha avisado a los moderadores.
+reason-unknown-word = Esa palabra no está en el diccionario.
+reason-compound = Aquí no se permiten palabras con guion ni contracciones.
+reason-markup = Escribe palabras sencillas, sin spoilers ni código.
+story-download = Descargar .txt
+not-story-channel = Este no es un canal de historias.
+compile-started = Compilando la historia hasta ese mensaje.
+rules-title = 📜 Cómo jugar
+rules-intro = Escribid la historia juntos, una palabra por mensaje.
+rules-words = Palabras por mensaje
+rules-per-user = Palabras por persona en una historia
+rules-contributors = Personas necesarias para terminar una historia
+rules-cooldown = Espera entre tus palabras
+rules-censor-mode = Las palabras prohibidas cuentan como
+rules-end = Terminar la historia
+rules-end-marker = Escribe `.` después de la última palabra.
+rules-end-reaction = O reacciona con { $emoji } a la última palabra.
+rules-none = Ninguno
+welcome-dm = 👋 ¡Gracias por unirte a la historia en { $guild }! Así se juega.
+welcome-channel = 👋 ¡Te damos la bienvenida a la historia, { $user }! Así se juega.
+prefs = Tus MD: explicaciones de borrado { $deletion }, historias terminadas { $stories }. Cámbialos con `one-word prefs <deletion-dms|story-dms> <on|off>`.
+prefs-on = activados
+prefs-off = desactivados
+export-sent = Las estadísticas se enviaron a tus MD.
+export-failed = No se pudieron enviar las estadísticas. ¿Permites MD de este servidor?
+modstats = Palabras eliminadas desde que se inició el bot, por motivo:
+modstats-empty = No se ha eliminado ninguna palabra desde que se inició el bot.
+missing-permissions = Me faltan estos permisos en { $channel }, así que no puedo borrar ni fijar mensajes allí: { $permissions }
+guild-welcome = ¡Gracias por añadirme! Usad `one-word setup` para elegir el canal de historias y sus reglas. Las palabrotas comunes se filtran hasta que lo desactivéis.
+story-numbered = { $title } #{ $number }
+cleanup-pins = Se eliminaron { $count } mensajes fijados duplicados u obsoletos.
+recompile-done = La historia se actualizó con el estilo actual.
+recompile-too-long = La historia ya no cabe en los mensajes en los que se publicó.
+archive-edited = La historia se editó. Su texto anterior se guarda como revisión.
+archive-edit-no-match = Ninguna palabra de la historia coincide.
+archive-edits = Ediciones
+archive-revision = Revisión { $revision } de { $revisions }
+archive-revision-not-found = La historia no tiene una revisión así.
+merge-done = Las historias se unieron en una.
+merge-same-story = Es la misma historia.
+merge-other-channel = Solo se pueden unir historias del mismo canal.
+topic-one-word = Una palabra por mensaje · { $count } palabras hasta ahora
+topic-words = Hasta { $max } palabras por mensaje · { $count } palabras hasta ahora
+reason-paused = El juego está en pausa mientras los mods resuelven algo. Continuará cuando lo reanuden.
+new-account-dm = Tu cuenta aún es demasiado nueva para participar en la historia de este servidor. Podrás unirte { $time }.
+reason-new-account = La cuenta aún es demasiado nueva para participar.
@@ -0,0 +1,129 @@
+settings-updated = Paramètres mis à jour
+admins-only = Seuls les admins peuvent modifier les paramètres.
+setting-unavailable = Ce paramètre n'est pas disponible sur { $platform }.
+story-title = L'histoire jusqu'ici
+story-continued = suite
+story-summary = Résumé
+title-vote = Votez pour le titre de cette histoire :
+daily-prompt = 📝 Idée du jour : { $prompt }
+narration-unavailable = La lecture à voix haute n'est pas configurée sur ce serveur.
+story-not-found = Histoire introuvable.
+narration-failed = Impossible de lire l'histoire.
+reason-too-many-words = Un seul mot est autorisé, ou un mot court suivi d'un autre.
+reason-banned-word = Ce mot est interdit ici.
+reason-toxicity = Le message a été jugé toxique.
+reason-banned-phrase = Cette expression est interdite ici.
+grace-notice = ❌ { $reason } Modifie ton message pour le corriger, sinon il sera supprimé { $deadline }.
+appeal-button = Faire appel
+appeal-sent = Ton appel a été transmis aux modérateurs.
+reason-emoji = Les emoji ne sont pas acceptés comme mots ici.
+reason-cooldown = Attends un peu avant d'ajouter ton prochain mot.
+compile-cooldown = ⏳ L'histoire vient d'être compilée. Continuez à écrire, elle pourra l'être à nouveau { $deadline }.
+backup-sent = La sauvegarde t'a été envoyée en message privé.
+backup-failed = Impossible de t'envoyer la sauvegarde. Acceptes-tu les messages privés de ce serveur ?
+backup-missing = Joins le fichier de sauvegarde à la commande restore.
+backup-invalid = Ce n'est pas un fichier de sauvegarde.
+backup-too-new = Cette sauvegarde vient d'une version plus récente du bot.
+backup-other-guild = Cette sauvegarde provient d'un autre serveur.
+audit-empty = Aucun paramètre n'a encore été modifié.
+owner-only = Seul le propriétaire du serveur peut modifier ce paramètre.
+mods-only = Seuls les modérateurs peuvent faire ça.
+setup-intro = Configurons le jeu d'histoire. Choisis le salon de l'histoire et la façon de repérer les mots interdits, décide s'il faut filtrer les grossièretés courantes et épingler les histoires compilées, puis enregistre.
+setup-cancelled = Configuration annulée, rien n'a été modifié.
+setup-not-yours = Seule la personne qui a lancé cette configuration peut l'utiliser.
+setup-expired = Cette configuration a expiré. Lances-en une nouvelle avec `one-word setup`.
+digest-title = 📰 Les histoires de la semaine
+digest-stories = Histoires terminées
+digest-words = Mots ajoutés
+digest-top = Meilleurs contributeurs
+digest-longest = Mot le plus long
+subscribed = Tu recevras chaque histoire terminée ici en MP. Arrête avec `one-word unsubscribe`.
+unsubscribed = Tu ne recevras plus les histoires terminées en MP.
+subscription-story = 📖 Une histoire a été terminée sur { $guild } : { $link }
+nudge = 💤 L'histoire s'est endormie… { $words } Quelle est la suite ?
+nudge-new = 💤 C'est calme ici. Commencez une nouvelle histoire avec un mot !
+sprint-start = 🏃 Sprint ! Ajoutez autant de mots que possible dans les { $minutes } prochaines minutes, sans délai.
+sprint-remaining = ⏱️ Plus que { $minutes } minutes de sprint !
+sprint-over = 🏁 Sprint terminé ! { $words } mots ajoutés, surtout par { $top } ({ $top_words }).
+sprint-over-empty = 🏁 Sprint terminé ! Aucun mot n'a été ajouté cette fois.
+sprint-running = Un sprint est déjà en cours ici.
+event-start = 🎉 L'événement { $event } a commencé !
+event-required = (À placer : { $words })
+event-not-found = Aucun événement ne porte ce nom.
+challenge-complete = 🏆 { $user } a placé le mot défi « { $word } » dans l'histoire !
+challenges = Défis en cours : { $words }
+challenges-empty = Il n'y a aucun défi en cours.
+challenge-not-found = Aucun défi en cours pour ce mot.
+story-trivia = Anecdotes
+trivia-longest = Mot le plus long : **{ $word }** par { $user }
+trivia-new = Première apparition ici : { $words }
+stats-title = 📊 Statistiques du serveur
+stats-stories = Histoires
+stats-words = Mots
+stats-contributors = Contributeurs
+stats-vocabulary = Mots différents
+stats-challenges = Défis relevés
+stats-longest = Mot le plus long
+stats-longest-by = **{ $word }** par { $user }
+xp-level-up = 🎉 { $user } a atteint le niveau { $level } !
+xp-off = L'XP n'est pas activée sur ce serveur.
+rank-empty = Il n'y a pas encore de mots à classer.
+story-needs-contributors = Cette histoire a besoin de { $needed } contributeurs différents avant de pouvoir se terminer. Elle en a { $count } pour l'instant.
+status-title = 📖 Histoire en cours
+status-channel = Salon
+reason-story-share = Tu as ajouté autant de mots à cette histoire qu'une personne le peut. Laisse les autres la terminer.
+reason-quiet-hours = C'est l'heure du calme ici. L'histoire reprendra ensuite.
+archive-not-found = Aucune histoire archivée ne correspond.
+archive-started = Commencée
+archive-ended = Terminée
+archive-compiled = Compilée
+archive-starter = Commencée par
+archive-ender = Terminée par
+archive-rules = Règles
+content-warning = ⚠️ Avertissement : { $topics }
+severity-warning = ⚠️ { $user }, ce mot n'est pas le bienvenu ici. Les modérateurs ont été prévenus.
+reason-unknown-word = Ce mot n'est pas dans le dictionnaire.
+reason-compound = Les mots composés et les élisions ne sont pas autorisés ici.
+reason-markup = Merci d'écrire des mots simples, sans spoilers ni code.
+story-download = Télécharger en .txt
+not-story-channel = Ce n'est pas un salon d'histoire.
+compile-started = L'histoire est compilée jusqu'à ce message.
+rules-title = 📜 Comment jouer
+rules-intro = Écrivez l'histoire ensemble, un mot par message.
+rules-words = Mots par message
+rules-per-user = Mots par personne dans une histoire
+rules-contributors = Personnes nécessaires pour finir une histoire
+rules-cooldown = Attente entre vos mots
+rules-censor-mode = Les mots interdits comptent en
+rules-end = Finir l'histoire
+rules-end-marker = Envoyez `.` après le dernier mot.
+rules-end-reaction = Ou réagissez avec { $emoji } au dernier mot.
+rules-none = Aucune
+welcome-dm = 👋 Merci de participer à l'histoire sur { $guild } ! Voici comment jouer.
+welcome-channel = 👋 Bienvenue dans l'histoire, { $user } ! Voici comment jouer.
+prefs = Tes MP : explications de suppression { $deletion }, histoires terminées { $stories }. Modifie-les avec `one-word prefs <deletion-dms|story-dms> <on|off>`.
+prefs-on = activées
+prefs-off = désactivées
+export-sent = Les statistiques t'ont été envoyées en message privé.
+export-failed = Impossible de t'envoyer les statistiques. Acceptes-tu les messages privés de ce serveur ?
+modstats = Mots supprimés depuis le démarrage du bot, par motif :
+modstats-empty = Aucun mot n'a été supprimé depuis le démarrage du bot.
+missing-permissions = Il me manque ces permissions dans { $channel }, je ne peux donc rien y supprimer ni épingler : { $permissions }
+guild-welcome = Merci de m'avoir ajouté ! Lancez `one-word setup` pour choisir le salon des histoires et ses règles. Les grossièretés courantes sont filtrées jusqu'à ce que vous désactiviez le filtre.
+story-numbered = { $title } #{ $number }
+cleanup-pins = { $count } épingles en double ou obsolètes supprimées.
+recompile-done = L'histoire a été mise à jour avec le style actuel.
+recompile-too-long = L'histoire ne tient plus dans les messages où elle a été publiée.
+archive-edited = L'histoire a été modifiée. Son texte précédent est conservé comme révision.
+archive-edit-no-match = Aucun mot de l'histoire ne correspond.
+archive-edits = Modifications
+archive-revision = Révision { $revision } sur { $revisions }
+archive-revision-not-found = L'histoire n'a pas de révision de ce type.
+merge-done = Les histoires ont été fusionnées en une seule.
+merge-same-story = C'est la même histoire.
+merge-other-channel = Seules les histoires d'un même salon peuvent être fusionnées.
+topic-one-word = Un mot par message · { $count } mots pour l'instant
+topic-words = Jusqu'à { $max } mots par message · { $count } mots pour l'instant
+reason-paused = Le jeu est en pause le temps que les modos règlent quelque chose. Il reprendra dès qu'ils le relanceront.
+new-account-dm = Ton compte est encore trop récent pour participer à l'histoire sur ce serveur. Tu pourras participer { $time }.
+reason-new-account = Le compte est encore trop récent pour participer.
@@ -0,0 +1,316 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serenity::http::Http;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Most messages deleted in one bulk delete, the API's limit.
+const BULK_DELETE_LIMIT: usize = 100;
+
+/// Latest timings the latency quantiles are taken from.
+const LATENCY_SAMPLES: usize = 1000;
+
+/// Quantiles of the latencies served at `/metrics`.
+const QUANTILES: [f64; 2] = [0.5, 0.95];
+
+/// Moderation work the bot doesn't need an answer for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Delete(MessageId),
+    React(MessageId, ReactionType),
+}
+
+/// Counts of queued actions, served by the web server at `/metrics`.
+#[derive(Default)]
+pub struct Metrics {
+    pub queued: AtomicU64,
+    pub sent: AtomicU64,
+    /// Actions merged into another one, or dropped as duplicates.
+    pub coalesced: AtomicU64,
+    pub failed: AtomicU64,
+    /// Messages whose handling panicked.
+    pub panics: AtomicU64,
+    /// How long actions wait in the queue before they run.
+    pub wait: Latency,
+    pub delete: Requests,
+    pub bulk_delete: Requests,
+    pub react: Requests,
+}
+
+/// Timings of something, to tell how long it usually takes.
+#[derive(Default)]
+pub struct Latency {
+    samples: std::sync::Mutex<VecDeque<Duration>>,
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Latency {
+    fn observe(&self, took: Duration) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(took.as_micros() as u64, Ordering::Relaxed);
+        let mut samples = self.samples.lock().unwrap();
+        samples.push_back(took);
+        if samples.len() > LATENCY_SAMPLES {
+            samples.pop_front();
+        }
+    }
+
+    /// The `q` quantile of the latest timings, in seconds.
+    fn quantile(&self, q: f64) -> f64 {
+        let mut samples: Vec<Duration> = self.samples.lock().unwrap().iter().copied().collect();
+        if samples.is_empty() {
+            return 0.0;
+        }
+        samples.sort();
+        let i = ((samples.len() - 1) as f64 * q).round() as usize;
+        samples[i].as_secs_f64()
+    }
+
+    /// The timings as a Prometheus summary, `label` added to each sample.
+    fn render(&self, name: &str, label: Option<&str>) -> String {
+        let labels = |extra: Option<String>| {
+            let labels: Vec<String> = label.map(str::to_string).into_iter().chain(extra).collect();
+            match labels.is_empty() {
+                true => String::new(),
+                false => format!("{{{}}}", labels.join(",")),
+            }
+        };
+        let mut text = String::new();
+        for q in QUANTILES {
+            let quantile = Some(format!("quantile=\"{}\"", q));
+            text += &format!("{}{} {}\n", name, labels(quantile), self.quantile(q));
+        }
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        text += &format!("{}_sum{} {}\n", name, labels(None), sum);
+        let count = self.count.load(Ordering::Relaxed);
+        text += &format!("{}_count{} {}\n", name, labels(None), count);
+        text
+    }
+}
+
+/// Timings and failures of the requests to one Discord API route.
+#[derive(Default)]
+pub struct Requests {
+    pub latency: Latency,
+    pub failed: AtomicU64,
+}
+
+/// Runs `request`, timing it as one of `requests`.
+async fn timed<T>(
+    requests: &Requests,
+    request: impl Future<Output = serenity::Result<T>>,
+) -> serenity::Result<T> {
+    let start = Instant::now();
+    let res = request.await;
+    requests.latency.observe(start.elapsed());
+    if res.is_err() {
+        requests.failed.fetch_add(1, Ordering::Relaxed);
+    }
+    res
+}
+
+/// Runs actions one channel at a time, so bursts of deletions and flags
+/// wait for the channel's rate limit instead of failing.
+#[derive(Default)]
+pub struct ActionQueue {
+    routes: Mutex<HashMap<ChannelId, UnboundedSender<(Instant, Action)>>>,
+    pub metrics: Metrics,
+}
+
+pub struct ActionContainer;
+impl TypeMapKey for ActionContainer {
+    type Value = Arc<ActionQueue>;
+}
+
+pub async fn queue(data: &RwLock<TypeMap>) -> Arc<ActionQueue> {
+    let data = data.read().await;
+    data.get::<ActionContainer>()
+        .expect("Expected ActionContainer in TypeMap")
+        .clone()
+}
+
+pub async fn delete(ctx: &Context, channel_id: ChannelId, id: MessageId) {
+    push(ctx, channel_id, Action::Delete(id)).await;
+}
+
+pub async fn react(
+    ctx: &Context,
+    channel_id: ChannelId,
+    id: MessageId,
+    reaction: impl Into<ReactionType>,
+) {
+    push(ctx, channel_id, Action::React(id, reaction.into())).await;
+}
+
+async fn push(ctx: &Context, channel_id: ChannelId, action: Action) {
+    let queue = queue(&ctx.data).await;
+    queue.metrics.queued.fetch_add(1, Ordering::Relaxed);
+
+    let mut routes = queue.routes.lock().await;
+    let action = (Instant::now(), action);
+    let action = match routes.get(&channel_id) {
+        Some(route) => match route.send(action) {
+            Ok(()) => return,
+            // The route's worker is gone, start a new one.
+            Err(unsent) => unsent.0,
+        },
+        None => action,
+    };
+    let (tx, rx) = mpsc::unbounded_channel();
+    let _ = tx.send(action);
+    routes.insert(channel_id, tx);
+    tokio::spawn(work(ctx.http.clone(), queue.clone(), channel_id, rx));
+}
+
+/// Runs the actions for `channel_id` until the queue is dropped.
+async fn work(
+    http: Arc<Http>,
+    queue: Arc<ActionQueue>,
+    channel_id: ChannelId,
+    mut rx: UnboundedReceiver<(Instant, Action)>,
+) {
+    while let Some(action) = rx.recv().await {
+        let mut batch = vec![action];
+        while let Ok(action) = rx.try_recv() {
+            batch.push(action);
+        }
+        let batch = batch
+            .into_iter()
+            .map(|(queued_at, action)| {
+                queue.metrics.wait.observe(queued_at.elapsed());
+                action
+            })
+            .collect();
+        run_batch(&http, &queue.metrics, channel_id, batch).await;
+    }
+}
+
+/// Runs everything that piled up, deletions first. Duplicates are dropped,
+/// and deletions are merged into bulk deletes.
+async fn run_batch(http: &Http, metrics: &Metrics, channel_id: ChannelId, batch: Vec<Action>) {
+    let total = batch.len();
+    let mut deletions: Vec<MessageId> = Vec::new();
+    let mut reactions: Vec<(MessageId, ReactionType)> = Vec::new();
+    for action in batch {
+        match action {
+            Action::Delete(id) if !deletions.contains(&id) => deletions.push(id),
+            Action::React(id, reaction) if !reactions.contains(&(id, reaction.clone())) => {
+                reactions.push((id, reaction));
+            }
+            _ => {}
+        }
+    }
+    // Reacting to a message that is about to be deleted is pointless.
+    reactions.retain(|(id, _)| !deletions.contains(id));
+
+    let mut requests = 0;
+    for chunk in deletions.chunks(BULK_DELETE_LIMIT) {
+        requests += delete_messages(http, metrics, channel_id, chunk).await;
+    }
+    for (id, reaction) in reactions {
+        requests += 1;
+        let reacted = channel_id.create_reaction(http, id, reaction);
+        record(metrics, timed(&metrics.react, reacted).await);
+    }
+    metrics
+        .coalesced
+        .fetch_add(total.saturating_sub(requests) as u64, Ordering::Relaxed);
+}
+
+/// Deletes `ids`, in bulk where possible. Returns the number of requests.
+async fn delete_messages(
+    http: &Http,
+    metrics: &Metrics,
+    channel_id: ChannelId,
+    ids: &[MessageId],
+) -> usize {
+    if let [id] = ids {
+        let deleted = channel_id.delete_message(http, id);
+        record(metrics, timed(&metrics.delete, deleted).await);
+        return 1;
+    }
+    let deleted = channel_id.delete_messages(http, ids);
+    match timed(&metrics.bulk_delete, deleted).await {
+        Ok(()) => {
+            metrics.sent.fetch_add(1, Ordering::Relaxed);
+            1
+        }
+        // Bulk deletes can't remove messages older than two weeks.
+        Err(why) => {
+            println!("Error bulk deleting messages {:?}", why);
+            for id in ids {
+                let deleted = channel_id.delete_message(http, id);
+                record(metrics, timed(&metrics.delete, deleted).await);
+            }
+            1 + ids.len()
+        }
+    }
+}
+
+fn record(metrics: &Metrics, res: serenity::Result<()>) {
+    match res {
+        Ok(()) => metrics.sent.fetch_add(1, Ordering::Relaxed),
+        Err(why) => {
+            println!("Error running queued action {:?}", why);
+            metrics.failed.fetch_add(1, Ordering::Relaxed)
+        }
+    };
+}
+
+/// The metrics in the Prometheus text format.
+pub fn render(metrics: &Metrics) -> String {
+    let counters = [
+        ("queued", &metrics.queued, "Actions queued."),
+        ("sent", &metrics.sent, "Requests that succeeded."),
+        (
+            "coalesced",
+            &metrics.coalesced,
+            "Actions merged or dropped as duplicates.",
+        ),
+        ("failed", &metrics.failed, "Requests that failed."),
+    ];
+    let mut text = String::new();
+    for (name, value, help) in counters {
+        text += &format!(
+            "# HELP one_word_actions_{0}_total {1}\n# TYPE one_word_actions_{0}_total counter\none_word_actions_{0}_total {2}\n",
+            name,
+            help,
+            value.load(Ordering::Relaxed)
+        );
+    }
+
+    text += &format!(
+        "# HELP one_word_message_panics_total Messages whose handling panicked.\n# TYPE one_word_message_panics_total counter\none_word_message_panics_total {}\n",
+        metrics.panics.load(Ordering::Relaxed)
+    );
+    text += "# HELP one_word_action_wait_seconds Time actions waited in the queue.\n# TYPE one_word_action_wait_seconds summary\n";
+    text += &metrics.wait.render("one_word_action_wait_seconds", None);
+    let routes = [
+        ("delete", &metrics.delete),
+        ("bulk_delete", &metrics.bulk_delete),
+        ("react", &metrics.react),
+    ];
+    text += "# HELP one_word_discord_request_seconds Discord API latency of queued actions.\n# TYPE one_word_discord_request_seconds summary\n";
+    for (route, requests) in routes {
+        let label = format!("route=\"{}\"", route);
+        text += &requests
+            .latency
+            .render("one_word_discord_request_seconds", Some(&label));
+    }
+    text += "# HELP one_word_discord_request_errors_total Discord API requests of queued actions that failed.\n# TYPE one_word_discord_request_errors_total counter\n";
+    for (route, requests) in routes {
+        text += &format!(
+            "one_word_discord_request_errors_total{{route=\"{}\"}} {}\n",
+            route,
+            requests.failed.load(Ordering::Relaxed)
+        );
+    }
+    text
+}
@@ -0,0 +1,194 @@
+use serenity::builder::CreateComponents;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::{InteractionResponseType, MessageFlags};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::bridge;
+use crate::config::get_config;
+use crate::i18n::tr;
+
+/// `appeal:<guild id>:<channel id>`, on the deletion DM.
+const APPEAL: &str = "appeal";
+/// `appeal-approve:<channel id>:<user id>`, on the mod log post.
+const APPROVE: &str = "appeal-approve";
+/// `appeal-reject`, on the mod log post.
+const REJECT: &str = "appeal-reject";
+
+/// Adds the button appealing the deletion of a word from `channel_id`.
+/// The word itself is expected in the description of the message's embed.
+pub fn button<'a>(
+    components: &'a mut CreateComponents,
+    label: &str,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> &'a mut CreateComponents {
+    components.create_action_row(|r| {
+        r.create_button(|b| {
+            b.custom_id(format!("{}:{}:{}", APPEAL, guild_id, channel_id))
+                .label(label)
+                .style(ButtonStyle::Secondary)
+        })
+    })
+}
+
+pub fn is_appeal(custom_id: &str) -> bool {
+    custom_id.starts_with(APPEAL)
+}
+
+pub async fn handle(ctx: &Context, component: &MessageComponentInteraction) {
+    let parts: Vec<&str> = component.data.custom_id.split(':').collect();
+    let ids: Vec<u64> = parts[1..].iter().filter_map(|id| id.parse().ok()).collect();
+    match (parts[0], ids.as_slice()) {
+        (APPEAL, [guild_id, channel_id]) => {
+            appeal(ctx, component, GuildId(*guild_id), ChannelId(*channel_id)).await
+        }
+        (APPROVE, [channel_id, user_id]) => {
+            approve(ctx, component, ChannelId(*channel_id), UserId(*user_id)).await
+        }
+        (REJECT, []) => {
+            if is_moderator(ctx, component).await {
+                decide(ctx, component, "Rejected").await;
+            }
+        }
+        _ => println!("Unknown appeal button {}", component.data.custom_id),
+    }
+}
+
+/// The word a button's message is about.
+fn word(component: &MessageComponentInteraction) -> Option<String> {
+    component
+        .message
+        .embeds
+        .first()
+        .and_then(|e| e.description.clone())
+}
+
+/// Forwards the appeal to the mod log channel.
+async fn appeal(
+    ctx: &Context,
+    component: &MessageComponentInteraction,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) {
+    let config = get_config(&ctx.data, guild_id).await;
+    let (mod_log, word) = match (config.mod_log_channel, word(component)) {
+        (Some(mod_log), Some(word)) => (mod_log, word),
+        _ => return,
+    };
+
+    let posted = mod_log
+        .send_message(&ctx.http, |m| {
+            m.content(format!(
+                "Appeal from {} ({}) in <#{}>",
+                component.user.tag(),
+                component.user.id,
+                channel_id
+            ))
+            .embed(|e| e.description(&word))
+            .components(|c| {
+                c.create_action_row(|r| {
+                    r.create_button(|b| {
+                        b.custom_id(format!("{}:{}:{}", APPROVE, channel_id, component.user.id))
+                            .label("Approve")
+                            .style(ButtonStyle::Success)
+                    })
+                    .create_button(|b| {
+                        b.custom_id(REJECT)
+                            .label("Reject")
+                            .style(ButtonStyle::Danger)
+                    })
+                })
+            })
+        })
+        .await;
+    if let Err(why) = posted {
+        println!("Error posting appeal {:?}", why);
+        return;
+    }
+
+    let res = component
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| {
+                    d.content(tr(config.locale, "appeal-sent"))
+                        .components(|c| c)
+                })
+        })
+        .await;
+    if let Err(why) = res {
+        println!("Error confirming appeal {:?}", why);
+    }
+}
+
+/// Posts the appealed word in the story channel, in the name of its author.
+async fn approve(
+    ctx: &Context,
+    component: &MessageComponentInteraction,
+    channel_id: ChannelId,
+    user_id: UserId,
+) {
+    if !is_moderator(ctx, component).await {
+        return;
+    }
+    let (guild_id, word) = match (component.guild_id, word(component)) {
+        (Some(guild_id), Some(word)) => (guild_id, word),
+        _ => return,
+    };
+    let user = match user_id.to_user(&ctx.http).await {
+        Ok(user) => user,
+        Err(why) => {
+            println!("Error fetching appealing user {:?}", why);
+            return;
+        }
+    };
+    if bridge::post_as(&ctx.http, &ctx.data, guild_id, channel_id, word, &user).await {
+        decide(ctx, component, "Approved").await;
+    }
+}
+
+/// Records the decision on the mod log or review post and removes its
+/// buttons.
+pub(crate) async fn decide(ctx: &Context, component: &MessageComponentInteraction, decision: &str) {
+    let content = format!(
+        "{}\n{} by {}",
+        component.message.content,
+        decision,
+        component.user.tag()
+    );
+    let res = component
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.content(content).components(|c| c))
+        })
+        .await;
+    if let Err(why) = res {
+        println!("Error recording decision {:?}", why);
+    }
+}
+
+/// Whether the user pressing a mod log or review button may decide on it,
+/// telling them off if not.
+pub(crate) async fn is_moderator(ctx: &Context, component: &MessageComponentInteraction) -> bool {
+    let allowed = component
+        .member
+        .as_ref()
+        .and_then(|m| m.permissions)
+        .is_some_and(|p| p.manage_messages());
+    if !allowed {
+        let res = component
+            .create_interaction_response(&ctx.http, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|d| {
+                        d.content("Only moderators can decide this.")
+                            .flags(MessageFlags::EPHEMERAL)
+                    })
+            })
+            .await;
+        if let Err(why) = res {
+            println!("Error replying to moderator button {:?}", why);
+        }
+    }
+    allowed
+}
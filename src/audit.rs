@@ -0,0 +1,104 @@
+use serde_json::Value;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::bus::{self, BusEvent};
+use crate::config::Config;
+use crate::scheduler::now;
+use crate::storage::{storage, AuditRecord};
+
+/// Settings holding API tokens, which the audit log doesn't show.
+const SECRET_FIELDS: &[&str] = &[
+    "gist_token",
+    "api_token",
+    "mastodon",
+    "llm",
+    "toxicity",
+    "illustrations",
+    "tts",
+];
+
+/// State the bot keeps in the config itself, rather than settings.
+const INTERNAL_FIELDS: &[&str] = &[
+    "compiled",
+    "next_story_number",
+    "approved_words",
+    "xp_progress",
+    "last_prompt_at",
+    "last_digest_at",
+    "announced_event",
+    "rules_pins",
+    "welcomed",
+    "removed_at",
+];
+
+/// Longest value shown in the audit log, keeping it within a message.
+const MAX_VALUE_LEN: usize = 60;
+
+/// Changes listed by `one-word audit`.
+pub const AUDIT_LIMIT: usize = 10;
+
+/// Records every setting that differs between `before` and `after` as
+/// changed by `user`.
+pub async fn record(
+    data: &RwLock<TypeMap>,
+    guild_id: GuildId,
+    user: &str,
+    user_id: Option<UserId>,
+    before: &Config,
+    after: &Config,
+) {
+    let (before, after) = match (serde_json::to_value(before), serde_json::to_value(after)) {
+        (Ok(Value::Object(before)), Ok(Value::Object(after))) => (before, after),
+        _ => return,
+    };
+
+    let storage = storage(data).await;
+    let mut fields = Vec::new();
+    for (field, new) in after.iter() {
+        let old = before.get(field).unwrap_or(&Value::Null);
+        if old == new || INTERNAL_FIELDS.contains(&field.as_str()) {
+            continue;
+        }
+        fields.push(field.clone());
+        storage
+            .record_audit(AuditRecord {
+                guild_id,
+                user: user.to_string(),
+                user_id,
+                field: field.clone(),
+                old: show(field, old),
+                new: show(field, new),
+                at: now(),
+            })
+            .await;
+    }
+    if !fields.is_empty() {
+        bus::publish(data, BusEvent::ConfigChanged { guild_id, fields }).await;
+    }
+}
+
+fn show(field: &str, value: &Value) -> String {
+    if SECRET_FIELDS.contains(&field) && !value.is_null() {
+        return "(hidden)".to_string();
+    }
+    let text = value.to_string();
+    match text.char_indices().nth(MAX_VALUE_LEN) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+/// The audit log as a Discord message, newest change first.
+pub fn render(records: &[AuditRecord]) -> String {
+    records
+        .iter()
+        .map(|r| {
+            format!(
+                "<t:{}:R> **{}** changed `{}`: `{}` → `{}`",
+                r.at, r.user, r.field, r.old, r.new
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,90 @@
+use serenity::http::Http;
+use serenity::model::prelude::*;
+
+use crate::config::Config;
+use crate::storage::Stats;
+
+/// Bumped when backups change in a way older versions can't restore.
+const VERSION: u32 = 1;
+
+/// Everything `one-word backup` saves about a guild.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Backup {
+    pub version: u32,
+    pub guild_id: GuildId,
+    pub config: Config,
+    /// Compiled stories pinned in the story channels. They are kept for the
+    /// record, restoring brings back the config and stats.
+    pub stories: Vec<ArchivedStory>,
+    pub stats: Stats,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct ArchivedStory {
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub title: String,
+    pub text: String,
+    pub posted_at: Timestamp,
+}
+
+/// Collects the backup of `guild_id`, whose bot user is `bot_id`.
+pub async fn create(
+    http: &Http,
+    bot_id: UserId,
+    guild_id: GuildId,
+    config: Config,
+    stats: Stats,
+) -> Backup {
+    let channels = std::iter::once(config.channel_id)
+        .chain(config.channels.keys().copied())
+        .filter(|c| c.0 != 0);
+
+    let mut stories = Vec::new();
+    for channel_id in channels {
+        let pins = match channel_id.pins(http).await {
+            Ok(pins) => pins,
+            Err(why) => {
+                println!("Error fetching pins {:?}", why);
+                continue;
+            }
+        };
+        for m in pins.iter().filter(|m| m.author.id == bot_id) {
+            let title = m.embeds.first().and_then(|e| e.title.clone());
+            let text: Vec<&str> = m
+                .embeds
+                .iter()
+                .filter_map(|e| e.description.as_deref())
+                .collect();
+            stories.push(ArchivedStory {
+                channel_id,
+                message_id: m.id,
+                title: title.unwrap_or_default(),
+                text: text.join(" "),
+                posted_at: m.timestamp,
+            });
+        }
+    }
+
+    Backup {
+        version: VERSION,
+        guild_id,
+        config,
+        stories,
+        stats,
+    }
+}
+
+/// Reads a backup file made by [`create`] for `guild_id`. Backups of other
+/// guilds would bring in their channels, admins and roles. Errors are
+/// translation keys.
+pub fn parse(file: &[u8], guild_id: GuildId) -> Result<Backup, &'static str> {
+    let backup: Backup = serde_json::from_slice(file).map_err(|_| "backup-invalid")?;
+    if backup.version > VERSION {
+        return Err("backup-too-new");
+    }
+    if backup.guild_id != guild_id {
+        return Err("backup-other-guild");
+    }
+    Ok(backup)
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+use serenity::http::Http;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::CacheAndHttp;
+
+use crate::bus::{self, BusEvent};
+use crate::config::{find_bridged_channel, get_config, set_config};
+use crate::feed::{publish, subscribe, FeedEvent, Source};
+use crate::review;
+use crate::storage;
+use crate::story;
+use crate::{generate_story, StoryEnd};
+
+const WEBHOOK_NAME: &str = "One Word Story bridge";
+
+/// Posts words written on bridged platforms in their Discord story channel,
+/// and compiles the story when a bridged platform asks for it.
+pub async fn relay(data: Arc<RwLock<TypeMap>>, cache_and_http: Arc<CacheAndHttp>) {
+    let http = &cache_and_http.http;
+    let mut rx = subscribe(&data).await;
+    let mut webhooks: HashMap<ChannelId, Webhook> = HashMap::new();
+
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return,
+        };
+
+        let (content, username) = match &event {
+            FeedEvent::Word {
+                word,
+                author,
+                source,
+                ..
+            } if *source != Source::Discord => (word.clone(), format!("{} ({})", author, source)),
+            FeedEvent::Compile { .. } => (".".to_string(), WEBHOOK_NAME.to_string()),
+            _ => continue,
+        };
+
+        let channel_id = event.channel_id();
+        let guild_id = match find_bridged_channel(&data, channel_id).await {
+            Some((guild_id, _)) => guild_id,
+            None => continue,
+        };
+
+        let webhook = match webhooks.get(&channel_id) {
+            Some(webhook) => webhook.clone(),
+            None => match webhook(http, &data, guild_id, channel_id).await {
+                Some(webhook) => {
+                    webhooks.insert(channel_id, webhook.clone());
+                    webhook
+                }
+                None => continue,
+            },
+        };
+
+        let posted = webhook
+            .execute(http, true, |w| w.content(content).username(username))
+            .await;
+        match (posted, &event) {
+            (Ok(Some(word)), FeedEvent::Word { .. }) => {
+                story::accept(&data, &word).await;
+                bus::publish(&data, BusEvent::word(guild_id, &word)).await;
+                storage::record_contribution(&data, guild_id, &word, None).await;
+            }
+            (Ok(Some(end)), FeedEvent::Compile { .. }) => {
+                let cache = &cache_and_http.cache;
+                let end = StoryEnd::Marker(end.id);
+                generate_story(http, cache, &data, channel_id, end, guild_id).await;
+            }
+            (Err(why), _) => println!("Error relaying bridged word {:?}", why),
+            _ => {}
+        }
+    }
+}
+
+/// Posts `word` in the story channel in the name of `user`. It goes through
+/// the bridge webhook, so it counts as a contribution to the story.
+pub(crate) async fn post_as(
+    http: &Http,
+    data: &RwLock<TypeMap>,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    word: String,
+    user: &User,
+) -> bool {
+    let webhook = match webhook(http, data, guild_id, channel_id).await {
+        Some(webhook) => webhook,
+        None => return false,
+    };
+
+    let posted = webhook
+        .execute(http, true, |w| {
+            w.content(&word)
+                .username(&user.name)
+                .avatar_url(user.face())
+        })
+        .await;
+    match posted {
+        Ok(Some(posted)) => {
+            story::accept(data, &posted).await;
+            bus::publish(data, BusEvent::word(guild_id, &posted)).await;
+            storage::record_contribution(data, guild_id, &posted, Some(user)).await;
+            review::count_accepted(data, guild_id, user.id).await;
+        }
+        Ok(None) => {}
+        Err(why) => {
+            println!("Error posting word as {} {:?}", user.tag(), why);
+            return false;
+        }
+    }
+
+    publish(
+        data,
+        FeedEvent::Word {
+            channel_id,
+            word,
+            author: user.name.clone(),
+            source: Source::Discord,
+        },
+    )
+    .await;
+    true
+}
+
+/// Notes `id` as the bridge webhook of `channel_id`.
+async fn keep(data: &RwLock<TypeMap>, guild_id: GuildId, channel_id: ChannelId, id: WebhookId) {
+    let config = get_config(data, guild_id).await;
+    if config
+        .bridge
+        .as_ref()
+        .and_then(|b| b.webhooks.get(&channel_id))
+        == Some(&id)
+    {
+        return;
+    }
+    set_config(data, guild_id, |config| {
+        let bridge = config.bridge.get_or_insert_with(Default::default);
+        bridge.webhooks.insert(channel_id, id);
+        if bridge.webhook_id == Some(id) {
+            bridge.webhook_id = None;
+        }
+    })
+    .await;
+}
+
+/// Fetches the bridge webhook of `channel_id`, creating it on first use.
+async fn webhook(
+    http: &Http,
+    data: &RwLock<TypeMap>,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> Option<Webhook> {
+    let existing = get_config(data, guild_id)
+        .await
+        .bridge
+        .and_then(|b| b.webhook(channel_id));
+    if let Some(id) = existing {
+        if let Ok(webhook) = http.get_webhook(id.0).await {
+            if webhook.channel_id == Some(channel_id) {
+                keep(data, guild_id, channel_id, id).await;
+                return Some(webhook);
+            }
+        }
+    }
+
+    match channel_id.create_webhook(http, WEBHOOK_NAME).await {
+        Ok(webhook) => {
+            keep(data, guild_id, channel_id, webhook.id).await;
+            Some(webhook)
+        }
+        Err(why) => {
+            println!("Error creating bridge webhook {:?}", why);
+            None
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::env;
+use std::io;
+use std::time::Duration;
+
+use reqwest::Url;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+/// The Redis channel, or NATS subject, events are published to.
+const CHANNEL: &str = "one-word-story";
+
+/// Events kept while the bus is unreachable. Newer ones are dropped.
+const QUEUE_LIMIT: usize = 1024;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Something that happened, as published to the event bus as JSON.
+#[derive(serde::Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BusEvent {
+    WordAccepted {
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        message_id: MessageId,
+        author_id: UserId,
+        word: String,
+    },
+    /// A message was deleted from a channel, whether or not it was a word
+    /// of a story.
+    WordDeleted {
+        channel_id: ChannelId,
+        message_id: MessageId,
+    },
+    StoryCompleted {
+        guild_id: GuildId,
+        channel_id: ChannelId,
+        message_id: Option<MessageId>,
+        number: u64,
+        title: String,
+        story: String,
+    },
+    /// Settings changed, named as in the audit log.
+    ConfigChanged {
+        guild_id: GuildId,
+        fields: Vec<String>,
+    },
+}
+
+impl BusEvent {
+    pub fn word(guild_id: GuildId, msg: &Message) -> Self {
+        BusEvent::WordAccepted {
+            guild_id,
+            channel_id: msg.channel_id,
+            message_id: msg.id,
+            author_id: msg.author.id,
+            word: msg.content.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Protocol {
+    Redis,
+    Nats,
+}
+
+pub struct BusContainer;
+impl TypeMapKey for BusContainer {
+    type Value = Option<mpsc::Sender<BusEvent>>;
+}
+
+/// Starts publishing to the bus at `EVENT_BUS_URL`, a `redis://` or
+/// `nats://` URL with optional credentials, if it is set.
+pub fn from_env() -> Option<mpsc::Sender<BusEvent>> {
+    let url = env::var("EVENT_BUS_URL").ok()?;
+    let url = match Url::parse(&url) {
+        Ok(url) => url,
+        Err(why) => {
+            println!("Invalid EVENT_BUS_URL {:?}", why);
+            return None;
+        }
+    };
+    let protocol = match url.scheme() {
+        "redis" => Protocol::Redis,
+        "nats" => Protocol::Nats,
+        scheme => {
+            println!("Unsupported event bus {}", scheme);
+            return None;
+        }
+    };
+    let (tx, rx) = mpsc::channel(QUEUE_LIMIT);
+    tokio::spawn(run(url, protocol, rx));
+    Some(tx)
+}
+
+/// Publishes `event`, if there is a bus.
+pub async fn publish(data: &RwLock<TypeMap>, event: BusEvent) {
+    let data = data.read().await;
+    let tx = data
+        .get::<BusContainer>()
+        .expect("Expected BusContainer in TypeMap");
+    // A full queue means the bus has been down for a while.
+    if let Some(tx) = tx {
+        let _ = tx.try_send(event);
+    }
+}
+
+async fn run(url: Url, protocol: Protocol, mut rx: mpsc::Receiver<BusEvent>) {
+    loop {
+        match connect(&url, protocol).await {
+            Ok((lines, writer)) => match forward(lines, writer, protocol, &mut rx).await {
+                Ok(()) => return,
+                Err(why) => println!("Event bus error {:?}", why),
+            },
+            Err(why) => println!("Error connecting to event bus {:?}", why),
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+type Lines = tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>;
+
+async fn connect(url: &Url, protocol: Protocol) -> io::Result<(Lines, OwnedWriteHalf)> {
+    let host = url.host_str().unwrap_or("localhost");
+    let port = url.port().unwrap_or(match protocol {
+        Protocol::Redis => 6379,
+        Protocol::Nats => 4222,
+    });
+    let (reader, mut writer) = TcpStream::connect((host, port)).await?.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let user = Some(url.username()).filter(|u| !u.is_empty());
+
+    match (protocol, url.password()) {
+        (Protocol::Redis, Some(password)) => {
+            let mut auth = vec!["AUTH"];
+            auth.extend(user);
+            auth.push(password);
+            writer.write_all(resp(&auth).as_bytes()).await?;
+        }
+        (Protocol::Redis, None) => {}
+        (Protocol::Nats, password) => {
+            // The server introduces itself before anything else.
+            lines.next_line().await?;
+            let connect = serde_json::json!({
+                "verbose": false,
+                "pedantic": false,
+                "name": "one-word-story",
+                "user": user,
+                "pass": password,
+            });
+            writer
+                .write_all(format!("CONNECT {}\r\n", connect).as_bytes())
+                .await?;
+        }
+    }
+    Ok((lines, writer))
+}
+
+/// Publishes events until there are no more, answering the server on the
+/// way.
+async fn forward(
+    mut lines: Lines,
+    mut writer: OwnedWriteHalf,
+    protocol: Protocol,
+    rx: &mut mpsc::Receiver<BusEvent>,
+) -> io::Result<()> {
+    loop {
+        tokio::select! {
+            line = lines.next_line() => match line? {
+                Some(line) if line == "PING" => writer.write_all(b"PONG\r\n").await?,
+                Some(line) if line.starts_with('-') => println!("Event bus replied {}", line),
+                Some(_) => {}
+                None => return Err(io::ErrorKind::UnexpectedEof.into()),
+            },
+            event = rx.recv() => match event {
+                Some(event) => {
+                    let json = serde_json::to_string(&event).unwrap();
+                    let frame = match protocol {
+                        Protocol::Redis => resp(&["PUBLISH", CHANNEL, &json]),
+                        Protocol::Nats => format!("PUB {} {}\r\n{}\r\n", CHANNEL, json.len(), json),
+                    };
+                    writer.write_all(frame.as_bytes()).await?;
+                }
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+/// `args` as a Redis command.
+fn resp(args: &[&str]) -> String {
+    let mut command = format!("*{}\r\n", args.len());
+    for arg in args {
+        command += &format!("${}\r\n{}\r\n", arg.len(), arg);
+    }
+    command
+}
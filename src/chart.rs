@@ -0,0 +1,106 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serenity::model::Timestamp;
+
+use crate::card::{line_height, Canvas, INK, MUTED};
+
+const WIDTH: usize = 800;
+const PADDING: usize = 40;
+const SCALE: usize = 2;
+/// Characters of a label shown before its bar.
+const LABEL_COLUMNS: usize = 12;
+/// Characters kept free after the longest bar for its value.
+const VALUE_COLUMNS: usize = 7;
+/// Most bars in a panel, so the chart stays readable in Discord.
+pub const MAX_BARS: usize = 31;
+
+const DAY: i64 = 24 * 60 * 60;
+
+const BAR: [u8; 3] = [0x4f, 0x7c, 0xa8];
+
+/// A titled bar chart, one bar per label.
+pub struct Panel {
+    pub title: String,
+    pub bars: Vec<(String, usize)>,
+}
+
+/// Renders `panels` as a PNG, one under the other. Each has a horizontal
+/// bar per label, scaled to the panel's largest value.
+pub fn render(panels: &[Panel]) -> Vec<u8> {
+    let row = line_height(SCALE);
+    let rows: usize = panels.iter().map(|p| p.bars.len().min(MAX_BARS) + 2).sum();
+    let mut canvas = Canvas::new(WIDTH, PADDING * 2 + rows * row);
+
+    let glyph = 8 * SCALE;
+    let bar_x = PADDING + (LABEL_COLUMNS + 1) * glyph;
+    let bar_width = WIDTH - PADDING - bar_x - VALUE_COLUMNS * glyph;
+    let mut y = PADDING;
+    for panel in panels {
+        canvas.text(PADDING, y, &panel.title, SCALE, INK);
+        y += row;
+        let max = panel.bars.iter().map(|(_, v)| *v).max().unwrap_or(0).max(1);
+        for (label, value) in panel.bars.iter().take(MAX_BARS) {
+            let label: String = label.chars().take(LABEL_COLUMNS).collect();
+            canvas.text(PADDING, y, &label, SCALE, MUTED);
+            let width = match value * bar_width / max {
+                0 if *value > 0 => 1,
+                width => width,
+            };
+            canvas.rect(bar_x, y, width, glyph, BAR);
+            canvas.text(bar_x + width + SCALE * 4, y, &value.to_string(), SCALE, INK);
+            y += row;
+        }
+        y += row;
+    }
+
+    canvas.png()
+}
+
+/// The UTC date of the unix time `at`, like 2024-05-17.
+pub fn day(at: i64) -> String {
+    Timestamp::from_unix_timestamp(at)
+        .ok()
+        .and_then(|t| t.to_string().get(..10).map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// How many of `times` fall on each day, oldest day first.
+pub fn per_day(times: impl IntoIterator<Item = i64>) -> Vec<(String, usize)> {
+    let mut days = BTreeMap::new();
+    for at in times {
+        *days.entry(day(at)).or_insert(0) += 1;
+    }
+    days.into_iter().collect()
+}
+
+/// How many of `times` fall on each of the `days` days up to and including
+/// the one of `today`, oldest day first. Days without any are kept.
+pub fn daily(
+    times: impl IntoIterator<Item = i64>,
+    today: i64,
+    days: usize,
+) -> Vec<(String, usize)> {
+    let counts: HashMap<String, usize> = per_day(times).into_iter().collect();
+    (0..days as i64)
+        .rev()
+        .map(|ago| day(today - ago * DAY))
+        .map(|day| {
+            let count = counts.get(&day).copied().unwrap_or(0);
+            (day, count)
+        })
+        .collect()
+}
+
+/// How often each of `names` occurs, most frequent first.
+pub fn tally<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for name in names {
+        *counts.entry(name).or_insert(0) += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(name, count)| (name.to_string(), count))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
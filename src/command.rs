@@ -0,0 +1,1128 @@
+use serenity::model::prelude::*;
+
+use crate::chart::MAX_BARS;
+use crate::config::{
+    CardMode, CompoundPolicy, EmbedChange, EmojiPolicy, Enforcement, EventChange, MarkupPolicy,
+    MatchMode, QuietHours, Rule, Segmentation, Severity, SeverityAction, SeverityChange, TitleMode,
+    UserPref,
+};
+use crate::cooldown::MAX_COOLDOWN;
+use crate::game::phrase_words;
+use crate::grace::MAX_GRACE_PERIOD;
+use crate::i18n::Locale;
+use crate::illustration::ImageConfig;
+use crate::llm::LlmConfig;
+use crate::mastodon::MastodonConfig;
+use crate::storage::{HistoryKind, StoryEdit};
+use crate::templates::TemplateKind;
+use crate::toxicity::{ToxicityAction, ToxicityConfig};
+use crate::tts::TtsConfig;
+use crate::welcome::WelcomeMode;
+use crate::xp::XpConfig;
+
+/// Who may run a command. Each tier may also run the commands of the
+/// tiers below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Tier {
+    /// Members with the guild's mod role.
+    Mod,
+    /// Server admins and users on the guild's admin list.
+    Admin,
+    /// The guild owner.
+    Owner,
+}
+
+impl Tier {
+    /// Translation key of the reply to members below this tier.
+    pub fn denied_key(self) -> &'static str {
+        match self {
+            Tier::Mod => "mods-only",
+            Tier::Admin => "admins-only",
+            Tier::Owner => "owner-only",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Command {
+    SetChannel(ChannelId),
+    AddChannel(ChannelId),
+    RemoveChannel(ChannelId),
+    BanWord(String),
+    UnbanWord(String),
+    SetGistToken(Option<String>),
+    SetApiToken(Option<String>),
+    SetMastodon(Option<MastodonConfig>),
+    SetMastodonTemplate(String),
+    SetLlm(Option<LlmConfig>),
+    SetSummaries(bool),
+    SetTitleMode(TitleMode),
+    SetNextTitle(String),
+    Prompt,
+    /// Sends the author every compiled story by DM.
+    Subscribe,
+    Unsubscribe,
+    /// Shows the author's preferences, or changes one of them.
+    Prefs(Option<(UserPref, bool)>),
+    SetDailyPrompt(bool),
+    SetDictionary(bool),
+    SetTopicUpdates(bool),
+    SetNativeSlowmode(bool),
+    AddSensitiveWord(String),
+    /// Changes the words or action of a severity tier.
+    Severity(Severity, SeverityChange),
+    RemoveSensitiveWord(String),
+    SetMarkGaps(bool),
+    /// Keeps records of a kind for the given seconds, or for good.
+    SetRetention(HistoryKind, Option<u64>),
+    SetStoryCharts(bool),
+    AddChallenge(String),
+    CancelChallenge(String),
+    ListChallenges,
+    /// Changes the guild's event with the given name.
+    Event(String, EventChange),
+    /// Starts a sprint lasting the number of seconds.
+    Sprint(u64),
+    /// Turns contributions away in the story channel, or the main one,
+    /// until the game is resumed.
+    Pause,
+    Resume,
+    SetRaidProtection(bool),
+    /// Seconds an account must have existed before contributing, 0 for
+    /// any.
+    SetMinAccountAge(u64),
+    /// Seconds a member must have been in the guild before contributing.
+    SetMinMemberAge(u64),
+    /// Number of a user's first words that need a moderator's approval, 0
+    /// for none.
+    SetApproval(usize),
+    /// Quietly deletes the user's messages for the number of seconds, or
+    /// lifts their mute.
+    ShadowMute(UserId, Option<u64>),
+    /// Shows the guild's all-time stats.
+    Stats,
+    /// Shows how the archived story with the number, or posted in the
+    /// message, came about, or the latest one. With a revision, shows that
+    /// version of its text.
+    ArchiveGet(Option<u64>, Option<usize>),
+    /// Edits the words of the archived story with the number, or posted in
+    /// the message.
+    EditStory(u64, StoryEdit),
+    /// Posts the archived story with the number, or posted in the message,
+    /// again in the current style.
+    Recompile(u64),
+    /// Joins two archived stories, each given by number or message.
+    Merge(u64, u64),
+    /// Shows how far the story in progress has come.
+    Status,
+    /// Shows how to play in the channel.
+    ShowRules,
+    /// Pins how to play in the channel, and keeps it up to date.
+    PinRules,
+    UnpinRules,
+    /// Shows the level card of the user, or of the author.
+    Rank(Option<UserId>),
+    SetXp(Option<XpConfig>),
+    /// Charts the words added in the guild over the last number of days.
+    Activity(usize),
+    SetModLogChannel(ChannelId),
+    SetReviewChannel(Option<ChannelId>),
+    SetDigestChannel(Option<ChannelId>),
+    SetQuietHours(Option<QuietHours>),
+    /// Minutes the guild's time zone is ahead of UTC.
+    SetTimezone(i32),
+    /// Seconds of quiet before nudging story channels, and the role to ping.
+    SetNudge(u64, Option<RoleId>),
+    SetToxicity(Option<ToxicityConfig>),
+    SetIllustrations(Option<ImageConfig>),
+    SetTts(Option<TtsConfig>),
+    SetStoryCards(CardMode),
+    SetEndReaction(Option<String>),
+    SetWelcome(WelcomeMode),
+    /// Changes the look of compiled stories.
+    Embed(EmbedChange),
+    SetLocale(Locale),
+    SetMatchMode(MatchMode),
+    SetGracePeriod(u64),
+    SetEnforcement(Enforcement),
+    SetEmojiPolicy(EmojiPolicy),
+    /// Sets a rule for the guild, or for a single channel.
+    SetRule(Option<ChannelId>, Rule),
+    SetPrefix(String),
+    SetTemplate(TemplateKind, Option<String>),
+    Narrate(MessageId),
+    BridgeTelegram(i64),
+    BridgeMatrix(String),
+    Unbridge,
+    Backup,
+    /// DMs the author CSVs of the guild's contributors and stories.
+    ExportStats,
+    Audit,
+    /// Shows how many words were deleted for each reason.
+    ModStats,
+    /// Deletes the bot's duplicate and outdated pins in the channel.
+    CleanupPins,
+    AddAdmin(UserId),
+    RemoveAdmin(UserId),
+    SetModRole(Option<RoleId>),
+    /// Restores the backup attached to the command.
+    Restore,
+    /// Starts the setup wizard.
+    Setup,
+}
+
+/// Shortest quiet before a nudge, in seconds. Channels are checked every
+/// 10 minutes anyway.
+const MIN_NUDGE: u64 = 10 * 60;
+
+/// Shortest time records are kept for, in seconds.
+const MIN_RETENTION: u64 = 24 * 60 * 60;
+
+const RETENTION_USAGE: &str = "Usage: one-word retention <contributions|audit> <duration|off>";
+const ARCHIVE_USAGE: &str = "Usage: one-word archive <get [number|story-message-id|latest] [--revision n]|edit <number|story-message-id> <remove word|replace word new>>";
+const ARCHIVE_EDIT_USAGE: &str =
+    "Usage: one-word archive edit <number|story-message-id> <remove word|replace word new>";
+
+/// Shortest and longest sprints, in seconds.
+const MIN_SPRINT: u64 = 60;
+const MAX_SPRINT: u64 = 3 * 60 * 60;
+
+/// Most different contributors a story can be made to wait for.
+const MAX_MIN_CONTRIBUTORS: usize = 25;
+
+/// Highest cap on one author's words in a story. Stories are compiled from
+/// up to 250 messages.
+const MAX_PER_USER: usize = 250;
+
+/// Longest embed footer Discord accepts.
+const MAX_FOOTER: usize = 2048;
+
+/// Days charted by `one-word activity` without an argument.
+const DEFAULT_ACTIVITY_DAYS: usize = 30;
+
+/// Seconds a shadow mute lasts without a duration.
+const DEFAULT_SHADOW_MUTE: u64 = 7 * 24 * 60 * 60;
+
+/// Prefix of commands in guilds that haven't picked their own.
+pub const DEFAULT_PREFIX: &str = "one-word";
+
+const USAGE: &str = "Usage: one-word <setup|set|set-channel|add-channel|remove-channel|ban|unban|severity|dictionary|topic|slowmode|content-warning|censor-mode|grace-period|enforcement|emoji|gist|api-token|mastodon|mastodon-template|template|bridge|backup|restore|export-stats|audit|modstats|cleanup-pins|archive|recompile|merge|status|rules|stats|rank|xp|activity|sprint|pause|resume|raid-protection|approval|shadowmute|account-age|member-age|admin|mod-role|llm|summaries|ai-titles|title|daily-prompt|timezone|quiet-hours|event|challenge|digest|nudge|gaps|retention|set-log-channel|review-channel|toxicity|illustrations|tts|narrate|cards|embed|end-reaction|welcome|charts|prompt|subscribe|unsubscribe|prefs> <args>";
+
+impl Command {
+    /// The lowest tier allowed to run the command, `None` if anyone may.
+    /// Settings that can lock the game or lose data are kept to the owner.
+    pub fn tier(&self) -> Option<Tier> {
+        let tier = match self {
+            Command::Prompt
+            | Command::Narrate(_)
+            | Command::Subscribe
+            | Command::Unsubscribe
+            | Command::Prefs(_)
+            | Command::ListChallenges
+            | Command::Stats
+            | Command::Status
+            | Command::ShowRules
+            | Command::ArchiveGet(..)
+            | Command::Rank(_) => return None,
+            Command::BanWord(_)
+            | Command::UnbanWord(_)
+            | Command::SetNextTitle(_)
+            | Command::Audit
+            | Command::ModStats
+            | Command::EditStory(..)
+            | Command::Activity(_)
+            | Command::Sprint(_)
+            | Command::Pause
+            | Command::Resume
+            | Command::ShadowMute(..)
+            | Command::AddChallenge(_)
+            | Command::CancelChallenge(_)
+            | Command::AddSensitiveWord(_)
+            | Command::RemoveSensitiveWord(_)
+            | Command::Severity(_, SeverityChange::Add(_) | SeverityChange::Remove(_)) => Tier::Mod,
+            Command::SetEnforcement(_)
+            | Command::SetRule(_, Rule::Enforcement(_))
+            | Command::AddAdmin(_)
+            | Command::RemoveAdmin(_)
+            | Command::SetApiToken(_)
+            | Command::SetRetention(..)
+            | Command::Restore => Tier::Owner,
+            _ => Tier::Admin,
+        };
+        Some(tier)
+    }
+}
+
+/// Parses `content` if it is a command, i.e. starts with `prefix` as a word
+/// of its own or glued to the subcommand, as in `!prompt`. Usage errors
+/// show the default prefix, see [`with_prefix`].
+pub fn parse_command(content: &str, prefix: &str) -> Option<Result<Command, &'static str>> {
+    // Guilds may have saved a blank prefix before it was rejected.
+    let prefix = match prefix.trim() {
+        "" => DEFAULT_PREFIX,
+        prefix => prefix,
+    };
+    content.split_whitespace().next()?.strip_prefix(prefix)?;
+
+    let words = match tokenize(content) {
+        Ok(words) => words,
+        Err(error) => return Some(Err(error)),
+    };
+    let glued = words.first()?.strip_prefix(prefix)?;
+    let (name, args) = match glued {
+        "" => match words.get(1) {
+            Some(name) => (name.to_lowercase(), &words[2..]),
+            None => return Some(Err(USAGE)),
+        },
+        name => (name.to_lowercase(), &words[1..]),
+    };
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    Some(parse_subcommand(&name, &args))
+}
+
+fn parse_subcommand(name: &str, args: &[&str]) -> Result<Command, &'static str> {
+    match name {
+        "prompt" => match args {
+            [] => Ok(Command::Prompt),
+            _ => Err("prompt takes no arguments"),
+        },
+        "set-channel" => match args {
+            [channel] => parse_channel(channel).map(Command::SetChannel),
+            _ => None,
+        }
+        .ok_or("set-channel requires a #channel mention"),
+        "add-channel" => match args {
+            [channel] => parse_channel(channel).map(Command::AddChannel),
+            _ => None,
+        }
+        .ok_or("add-channel requires a #channel mention"),
+        "remove-channel" => match args {
+            [channel] => parse_channel(channel).map(Command::RemoveChannel),
+            _ => None,
+        }
+        .ok_or("remove-channel requires a #channel mention"),
+        "nudge" => match args {
+            ["off"] => Ok(Command::SetNudge(0, None)),
+            [after] => parse_nudge(after).map(|after| Command::SetNudge(after, None)),
+            [after, role] => parse_nudge(after).and_then(|after| {
+                parse_role(role)
+                    .map(|role| Command::SetNudge(after, Some(role)))
+                    .ok_or("nudge takes a @role mention to ping")
+            }),
+            _ => Err("Usage: one-word nudge <duration|off> [@role]"),
+        },
+        "digest" => match args {
+            ["off"] => Ok(Command::SetDigestChannel(None)),
+            [channel] => parse_channel(channel)
+                .map(|channel| Command::SetDigestChannel(Some(channel)))
+                .ok_or("digest requires a #channel mention, or off"),
+            _ => Err("Usage: one-word digest <#channel|off>"),
+        },
+        "review-channel" => match args {
+            ["off"] => Ok(Command::SetReviewChannel(None)),
+            [channel] => parse_channel(channel)
+                .map(|channel| Command::SetReviewChannel(Some(channel)))
+                .ok_or("review-channel requires a #channel mention, or off"),
+            _ => Err("Usage: one-word review-channel <#channel|off>"),
+        },
+        "set-log-channel" => match args {
+            [channel] => parse_channel(channel).map(Command::SetModLogChannel),
+            _ => None,
+        }
+        .ok_or("set-log-channel requires a #channel mention"),
+        "set" => match args {
+            ["language", code] => Locale::parse(code)
+                .map(Command::SetLocale)
+                .ok_or("Unknown language, pick one of en, de, fr or es"),
+            ["language", ..] => Err("Usage: one-word set language <en|de|fr|es>"),
+            ["prefix", prefix] if !prefix.is_empty() && !prefix.contains(char::is_whitespace) => {
+                Ok(Command::SetPrefix(prefix.to_string()))
+            }
+            ["prefix", ..] => Err("set prefix requires exactly one prefix, without spaces"),
+            ["--channel", channel, key, value] => {
+                let channel = parse_channel(channel).ok_or("--channel requires a #channel mention")?;
+                parse_rule(key, value).map(|rule| Command::SetRule(Some(channel), rule))
+            }
+            [key, value] if *key != "--channel" => {
+                parse_rule(key, value).map(|rule| Command::SetRule(None, rule))
+            }
+            _ => Err("Usage: one-word set [--channel #channel] <language|prefix|match-mode|max-words|min-contributors|max-per-user|segmentation|hyphens|apostrophes|cooldown|compile-cooldown|enforcement|emoji|markup> <value>"),
+        },
+        "ban" => match args {
+            [word] => Ok(Command::BanWord(word.to_string())),
+            _ => Err("ban requires one word, or a \"quoted phrase\""),
+        },
+        "unban" => match args {
+            [word] => Ok(Command::UnbanWord(word.to_string())),
+            _ => Err("unban requires one word, or a \"quoted phrase\""),
+        },
+        "censor-mode" => match args {
+            ["substring"] => Ok(Command::SetMatchMode(MatchMode::Substring)),
+            ["whole-word"] => Ok(Command::SetMatchMode(MatchMode::WholeWord)),
+            ["word-boundary"] => Ok(Command::SetMatchMode(MatchMode::WordBoundary)),
+            _ => Err("Usage: one-word censor-mode <substring|whole-word|word-boundary>"),
+        },
+        "grace-period" => match args {
+            ["off"] => Ok(Command::SetGracePeriod(0)),
+            [seconds] => seconds
+                .parse()
+                .ok()
+                .filter(|s| *s <= MAX_GRACE_PERIOD)
+                .map(Command::SetGracePeriod)
+                .ok_or("The grace period must be a number of seconds, up to 300"),
+            _ => Err("Usage: one-word grace-period <seconds|off>"),
+        },
+        "enforcement" => match args {
+            ["delete"] => Ok(Command::SetEnforcement(Enforcement::Delete)),
+            ["sanitize"] => Ok(Command::SetEnforcement(Enforcement::Sanitize)),
+            _ => Err("Usage: one-word enforcement <delete|sanitize>"),
+        },
+        "emoji" => match args {
+            ["allow"] => Ok(Command::SetEmojiPolicy(EmojiPolicy::Allow)),
+            ["strip"] => Ok(Command::SetEmojiPolicy(EmojiPolicy::Strip)),
+            ["reject"] => Ok(Command::SetEmojiPolicy(EmojiPolicy::Reject)),
+            _ => Err("Usage: one-word emoji <allow|strip|reject>"),
+        },
+        "gist" => match args {
+            ["off"] => Ok(Command::SetGistToken(None)),
+            [token] => Ok(Command::SetGistToken(Some(token.to_string()))),
+            _ => Err("Usage: one-word gist <github-token|off>"),
+        },
+        "api-token" => match args {
+            ["off"] => Ok(Command::SetApiToken(None)),
+            [token] => Ok(Command::SetApiToken(Some(token.to_string()))),
+            _ => Err("Usage: one-word api-token <token|off>"),
+        },
+        "mastodon" => match args {
+            ["off"] => Ok(Command::SetMastodon(None)),
+            [instance, token] => Ok(Command::SetMastodon(Some(MastodonConfig {
+                instance: instance.to_string(),
+                access_token: token.to_string(),
+                template: None,
+                max_chars: None,
+            }))),
+            _ => Err("Usage: one-word mastodon <instance-url> <access-token>"),
+        },
+        "mastodon-template" => match args {
+            [] => Err("mastodon-template requires the toot text, e.g. \"{story} {link}\""),
+            text => Ok(Command::SetMastodonTemplate(text.join(" "))),
+        },
+        "template" => match args {
+            [kind, rest @ ..] => {
+                let kind = TemplateKind::parse(kind)
+                    .ok_or("Unknown template, pick one of deletion-dm, title or completion")?;
+                match rest {
+                    [] => Err("template requires the text, or off"),
+                    ["off"] => Ok(Command::SetTemplate(kind, None)),
+                    text => Ok(Command::SetTemplate(kind, Some(text.join(" ")))),
+                }
+            }
+            [] => Err("Usage: one-word template <deletion-dm|title|completion> <text|off>"),
+        },
+        "llm" => match args {
+            ["off"] => Ok(Command::SetLlm(None)),
+            [endpoint, api_key] | [endpoint, api_key, _] => Ok(Command::SetLlm(Some(LlmConfig {
+                endpoint: endpoint.to_string(),
+                api_key: api_key.to_string(),
+                model: args.get(2).map(|m| m.to_string()),
+            }))),
+            _ => Err("Usage: one-word llm <endpoint> <api-key> [model]"),
+        },
+        "illustrations" => match args {
+            ["off"] => Ok(Command::SetIllustrations(None)),
+            [endpoint, api_key] | [endpoint, api_key, _] => {
+                Ok(Command::SetIllustrations(Some(ImageConfig {
+                    endpoint: endpoint.to_string(),
+                    api_key: api_key.to_string(),
+                    model: args.get(2).map(|m| m.to_string()),
+                })))
+            }
+            _ => Err("Usage: one-word illustrations <endpoint> <api-key> [model]"),
+        },
+        "tts" => match args {
+            ["off"] => Ok(Command::SetTts(None)),
+            [endpoint, api_key] | [endpoint, api_key, _] => Ok(Command::SetTts(Some(TtsConfig {
+                endpoint: endpoint.to_string(),
+                api_key: api_key.to_string(),
+                voice: args.get(2).map(|v| v.to_string()),
+            }))),
+            _ => Err("Usage: one-word tts <endpoint> <api-key> [voice]"),
+        },
+        "toxicity" => match args {
+            ["off"] => Ok(Command::SetToxicity(None)),
+            [api_key, threshold, action] => {
+                let threshold = threshold
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|t| (0.0..=1.0).contains(t))
+                    .ok_or("The toxicity threshold must be a number between 0 and 1")?;
+                let action = match action.to_lowercase().as_str() {
+                    "delete" => ToxicityAction::Delete,
+                    "flag" => ToxicityAction::Flag,
+                    "log" => ToxicityAction::Log,
+                    "review" => ToxicityAction::Review,
+                    _ => return Err("The toxicity action must be delete, flag, log or review"),
+                };
+                Ok(Command::SetToxicity(Some(ToxicityConfig {
+                    api_key: api_key.to_string(),
+                    threshold,
+                    action,
+                })))
+            }
+            _ => Err(
+                "Usage: one-word toxicity <perspective-api-key> <threshold 0-1> <delete|flag|log|review>",
+            ),
+        },
+        "narrate" => match args {
+            [id] => id
+                .parse::<u64>()
+                .map(|id| Command::Narrate(MessageId(id)))
+                .map_err(|_| "narrate requires the id of a story message"),
+            _ => Err("Usage: one-word narrate <story message id>"),
+        },
+        "summaries" => match args {
+            ["on"] => Ok(Command::SetSummaries(true)),
+            ["off"] => Ok(Command::SetSummaries(false)),
+            _ => Err("Usage: one-word summaries <on|off>"),
+        },
+        "ai-titles" => match args {
+            ["off"] => Ok(Command::SetTitleMode(TitleMode::Off)),
+            ["on"] => Ok(Command::SetTitleMode(TitleMode::Suggest)),
+            ["vote"] => Ok(Command::SetTitleMode(TitleMode::Vote)),
+            _ => Err("Usage: one-word ai-titles <on|vote|off>"),
+        },
+        "cards" => match args {
+            ["off"] => Ok(Command::SetStoryCards(CardMode::Off)),
+            ["on"] => Ok(Command::SetStoryCards(CardMode::Attach)),
+            ["only"] => Ok(Command::SetStoryCards(CardMode::Only)),
+            _ => Err("Usage: one-word cards <on|only|off>"),
+        },
+        "embed" => parse_embed(args).map(Command::Embed),
+        "rules" => match args {
+            [] => Ok(Command::ShowRules),
+            ["pin"] => Ok(Command::PinRules),
+            ["unpin"] => Ok(Command::UnpinRules),
+            _ => Err("Usage: one-word rules [pin|unpin]"),
+        },
+        "welcome" => match args {
+            ["dm"] => Ok(Command::SetWelcome(WelcomeMode::Dm)),
+            ["channel"] => Ok(Command::SetWelcome(WelcomeMode::Channel)),
+            ["off"] => Ok(Command::SetWelcome(WelcomeMode::Off)),
+            _ => Err("Usage: one-word welcome <dm|channel|off>"),
+        },
+        "end-reaction" => match args {
+            ["off"] => Ok(Command::SetEndReaction(None)),
+            [emoji] => Ok(Command::SetEndReaction(Some(emoji.to_string()))),
+            _ => Err("Usage: one-word end-reaction <emoji|off>"),
+        },
+        "title" => match args {
+            [] => Err("title requires the title of the next story"),
+            title => Ok(Command::SetNextTitle(title.join(" "))),
+        },
+        "subscribe" => match args {
+            [] => Ok(Command::Subscribe),
+            _ => Err("subscribe takes no arguments"),
+        },
+        "unsubscribe" => match args {
+            [] => Ok(Command::Unsubscribe),
+            _ => Err("unsubscribe takes no arguments"),
+        },
+        "prefs" => match args {
+            [] => Ok(Command::Prefs(None)),
+            [pref, on @ ("on" | "off")] => {
+                let pref = match *pref {
+                    "deletion-dms" => UserPref::DeletionDms,
+                    "story-dms" => UserPref::StoryDms,
+                    _ => return Err("The preference must be deletion-dms or story-dms"),
+                };
+                Ok(Command::Prefs(Some((pref, *on == "on"))))
+            }
+            _ => Err("Usage: one-word prefs [<deletion-dms|story-dms> <on|off>]"),
+        },
+        "modstats" => match args {
+            [] => Ok(Command::ModStats),
+            _ => Err("modstats takes no arguments"),
+        },
+        "cleanup-pins" => match args {
+            [] => Ok(Command::CleanupPins),
+            _ => Err("cleanup-pins takes no arguments"),
+        },
+        "export-stats" => match args {
+            [] => Ok(Command::ExportStats),
+            _ => Err("export-stats takes no arguments"),
+        },
+        "dictionary" => match args {
+            ["on"] => Ok(Command::SetDictionary(true)),
+            ["off"] => Ok(Command::SetDictionary(false)),
+            _ => Err("Usage: one-word dictionary <on|off>"),
+        },
+        "topic" => match args {
+            ["on"] => Ok(Command::SetTopicUpdates(true)),
+            ["off"] => Ok(Command::SetTopicUpdates(false)),
+            _ => Err("Usage: one-word topic <on|off>"),
+        },
+        "slowmode" => match args {
+            ["on"] => Ok(Command::SetNativeSlowmode(true)),
+            ["off"] => Ok(Command::SetNativeSlowmode(false)),
+            _ => Err("Usage: one-word slowmode <on|off>"),
+        },
+        "daily-prompt" => match args {
+            ["on"] => Ok(Command::SetDailyPrompt(true)),
+            ["off"] => Ok(Command::SetDailyPrompt(false)),
+            _ => Err("Usage: one-word daily-prompt <on|off>"),
+        },
+        "severity" => parse_severity(args),
+        "content-warning" => match args {
+            ["add", words @ ..] if !words.is_empty() => {
+                Ok(Command::AddSensitiveWord(sensitive_phrase(words)))
+            }
+            ["remove", words @ ..] if !words.is_empty() => {
+                Ok(Command::RemoveSensitiveWord(sensitive_phrase(words)))
+            }
+            _ => Err("Usage: one-word content-warning <add|remove> <word or phrase>"),
+        },
+        "challenge" => match args {
+            ["list"] => Ok(Command::ListChallenges),
+            ["cancel", word] => Ok(Command::CancelChallenge(word.to_lowercase())),
+            [word] => Ok(Command::AddChallenge(word.to_lowercase())),
+            _ => Err("Usage: one-word challenge <word|list|cancel <word>>"),
+        },
+        "event" => parse_event(args),
+        "quiet-hours" => parse_quiet_hours(args),
+        "timezone" => match args {
+            [offset] => parse_utc_offset(offset)
+                .map(Command::SetTimezone)
+                .ok_or("The time zone must be an offset like UTC+2 or UTC-5:30"),
+            _ => Err("Usage: one-word timezone <UTC+H[:MM]>"),
+        },
+        "pause" => match args {
+            [] => Ok(Command::Pause),
+            _ => Err("pause takes no arguments"),
+        },
+        "approval" => match args {
+            ["off"] => Ok(Command::SetApproval(0)),
+            [count] => count
+                .parse()
+                .ok()
+                .filter(|count| *count > 0)
+                .map(Command::SetApproval)
+                .ok_or("approval takes how many of a user's first words need approving, or off"),
+            _ => Err("Usage: one-word approval <count|off>"),
+        },
+        "shadowmute" => match args {
+            [user] => parse_user(user).map(|user| Command::ShadowMute(user, Some(DEFAULT_SHADOW_MUTE))),
+            [user, "off"] => parse_user(user).map(|user| Command::ShadowMute(user, None)),
+            [user, duration] => parse_user(user)
+                .zip(parse_duration(duration))
+                .map(|(user, seconds)| Command::ShadowMute(user, Some(seconds))),
+            _ => None,
+        }
+        .ok_or("Usage: one-word shadowmute @user [duration|off]"),
+        "account-age" => match args {
+            ["off"] => Ok(Command::SetMinAccountAge(0)),
+            [age] => parse_duration(age)
+                .map(Command::SetMinAccountAge)
+                .ok_or("The age must be a duration, like 7d, or off"),
+            _ => Err("Usage: one-word account-age <duration|off>"),
+        },
+        "member-age" => match args {
+            ["off"] => Ok(Command::SetMinMemberAge(0)),
+            [age] => parse_duration(age)
+                .map(Command::SetMinMemberAge)
+                .ok_or("The age must be a duration, like 1d, or off"),
+            _ => Err("Usage: one-word member-age <duration|off>"),
+        },
+        "raid-protection" => match args {
+            ["on"] => Ok(Command::SetRaidProtection(true)),
+            ["off"] => Ok(Command::SetRaidProtection(false)),
+            _ => Err("Usage: one-word raid-protection <on|off>"),
+        },
+        "resume" => match args {
+            [] => Ok(Command::Resume),
+            _ => Err("resume takes no arguments"),
+        },
+        "sprint" => match args {
+            [duration] => parse_duration(duration)
+                .filter(|seconds| (MIN_SPRINT..=MAX_SPRINT).contains(seconds))
+                .map(Command::Sprint)
+                .ok_or("A sprint lasts from 1 minute to 3 hours, like 15m"),
+            _ => Err("Usage: one-word sprint <duration>"),
+        },
+        "archive" => match args {
+            ["get", rest @ ..] => parse_archive_get(rest),
+            ["edit", id, change @ ..] => {
+                let id = parse_story_id(id).ok_or(ARCHIVE_EDIT_USAGE)?;
+                let edit = match change {
+                    ["remove", word] => StoryEdit::Remove(word.to_string()),
+                    ["replace", word, new] => StoryEdit::Replace(word.to_string(), new.to_string()),
+                    _ => return Err(ARCHIVE_EDIT_USAGE),
+                };
+                Ok(Command::EditStory(id, edit))
+            }
+            _ => Err(ARCHIVE_USAGE),
+        },
+        "merge" => match args {
+            [a, b] => match (parse_story_id(a), parse_story_id(b)) {
+                (Some(a), Some(b)) => Ok(Command::Merge(a, b)),
+                _ => Err("merge takes two stories' numbers or the IDs of their messages"),
+            },
+            _ => Err("Usage: one-word merge <number|story-message-id> <number|story-message-id>"),
+        },
+        "recompile" => match args {
+            [id] => parse_story_id(id)
+                .map(Command::Recompile)
+                .ok_or("recompile takes a story's number or the ID of its message"),
+            _ => Err("Usage: one-word recompile <number|story-message-id>"),
+        },
+        "status" => match args {
+            [] => Ok(Command::Status),
+            _ => Err("status takes no arguments"),
+        },
+        "stats" => match args {
+            [] => Ok(Command::Stats),
+            _ => Err("stats takes no arguments"),
+        },
+        "activity" => match args {
+            [] => Ok(Command::Activity(DEFAULT_ACTIVITY_DAYS)),
+            [days] => days
+                .trim_end_matches('d')
+                .parse()
+                .ok()
+                .filter(|days| (1..=MAX_BARS).contains(days))
+                .map(Command::Activity)
+                .ok_or("activity takes a number of days up to 31, like 30d"),
+            _ => Err("Usage: one-word activity [days]"),
+        },
+        "rank" => match args {
+            [] => Ok(Command::Rank(None)),
+            [user] => parse_user(user)
+                .map(|user| Command::Rank(Some(user)))
+                .ok_or("rank takes a user mention"),
+            _ => Err("Usage: one-word rank [@user]"),
+        },
+        "xp" => match args {
+            ["off"] => Ok(Command::SetXp(None)),
+            ["on", amounts @ ..] if amounts.len() <= 3 => {
+                let mut xp = XpConfig::default();
+                let fields = [&mut xp.per_word, &mut xp.streak_bonus, &mut xp.milestone];
+                for (field, amount) in fields.into_iter().zip(amounts) {
+                    *field = amount
+                        .parse()
+                        .map_err(|_| "XP amounts must be whole numbers")?;
+                }
+                Ok(Command::SetXp(Some(xp)))
+            }
+            _ => Err("Usage: one-word xp <on [xp-per-word] [streak-bonus] [milestone-levels]|off>"),
+        },
+        "charts" => match args {
+            ["on"] => Ok(Command::SetStoryCharts(true)),
+            ["off"] => Ok(Command::SetStoryCharts(false)),
+            _ => Err("Usage: one-word charts <on|off>"),
+        },
+        "retention" => {
+            let kind = match args.first() {
+                Some(&"contributions") => HistoryKind::Contributions,
+                Some(&"audit") => HistoryKind::Audit,
+                _ => return Err(RETENTION_USAGE),
+            };
+            match args {
+                [_, "off"] => Ok(Command::SetRetention(kind, None)),
+                [_, duration] => parse_duration(duration)
+                    .filter(|keep| *keep >= MIN_RETENTION)
+                    .map(|keep| Command::SetRetention(kind, Some(keep)))
+                    .ok_or("Records must be kept for at least a day, like 90d"),
+                _ => Err(RETENTION_USAGE),
+            }
+        }
+        "gaps" => match args {
+            ["on"] => Ok(Command::SetMarkGaps(true)),
+            ["off"] => Ok(Command::SetMarkGaps(false)),
+            _ => Err("Usage: one-word gaps <on|off>"),
+        },
+        "admin" => match args {
+            ["add", user] => parse_user(user).map(Command::AddAdmin),
+            ["remove", user] => parse_user(user).map(Command::RemoveAdmin),
+            _ => None,
+        }
+        .ok_or("Usage: one-word admin <add|remove> @user"),
+        "mod-role" => match args {
+            ["off"] => Ok(Command::SetModRole(None)),
+            [role] => parse_role(role)
+                .map(|role| Command::SetModRole(Some(role)))
+                .ok_or("mod-role requires a @role mention, or off"),
+            _ => Err("Usage: one-word mod-role <@role|off>"),
+        },
+        "audit" => match args {
+            [] => Ok(Command::Audit),
+            _ => Err("audit takes no arguments"),
+        },
+        "backup" => match args {
+            [] => Ok(Command::Backup),
+            _ => Err("backup takes no arguments"),
+        },
+        "setup" => match args {
+            [] => Ok(Command::Setup),
+            _ => Err("setup takes no arguments"),
+        },
+        "restore" => match args {
+            [] => Ok(Command::Restore),
+            _ => Err("restore takes no arguments, attach the backup file instead"),
+        },
+        "bridge" => match args {
+            ["off"] => Ok(Command::Unbridge),
+            ["telegram", chat_id] => chat_id
+                .parse()
+                .map(Command::BridgeTelegram)
+                .map_err(|_| "Invalid Telegram chat id"),
+            ["matrix", room_id] => Ok(Command::BridgeMatrix(room_id.to_string())),
+            _ => Err("Usage: one-word bridge <telegram <chat-id>|matrix <room-id>|off>"),
+        },
+        _ => Err(USAGE),
+    }
+}
+
+/// Parses a rule for `one-word set`. `default` unsets the rule.
+pub fn parse_rule(key: &str, value: &str) -> Result<Rule, &'static str> {
+    let value = Some(value).filter(|v| *v != "default");
+    match key {
+        "match-mode" => match value {
+            None => Ok(None),
+            Some("substring") => Ok(Some(MatchMode::Substring)),
+            Some("whole-word") => Ok(Some(MatchMode::WholeWord)),
+            Some("word-boundary") => Ok(Some(MatchMode::WordBoundary)),
+            _ => Err("The match mode must be substring, whole-word, word-boundary or default"),
+        }
+        .map(Rule::MatchMode),
+        "max-words" => match value {
+            None => Ok(None),
+            Some(max) => max
+                .parse()
+                .ok()
+                .filter(|max| (1..=10).contains(max))
+                .map(Some)
+                .ok_or("max-words must be a number from 1 to 10, or default"),
+        }
+        .map(Rule::MaxWords),
+        "min-contributors" => match value {
+            None => Ok(None),
+            Some(min) => min
+                .parse()
+                .ok()
+                .filter(|min| (1..=MAX_MIN_CONTRIBUTORS).contains(min))
+                .map(Some)
+                .ok_or("min-contributors must be a number from 1 to 25, or default"),
+        }
+        .map(Rule::MinContributors),
+        "max-per-user" => match value {
+            None => Ok(None),
+            Some(max) => max
+                .parse()
+                .ok()
+                .filter(|max| (1..=MAX_PER_USER).contains(max))
+                .map(Some)
+                .ok_or("max-per-user must be a number from 1 to 250, or default"),
+        }
+        .map(Rule::MaxPerUser),
+        "segmentation" => match value {
+            None => Ok(None),
+            Some("whitespace") => Ok(Some(Segmentation::Whitespace)),
+            Some("unicode") => Ok(Some(Segmentation::Unicode)),
+            _ => Err("The segmentation must be whitespace, unicode or default"),
+        }
+        .map(Rule::Segmentation),
+        "hyphens" => parse_compound_policy(value).map(Rule::Hyphens),
+        "apostrophes" => parse_compound_policy(value).map(Rule::Apostrophes),
+        "cooldown" => parse_cooldown(value).map(Rule::Cooldown),
+        "compile-cooldown" => parse_cooldown(value).map(Rule::CompileCooldown),
+        "enforcement" => match value {
+            None => Ok(None),
+            Some("delete") => Ok(Some(Enforcement::Delete)),
+            Some("sanitize") => Ok(Some(Enforcement::Sanitize)),
+            _ => Err("The enforcement must be delete, sanitize or default"),
+        }
+        .map(Rule::Enforcement),
+        "emoji" => match value {
+            None => Ok(None),
+            Some("allow") => Ok(Some(EmojiPolicy::Allow)),
+            Some("strip") => Ok(Some(EmojiPolicy::Strip)),
+            Some("reject") => Ok(Some(EmojiPolicy::Reject)),
+            _ => Err("The emoji policy must be allow, strip, reject or default"),
+        }
+        .map(Rule::EmojiPolicy),
+        "markup" => match value {
+            None => Ok(None),
+            Some("strip") => Ok(Some(MarkupPolicy::Strip)),
+            Some("reject") => Ok(Some(MarkupPolicy::Reject)),
+            _ => Err("The markup policy must be strip, reject or default"),
+        }
+        .map(Rule::Markup),
+        _ => Err("Unknown rule, pick one of match-mode, max-words, min-contributors, max-per-user, segmentation, hyphens, apostrophes, cooldown, compile-cooldown, enforcement, emoji or markup"),
+    }
+}
+
+fn parse_compound_policy(value: Option<&str>) -> Result<Option<CompoundPolicy>, &'static str> {
+    match value {
+        None => Ok(None),
+        Some("join") => Ok(Some(CompoundPolicy::Join)),
+        Some("reject") => Ok(Some(CompoundPolicy::Reject)),
+        Some("split") => Ok(Some(CompoundPolicy::Split)),
+        _ => Err("Hyphens and apostrophes can join, reject, split or be default"),
+    }
+}
+
+fn parse_cooldown(value: Option<&str>) -> Result<Option<u64>, &'static str> {
+    match value {
+        None => Ok(None),
+        Some("off") => Ok(Some(0)),
+        Some(seconds) => seconds
+            .parse()
+            .ok()
+            .filter(|s| *s <= MAX_COOLDOWN)
+            .map(Some)
+            .ok_or("The cooldown must be a number of seconds up to 3600, off or default"),
+    }
+}
+
+/// Parses a duration like `90`, `15m`, `6h` or `2d` into seconds.
+fn parse_duration(arg: &str) -> Option<u64> {
+    let (number, unit) = match arg.char_indices().last()? {
+        (i, unit) if unit.is_ascii_alphabetic() => (&arg[..i], unit),
+        _ => (arg, 's'),
+    };
+    let unit = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(unit)
+}
+
+/// Parses how long story channels may be quiet before a nudge.
+fn parse_nudge(arg: &str) -> Result<u64, &'static str> {
+    parse_duration(arg)
+        .filter(|after| *after >= MIN_NUDGE)
+        .ok_or("The nudge needs a duration of at least 10 minutes, like 6h")
+}
+
+/// Splits a command into words. Text in double quotes is kept together as
+/// a single word, without the quotes.
+fn tokenize(content: &str) -> Result<Vec<String>, &'static str> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quoted = false;
+
+    for c in content.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quoted {
+        return Err("Missing closing quote");
+    }
+    if in_word {
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+fn parse_event(args: &[&str]) -> Result<Command, &'static str> {
+    let (name, change) = match args {
+        ["add", name, start, end] => match (parse_month_day(start), parse_month_day(end)) {
+            (Some(start), Some(end)) => (name, EventChange::Add { start, end }),
+            _ => return Err("Event dates are given as MM-DD, like 10-01"),
+        },
+        ["remove", name] => (name, EventChange::Remove),
+        ["prompt", name, prompt] => (name, EventChange::Prompt(prompt.to_string())),
+        ["color", name, color] => match u32::from_str_radix(color.trim_start_matches('#'), 16) {
+            Ok(color) if color <= 0xffffff => (name, EventChange::Color(color)),
+            _ => return Err("The color must be a hex color, like #ff7518"),
+        },
+        ["ban", name, word] => (name, EventChange::Ban(word.to_string())),
+        ["require", name, word] => (name, EventChange::Require(word.to_string())),
+        _ => return Err("Usage: one-word event <add <name> <MM-DD> <MM-DD>|remove <name>|prompt <name> \"<prompt>\"|color <name> <#hex>|ban <name> <word>|require <name> <word>>"),
+    };
+    Ok(Command::Event(name.to_string(), change))
+}
+
+fn parse_severity(args: &[&str]) -> Result<Command, &'static str> {
+    const USAGE: &str = "Usage: one-word severity <mild|strong|slur> <add|remove <word>|action <allow|spoiler|review|delete|delete-warn>>";
+    let (severity, change) = match args {
+        [severity, change @ ..] => (severity, change),
+        _ => return Err(USAGE),
+    };
+    let severity = match severity.to_lowercase().as_str() {
+        "mild" => Severity::Mild,
+        "strong" => Severity::Strong,
+        "slur" => Severity::Slur,
+        _ => return Err("The severity must be mild, strong or slur"),
+    };
+    let change = match change {
+        ["add", word] => SeverityChange::Add(word.to_string()),
+        ["remove", word] => SeverityChange::Remove(word.to_string()),
+        ["action", action] => SeverityChange::Action(match action.to_lowercase().as_str() {
+            "allow" => SeverityAction::Allow,
+            "spoiler" => SeverityAction::Spoiler,
+            "review" => SeverityAction::Review,
+            "delete" => SeverityAction::Delete,
+            "delete-warn" => SeverityAction::DeleteWarn,
+            _ => return Err("The action must be allow, spoiler, review, delete or delete-warn"),
+        }),
+        _ => return Err(USAGE),
+    };
+    Ok(Command::Severity(severity, change))
+}
+
+/// A story's number, as in 42 or #42, or the ID of its message.
+fn parse_story_id(id: &str) -> Option<u64> {
+    id.trim_start_matches('#').parse().ok()
+}
+
+fn parse_archive_get(args: &[&str]) -> Result<Command, &'static str> {
+    let (args, revision) = match args {
+        [args @ .., "--revision", n] => match n.parse() {
+            Ok(n) if n > 0 => (args, Some(n)),
+            _ => return Err("The revision must be a number from 1 on"),
+        },
+        args => (args, None),
+    };
+    match args {
+        [] | ["latest"] => Ok(Command::ArchiveGet(None, revision)),
+        [id] => parse_story_id(id)
+            .map(|id| Command::ArchiveGet(Some(id), revision))
+            .ok_or("archive get takes a story's number, the ID of its message, or latest"),
+        _ => Err(ARCHIVE_USAGE),
+    }
+}
+
+fn parse_embed(args: &[&str]) -> Result<EmbedChange, &'static str> {
+    match args {
+        ["color", "default"] => Ok(EmbedChange::Color(None)),
+        ["color", color] => match u32::from_str_radix(color.trim_start_matches('#'), 16) {
+            Ok(color) if color <= 0xffffff => Ok(EmbedChange::Color(Some(color))),
+            _ => Err("The color must be a hex color, like #ff7518, or default"),
+        },
+        ["title", "off"] => Ok(EmbedChange::Title(None)),
+        ["title", template] if template.contains("{title}") => {
+            Ok(EmbedChange::Title(Some(template.to_string())))
+        }
+        ["title", ..] => Err("The title template must contain {title}, like \"📖 {title}\""),
+        ["thumbnail", "on"] => Ok(EmbedChange::Thumbnail(true)),
+        ["thumbnail", "off"] => Ok(EmbedChange::Thumbnail(false)),
+        ["footer", "off"] => Ok(EmbedChange::Footer(None)),
+        ["footer", text @ ..] if !text.is_empty() => {
+            let footer = text.join(" ");
+            match footer.chars().count() <= MAX_FOOTER {
+                true => Ok(EmbedChange::Footer(Some(footer))),
+                false => Err("The footer can be up to 2048 characters long"),
+            }
+        }
+        _ => Err("Usage: one-word embed <color <#hex|default>|title \"<template>\"|off|thumbnail <on|off>|footer <text|off>>"),
+    }
+}
+
+/// A sensitive word or phrase the way stories are checked for it.
+fn sensitive_phrase(words: &[&str]) -> String {
+    phrase_words(&words.join(" ")).join(" ")
+}
+
+fn parse_quiet_hours(args: &[&str]) -> Result<Command, &'static str> {
+    const USAGE: &str = "Usage: one-word quiet-hours <HH:MM-HH:MM|off>";
+    let range = match args {
+        ["off"] => return Ok(Command::SetQuietHours(None)),
+        [range] => range,
+        _ => return Err(USAGE),
+    };
+    let (start, end) = range.split_once('-').ok_or(USAGE)?;
+    let times = parse_time(start).zip(parse_time(end));
+    let (start, end) = times
+        .filter(|(start, end)| start != end)
+        .ok_or("Quiet hours run between two different times, like 23:00-07:00")?;
+    Ok(Command::SetQuietHours(Some(QuietHours { start, end })))
+}
+
+/// Minutes after midnight of a time like `07:30`.
+fn parse_time(arg: &str) -> Option<u32> {
+    let (hours, minutes) = arg.split_once(':')?;
+    let hours: u32 = hours.parse().ok().filter(|h| *h < 24)?;
+    let minutes: u32 = minutes.parse().ok().filter(|m| *m < 60)?;
+    Some(hours * 60 + minutes)
+}
+
+/// Minutes ahead of UTC of an offset like `UTC+2`, `UTC-5:30` or `UTC`.
+fn parse_utc_offset(arg: &str) -> Option<i32> {
+    let offset = arg.to_uppercase();
+    let offset = offset.strip_prefix("UTC")?;
+    if offset.is_empty() {
+        return Some(0);
+    }
+    let (sign, offset) = match offset.split_at(1) {
+        ("+", offset) => (1, offset),
+        ("-", offset) => (-1, offset),
+        _ => return None,
+    };
+    let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+    let hours: i32 = hours.parse().ok().filter(|h| *h <= 14)?;
+    let minutes: i32 = minutes.parse().ok().filter(|m| *m < 60)?;
+    Some(sign * (hours * 60 + minutes))
+}
+
+/// Parses a day of the year given as `MM-DD`.
+fn parse_month_day(arg: &str) -> Option<String> {
+    let (month, day) = arg.split_once('-')?;
+    let valid = month.len() == 2
+        && day.len() == 2
+        && month.parse::<u32>().is_ok_and(|m| (1..=12).contains(&m))
+        && day.parse::<u32>().is_ok_and(|d| (1..=31).contains(&d));
+    valid.then(|| arg.to_string())
+}
+
+/// Parses a `<#id>` channel mention or a bare channel id.
+fn parse_channel(arg: &str) -> Option<ChannelId> {
+    arg.replace("<#", "")
+        .replace('>', "")
+        .parse::<u64>()
+        .map(ChannelId)
+        .ok()
+}
+
+/// Parses a `<@&id>` role mention or a bare role id.
+fn parse_role(arg: &str) -> Option<RoleId> {
+    arg.trim_start_matches("<@&")
+        .trim_end_matches('>')
+        .parse::<u64>()
+        .map(RoleId)
+        .ok()
+}
+
+/// Parses a `<@id>` user mention or a bare user id.
+fn parse_user(arg: &str) -> Option<UserId> {
+    arg.trim_start_matches("<@")
+        .trim_start_matches('!')
+        .trim_end_matches('>')
+        .parse::<u64>()
+        .map(UserId)
+        .ok()
+}
+
+/// Shows a usage error with the prefix the guild actually uses.
+pub fn with_prefix(error: &str, prefix: &str) -> String {
+    error.replacen(DEFAULT_PREFIX, prefix, 1)
+}
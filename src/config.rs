@@ -0,0 +1,1007 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::chart;
+use crate::command::DEFAULT_PREFIX;
+use crate::game::{phrase_words, WordFilter};
+use crate::i18n::Locale;
+use crate::illustration::ImageConfig;
+use crate::llm::LlmConfig;
+use crate::mastodon::MastodonConfig;
+use crate::scheduler::now;
+use crate::storage::{storage, HistoryKind};
+use crate::templates::Templates;
+use crate::toxicity::ToxicityConfig;
+use crate::tts::TtsConfig;
+use crate::welcome::WelcomeMode;
+use crate::xp::{UserXp, XpConfig};
+
+/// Settings for a single guild, Telegram group or Matrix room.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    pub channel_id: ChannelId,
+    pub banned_words: HashSet<String>,
+    /// Banned sequences of words, checked across contributions.
+    pub banned_phrases: HashSet<String>,
+    /// Lowercased words and phrases a compiled story is spoilered and
+    /// given a content warning for.
+    pub sensitive_words: HashSet<String>,
+    /// Turn away words missing from the dictionary of the guild's language.
+    pub dictionary: bool,
+    /// Words listed by how bad they are, each severity with its own action.
+    pub severity_tiers: BTreeMap<Severity, SeverityTier>,
+    /// How banned words are found in contributions.
+    pub match_mode: MatchMode,
+    /// Seconds authors get to fix an invalid contribution before it is
+    /// deleted. 0 deletes right away.
+    pub grace_period: u64,
+    /// What happens to contributions with too many words.
+    pub enforcement: Enforcement,
+    /// How emoji in contributions are treated.
+    pub emoji_policy: EmojiPolicy,
+    /// Whether spoiler and code markup is stripped from contributions.
+    pub markup: MarkupPolicy,
+    /// Most words in a contribution, all but one of them short. Defaults to
+    /// [`DEFAULT_MAX_WORDS`].
+    pub max_words: Option<usize>,
+    /// Different contributors a story needs before it can end. 0 lets any
+    /// story end.
+    pub min_contributors: usize,
+    /// Most words one author may add to a story. 0 doesn't limit authors.
+    pub max_per_user: usize,
+    /// How contributions are split into words.
+    pub segmentation: Segmentation,
+    /// How hyphenated words, like "well-known", are counted.
+    pub hyphens: CompoundPolicy,
+    /// How contractions, like "don't", are counted.
+    pub apostrophes: CompoundPolicy,
+    /// Seconds an author waits before contributing again. 0 doesn't limit
+    /// authors.
+    pub cooldown: u64,
+    /// Seconds between compiled stories in a channel. 0 compiles whenever
+    /// asked.
+    pub compile_cooldown: u64,
+    /// Further story channels, and channels whose rules differ from the
+    /// guild's.
+    pub channels: HashMap<ChannelId, ChannelRules>,
+    /// GitHub token used to publish completed stories as gists.
+    pub gist_token: Option<String>,
+    /// Bearer token letting external tools manage the guild through the
+    /// HTTP API.
+    pub api_token: Option<String>,
+    /// Mastodon account completed stories are cross-posted to.
+    pub mastodon: Option<MastodonConfig>,
+    /// OpenAI compatible API used for AI features.
+    pub llm: Option<LlmConfig>,
+    /// Add an AI summary to compiled stories.
+    pub summaries: bool,
+    /// How compiled stories without a manual title get one.
+    pub ai_titles: TitleMode,
+    /// Title for the next compiled story, set with `one-word title`.
+    pub next_title: Option<String>,
+    /// Post a new story prompt in the story channel every day.
+    pub daily_prompt: bool,
+    /// Unix time of the last daily prompt.
+    pub last_prompt_at: u64,
+    /// Seconds a story channel may be quiet before the bot nudges it. 0
+    /// never nudges.
+    pub nudge_after: u64,
+    /// Role pinged by nudges.
+    pub nudge_role: Option<RoleId>,
+    /// Lowercased words mods challenge contributors to work into the story.
+    pub challenges: HashSet<String>,
+    /// Themed events, each running every year between two dates.
+    pub events: Vec<Event>,
+    /// Name of the running event, once its start was announced.
+    pub announced_event: Option<String>,
+    /// Time of day contributions are turned away.
+    pub quiet_hours: Option<QuietHours>,
+    /// Story channels that turn contributions away until a mod resumes the
+    /// game.
+    pub paused: HashSet<ChannelId>,
+    /// Don't pause the game when new accounts flood a story channel with
+    /// invalid messages.
+    pub skip_raid_protection: bool,
+    /// Seconds an account must have existed before it may contribute.
+    pub min_account_age: u64,
+    /// Seconds a member must have been in the guild before they may
+    /// contribute.
+    pub min_member_age: u64,
+    /// Users whose messages are quietly deleted, with the unix time their
+    /// mute ends.
+    pub shadow_muted: HashMap<UserId, u64>,
+    /// Minutes the guild's time zone is ahead of UTC. Dates and times of
+    /// day, like those of events and quiet hours, are in this time zone.
+    pub utc_offset: i32,
+    /// Channel the weekly digest is posted in.
+    pub digest_channel: Option<ChannelId>,
+    /// Unix time of the last weekly digest.
+    pub last_digest_at: u64,
+    /// Mark where accepted words were deleted in compiled stories.
+    pub mark_gaps: bool,
+    /// Also reject the common profanity built into the word filter.
+    pub profanity_filter: bool,
+    /// Leave compiled stories unpinned.
+    pub skip_pins: bool,
+    /// Reacting with this emoji to the latest word ends the story, like
+    /// posting the end marker.
+    pub end_reaction: Option<String>,
+    /// Messages with the rules pinned in each channel, kept up to date.
+    pub rules_pins: HashMap<ChannelId, MessageId>,
+    /// Keep the topic of story channels showing the rules and how long the
+    /// story has grown.
+    pub topic_updates: bool,
+    /// Have Discord enforce the cooldown with the channel's slowmode,
+    /// lifted during sprints.
+    pub native_slowmode: bool,
+    /// How first-time contributors are told how to play.
+    pub welcome: WelcomeMode,
+    /// Users who were told how to play already.
+    pub welcomed: HashSet<UserId>,
+    /// Newest contribution of the last story compiled in each channel, so
+    /// a story isn't compiled twice.
+    pub compiled: HashMap<ChannelId, MessageId>,
+    /// Number of the next compiled story. Guilds from before it was kept
+    /// start after the last story in the archive.
+    pub next_story_number: u64,
+    /// Unix time the bot was removed from the guild, if it hasn't been
+    /// invited back since.
+    pub removed_at: Option<u64>,
+    /// How long records are kept in the history.
+    pub retention: RetentionPolicy,
+    /// Users allowed to change settings without being server admins.
+    pub admins: HashSet<UserId>,
+    /// Role whose members may run moderation commands.
+    pub mod_role: Option<RoleId>,
+    /// Users sent every compiled story by DM.
+    pub subscribers: HashSet<UserId>,
+    /// Channel moderation notices are posted in.
+    pub mod_log_channel: Option<ChannelId>,
+    /// Channel borderline contributions wait in for a moderator's review.
+    pub review_channel: Option<ChannelId>,
+    /// A user's first this many words wait in the review channel for a
+    /// moderator's approval. Needs a review channel.
+    pub approval_contributions: usize,
+    /// Accepted words of each user counting towards approval, up to
+    /// `approval_contributions`.
+    pub approved_words: HashMap<UserId, usize>,
+    /// Toxicity model checking contributions on top of the banned words.
+    pub toxicity: Option<ToxicityConfig>,
+    /// Image API used to illustrate compiled stories with a cover.
+    pub illustrations: Option<ImageConfig>,
+    /// Speech API used to narrate archived stories.
+    pub tts: Option<TtsConfig>,
+    /// Whether compiled stories come with a rendered story card.
+    pub story_cards: CardMode,
+    /// Post a chart of who wrote how much, and when, with compiled stories.
+    pub story_charts: bool,
+    /// XP and levels for contributors, if the guild opted in.
+    pub xp: Option<XpConfig>,
+    /// XP each user earned so far.
+    pub xp_progress: HashMap<UserId, UserXp>,
+    /// Language of the bot's replies.
+    pub locale: Locale,
+    /// Custom wording of the bot's messages.
+    pub templates: Templates,
+    /// How compiled stories look.
+    pub embed: EmbedStyle,
+    /// Replaces `one-word` in front of commands.
+    pub prefix: Option<String>,
+    /// Telegram group and Matrix room sharing the story channel's story.
+    pub bridge: Option<Bridge>,
+}
+
+impl Config {
+    pub fn prefix(&self) -> &str {
+        self.prefix.as_deref().unwrap_or(DEFAULT_PREFIX)
+    }
+
+    /// Bans a word, or a phrase if `word` has several.
+    pub fn ban(&mut self, word: String) {
+        match phrase_words(&word) {
+            words if words.len() > 1 => self.banned_phrases.insert(words.join(" ")),
+            _ => self.banned_words.insert(word),
+        };
+    }
+
+    pub fn unban(&mut self, word: &str) {
+        match phrase_words(word) {
+            words if words.len() > 1 => self.banned_phrases.remove(&words.join(" ")),
+            _ => self.banned_words.remove(word),
+        };
+    }
+
+    /// The sensitive words and phrases found in `text`, sorted.
+    pub fn content_warnings(&self, text: &str) -> Vec<&str> {
+        let words = phrase_words(text);
+        let mut found: Vec<&str> = self
+            .sensitive_words
+            .iter()
+            .filter(|sensitive| {
+                let sensitive: Vec<&str> = sensitive.split(' ').collect();
+                words.windows(sensitive.len()).any(|w| w == sensitive)
+            })
+            .map(String::as_str)
+            .collect();
+        found.sort();
+        found
+    }
+
+    /// Whether contributions in `channel_id` make up a story.
+    pub fn is_story_channel(&self, channel_id: ChannelId) -> bool {
+        channel_id == self.channel_id || self.channels.contains_key(&channel_id)
+    }
+
+    /// The rules in `channel_id`. The channel's overrides come first, then
+    /// the guild's settings, then the defaults.
+    pub fn rules(&self, channel_id: ChannelId) -> Rules {
+        let channel = self.channels.get(&channel_id).cloned().unwrap_or_default();
+        Rules {
+            match_mode: channel.match_mode.unwrap_or(self.match_mode),
+            max_words: channel
+                .max_words
+                .or(self.max_words)
+                .unwrap_or(DEFAULT_MAX_WORDS),
+            min_contributors: channel.min_contributors.unwrap_or(self.min_contributors),
+            max_per_user: channel.max_per_user.unwrap_or(self.max_per_user),
+            segmentation: channel.segmentation.unwrap_or(self.segmentation),
+            hyphens: channel.hyphens.unwrap_or(self.hyphens),
+            apostrophes: channel.apostrophes.unwrap_or(self.apostrophes),
+            cooldown: channel.cooldown.unwrap_or(self.cooldown),
+            compile_cooldown: channel.compile_cooldown.unwrap_or(self.compile_cooldown),
+            enforcement: channel.enforcement.unwrap_or(self.enforcement),
+            emoji_policy: channel.emoji_policy.unwrap_or(self.emoji_policy),
+            markup: channel.markup.unwrap_or(self.markup),
+        }
+    }
+
+    /// Sets a rule for the whole guild, or overrides it in `channel`.
+    pub fn set_rule(&mut self, channel: Option<ChannelId>, rule: Rule) {
+        if let Some(channel) = channel {
+            let rules = self.channels.entry(channel).or_default();
+            match rule {
+                Rule::MatchMode(mode) => rules.match_mode = mode,
+                Rule::MaxWords(max) => rules.max_words = max,
+                Rule::MinContributors(min) => rules.min_contributors = min,
+                Rule::MaxPerUser(max) => rules.max_per_user = max,
+                Rule::Segmentation(segmentation) => rules.segmentation = segmentation,
+                Rule::Hyphens(policy) => rules.hyphens = policy,
+                Rule::Apostrophes(policy) => rules.apostrophes = policy,
+                Rule::Cooldown(seconds) => rules.cooldown = seconds,
+                Rule::CompileCooldown(seconds) => rules.compile_cooldown = seconds,
+                Rule::Enforcement(enforcement) => rules.enforcement = enforcement,
+                Rule::EmojiPolicy(policy) => rules.emoji_policy = policy,
+                Rule::Markup(policy) => rules.markup = policy,
+            }
+            return;
+        }
+
+        match rule {
+            Rule::MatchMode(mode) => self.match_mode = mode.unwrap_or_default(),
+            Rule::MaxWords(max) => self.max_words = max,
+            Rule::MinContributors(min) => self.min_contributors = min.unwrap_or_default(),
+            Rule::MaxPerUser(max) => self.max_per_user = max.unwrap_or_default(),
+            Rule::Segmentation(segmentation) => {
+                self.segmentation = segmentation.unwrap_or_default()
+            }
+            Rule::Hyphens(policy) => self.hyphens = policy.unwrap_or_default(),
+            Rule::Apostrophes(policy) => self.apostrophes = policy.unwrap_or_default(),
+            Rule::Cooldown(seconds) => self.cooldown = seconds.unwrap_or_default(),
+            Rule::CompileCooldown(seconds) => self.compile_cooldown = seconds.unwrap_or_default(),
+            Rule::Enforcement(enforcement) => self.enforcement = enforcement.unwrap_or_default(),
+            Rule::EmojiPolicy(policy) => self.emoji_policy = policy.unwrap_or_default(),
+            Rule::Markup(policy) => self.markup = policy.unwrap_or_default(),
+        }
+    }
+
+    /// The unix time `at` moved into the guild's time zone, for telling its
+    /// date and time of day.
+    pub fn local(&self, at: u64) -> i64 {
+        at as i64 + self.utc_offset as i64 * 60
+    }
+
+    /// Today's date in the guild's time zone as `MM-DD`, as events are
+    /// scheduled.
+    pub fn month_day(&self) -> String {
+        chart::day(self.local(now()))
+            .get(5..)
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// The unix time `at` in the guild's time zone, like
+    /// `2024-05-17 14:30 UTC+2`.
+    pub fn local_time(&self, at: u64) -> String {
+        let time = Timestamp::from_unix_timestamp(self.local(at))
+            .map(|t| t.to_string().replacen('T', " ", 1))
+            .unwrap_or_default();
+        format!(
+            "{} {}",
+            time.get(..16).unwrap_or_default(),
+            utc_offset_name(self.utc_offset)
+        )
+    }
+
+    /// The first of the guild's events running on `day`, given as `MM-DD`.
+    pub fn active_event(&self, day: &str) -> Option<&Event> {
+        self.events.iter().find(|e| e.is_running(day))
+    }
+
+    /// Applies `change` to the event called `name`. Returns false if there
+    /// is no such event to change.
+    pub fn change_event(&mut self, name: &str, change: EventChange) -> bool {
+        if let EventChange::Add { start, end } = change {
+            match self.events.iter_mut().find(|e| e.name == name) {
+                Some(event) => {
+                    event.start = start;
+                    event.end = end;
+                }
+                None => self.events.push(Event {
+                    name: name.to_string(),
+                    start,
+                    end,
+                    ..Default::default()
+                }),
+            }
+            return true;
+        }
+        if let EventChange::Remove = change {
+            let count = self.events.len();
+            self.events.retain(|e| e.name != name);
+            return self.events.len() < count;
+        }
+
+        let event = match self.events.iter_mut().find(|e| e.name == name) {
+            Some(event) => event,
+            None => return false,
+        };
+        match change {
+            EventChange::Prompt(prompt) => event.prompts.push(prompt),
+            EventChange::Color(color) => event.color = Some(color),
+            EventChange::Ban(word) => {
+                event.banned_words.insert(word);
+            }
+            EventChange::Require(word) => event.required_words.push(word),
+            EventChange::Add { .. } | EventChange::Remove => {}
+        }
+        true
+    }
+
+    /// Applies `change` to the words of `severity`.
+    pub fn change_severity(&mut self, severity: Severity, change: SeverityChange) {
+        let tier = self.severity_tiers.entry(severity).or_default();
+        match change {
+            SeverityChange::Add(word) => {
+                tier.words.insert(word);
+            }
+            SeverityChange::Remove(word) => {
+                tier.words.remove(&word);
+            }
+            SeverityChange::Action(action) => tier.action = action,
+        }
+    }
+
+    pub fn change_embed(&mut self, change: EmbedChange) {
+        match change {
+            EmbedChange::Color(color) => self.embed.color = color,
+            EmbedChange::Title(title) => self.embed.title = title,
+            EmbedChange::Thumbnail(thumbnail) => self.embed.thumbnail = thumbnail,
+            EmbedChange::Footer(footer) => self.embed.footer = footer,
+        }
+    }
+
+    /// The most words a contribution has to be checked against to find
+    /// banned phrases.
+    pub fn phrase_lookback(&self) -> usize {
+        self.banned_phrases
+            .iter()
+            .map(|p| p.split_whitespace().count().saturating_sub(1))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// A themed event, like spooky stories in October. It runs every year from
+/// `start` to `end`, both given as `MM-DD` in the guild's timezone, and may
+/// run over new year.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Event {
+    pub name: String,
+    pub start: String,
+    pub end: String,
+    /// Story prompts used instead of the usual ones.
+    pub prompts: Vec<String>,
+    /// Color of compiled story embeds.
+    pub color: Option<u32>,
+    /// Words banned on top of the guild's while the event runs.
+    pub banned_words: HashSet<String>,
+    /// Words the event's prompts ask stories to work in.
+    pub required_words: Vec<String>,
+}
+
+impl Event {
+    /// Whether the event runs on `day`, given as `MM-DD`.
+    pub fn is_running(&self, day: &str) -> bool {
+        let (start, end) = (self.start.as_str(), self.end.as_str());
+        match start <= end {
+            true => start <= day && day <= end,
+            false => start <= day || day <= end,
+        }
+    }
+}
+
+/// A daily stretch of time without new words, so a story doesn't race
+/// ahead while most of the guild sleeps. Times are minutes after midnight
+/// in the guild's time zone, `end` not included, and may wrap past
+/// midnight.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+pub struct QuietHours {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl QuietHours {
+    /// Whether the local time `at`, see [`Config::local`], falls in the
+    /// quiet hours.
+    pub fn contains(&self, at: i64) -> bool {
+        let minute = (at.rem_euclid(24 * 60 * 60) / 60) as u32;
+        match self.start <= self.end {
+            true => self.start <= minute && minute < self.end,
+            false => self.start <= minute || minute < self.end,
+        }
+    }
+}
+
+/// Names the time zone `utc_offset` minutes ahead of UTC, like `UTC+2` or
+/// `UTC-5:30`.
+pub fn utc_offset_name(utc_offset: i32) -> String {
+    let sign = if utc_offset < 0 { '-' } else { '+' };
+    let (hours, minutes) = (utc_offset.abs() / 60, utc_offset.abs() % 60);
+    match (hours, minutes) {
+        (0, 0) => "UTC".to_string(),
+        (hours, 0) => format!("UTC{}{}", sign, hours),
+        (hours, minutes) => format!("UTC{}{}:{:02}", sign, hours, minutes),
+    }
+}
+
+/// How bad the words of a severity tier are.
+#[derive(
+    serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Mild,
+    Strong,
+    Slur,
+}
+
+/// What happens to contributions with words of a severity tier, from the
+/// most lenient to the strictest.
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SeverityAction {
+    Allow,
+    /// Accept the word, but hide it behind a spoiler in the compiled story.
+    Spoiler,
+    /// Take the word out of the story until a moderator reviews it, or
+    /// delete it if the guild has no review channel.
+    Review,
+    #[default]
+    Delete,
+    /// Delete the word, and warn its author in the story channel.
+    DeleteWarn,
+}
+
+/// The words of a severity tier and what happens to them.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct SeverityTier {
+    pub words: HashSet<String>,
+    pub action: SeverityAction,
+}
+
+/// A change to a severity tier, made with `one-word severity`.
+#[derive(Debug, Clone)]
+pub enum SeverityChange {
+    Add(String),
+    Remove(String),
+    Action(SeverityAction),
+}
+
+/// The look of a guild's compiled stories.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct EmbedStyle {
+    /// Used unless an event has a color of its own.
+    pub color: Option<u32>,
+    /// Wraps the story title. `{title}` and the story's `{number}` are
+    /// filled in.
+    pub title: Option<String>,
+    /// Shows the guild icon next to the story.
+    pub thumbnail: bool,
+    /// Shown under the story, e.g. the license of published stories.
+    pub footer: Option<String>,
+}
+
+/// A change to the look of stories, made with `one-word embed`. `None`
+/// goes back to the default.
+#[derive(Debug, Clone)]
+pub enum EmbedChange {
+    Color(Option<u32>),
+    Title(Option<String>),
+    Thumbnail(bool),
+    Footer(Option<String>),
+}
+
+/// A change to an event, made with `one-word event`.
+#[derive(Debug, Clone)]
+pub enum EventChange {
+    /// Adds the event, or moves its dates.
+    Add {
+        start: String,
+        end: String,
+    },
+    Remove,
+    Prompt(String),
+    Color(u32),
+    Ban(String),
+    Require(String),
+}
+
+/// Words allowed in a contribution if the guild doesn't say otherwise.
+pub const DEFAULT_MAX_WORDS: usize = 2;
+
+/// Seconds records of each kind are kept in the history, for good if
+/// `None`. Pruned contributions no longer count towards stats.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    pub contributions: Option<u64>,
+    pub audit: Option<u64>,
+}
+
+impl RetentionPolicy {
+    pub fn get(&self, kind: HistoryKind) -> Option<u64> {
+        match kind {
+            HistoryKind::Contributions => self.contributions,
+            HistoryKind::Audit => self.audit,
+        }
+    }
+
+    pub fn set(&mut self, kind: HistoryKind, keep: Option<u64>) {
+        match kind {
+            HistoryKind::Contributions => self.contributions = keep,
+            HistoryKind::Audit => self.audit = keep,
+        }
+    }
+}
+
+/// Rules of a single story channel. Rules left unset follow the guild.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ChannelRules {
+    pub match_mode: Option<MatchMode>,
+    pub max_words: Option<usize>,
+    pub min_contributors: Option<usize>,
+    pub max_per_user: Option<usize>,
+    pub segmentation: Option<Segmentation>,
+    pub hyphens: Option<CompoundPolicy>,
+    pub apostrophes: Option<CompoundPolicy>,
+    pub cooldown: Option<u64>,
+    pub compile_cooldown: Option<u64>,
+    pub enforcement: Option<Enforcement>,
+    pub emoji_policy: Option<EmojiPolicy>,
+    pub markup: Option<MarkupPolicy>,
+}
+
+/// The rules in effect in a story channel, see [`Config::rules`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+pub struct Rules {
+    pub match_mode: MatchMode,
+    pub max_words: usize,
+    pub min_contributors: usize,
+    pub max_per_user: usize,
+    pub segmentation: Segmentation,
+    pub hyphens: CompoundPolicy,
+    pub apostrophes: CompoundPolicy,
+    pub cooldown: u64,
+    pub compile_cooldown: u64,
+    pub enforcement: Enforcement,
+    pub emoji_policy: EmojiPolicy,
+    pub markup: MarkupPolicy,
+}
+
+/// A rule changed with `one-word set`. `None` goes back to the default,
+/// which for a channel is the guild's setting.
+#[derive(Debug, Clone, Copy)]
+pub enum Rule {
+    MatchMode(Option<MatchMode>),
+    MaxWords(Option<usize>),
+    MinContributors(Option<usize>),
+    MaxPerUser(Option<usize>),
+    Segmentation(Option<Segmentation>),
+    Hyphens(Option<CompoundPolicy>),
+    Apostrophes(Option<CompoundPolicy>),
+    Cooldown(Option<u64>),
+    CompileCooldown(Option<u64>),
+    Enforcement(Option<Enforcement>),
+    EmojiPolicy(Option<EmojiPolicy>),
+    Markup(Option<MarkupPolicy>),
+}
+
+/// How a hyphenated word or a contraction counts.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompoundPolicy {
+    /// As a single word.
+    #[default]
+    Join,
+    Reject,
+    /// As the words on either side of the hyphen or apostrophe.
+    Split,
+}
+
+/// How contributions are split into words.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Segmentation {
+    /// At whitespace.
+    #[default]
+    Whitespace,
+    /// Also around Chinese and Japanese characters, which don't put spaces
+    /// between words.
+    Unicode,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    /// Anywhere in a contribution, also catching common obfuscations.
+    #[default]
+    Substring,
+    /// Only a whole word, ignoring surrounding punctuation.
+    WholeWord,
+    /// Anywhere not directly next to other letters or digits.
+    WordBoundary,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Enforcement {
+    /// Delete them like any other invalid contribution.
+    #[default]
+    Delete,
+    /// Replace them with their first word, posted in the author's name.
+    Sanitize,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmojiPolicy {
+    /// An emoji counts as a word.
+    #[default]
+    Allow,
+    /// Emoji are left out of compiled stories, and don't count as a word.
+    Strip,
+    /// Contributions with emoji are invalid.
+    Reject,
+}
+
+/// Spoilers (`||word||`) and code (`` `word` ``) in contributions.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkupPolicy {
+    /// The markup is ignored when checking contributions and left out of
+    /// compiled stories.
+    #[default]
+    Strip,
+    /// Contributions with markup are invalid.
+    Reject,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TitleMode {
+    /// Use the generic "Story so far".
+    #[default]
+    Off,
+    /// Use a title suggested by the LLM.
+    Suggest,
+    /// Let the channel vote between three suggested titles.
+    Vote,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CardMode {
+    #[default]
+    Off,
+    /// Attach the card to the first story embed.
+    Attach,
+    /// Post only the card, without the story text.
+    Only,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Bridge {
+    pub telegram: Option<i64>,
+    pub matrix: Option<String>,
+    /// Webhooks used to post bridged words, by story channel.
+    pub webhooks: HashMap<ChannelId, WebhookId>,
+    /// The one webhook kept before they were kept by channel. It is moved
+    /// to its channel on first use.
+    pub webhook_id: Option<WebhookId>,
+}
+
+impl Bridge {
+    /// The webhook posting bridged words in `channel_id`. Webhook ids are
+    /// unique, so the one from before is only ever matched in its channel.
+    pub fn webhook(&self, channel_id: ChannelId) -> Option<WebhookId> {
+        self.webhooks.get(&channel_id).copied().or(self.webhook_id)
+    }
+}
+
+/// Everything stored in `CONFIG_FILE`.
+///
+/// `legacy` holds a config written by older versions of the bot, before
+/// settings were kept per guild. It is moved to its guild as soon as the
+/// guild owning its channel shows up (see [`adopt_legacy_config`]).
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct Configs {
+    pub guilds: HashMap<GuildId, Config>,
+    /// Telegram groups, keyed by chat id.
+    #[serde(default)]
+    pub telegram: HashMap<i64, Config>,
+    /// Matrix rooms, keyed by room id.
+    #[serde(default)]
+    pub matrix: HashMap<String, Config>,
+    /// Discord users' own preferences, for every guild.
+    #[serde(default)]
+    pub users: HashMap<UserId, UserPrefs>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legacy: Option<Config>,
+}
+
+/// What a user wants to be sent, whatever the guild's settings.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct UserPrefs {
+    /// DMs explaining why a word was deleted.
+    pub deletion_dms: bool,
+    /// DMs with the stories completed in the guilds subscribed to.
+    pub story_dms: bool,
+}
+
+impl Default for UserPrefs {
+    fn default() -> Self {
+        UserPrefs {
+            deletion_dms: true,
+            story_dms: true,
+        }
+    }
+}
+
+/// A preference changed with `one-word prefs`.
+#[derive(Debug, Clone, Copy)]
+pub enum UserPref {
+    DeletionDms,
+    StoryDms,
+}
+
+impl UserPrefs {
+    pub fn set(&mut self, pref: UserPref, on: bool) {
+        match pref {
+            UserPref::DeletionDms => self.deletion_dms = on,
+            UserPref::StoryDms => self.story_dms = on,
+        }
+    }
+}
+
+pub struct ConfigContainer;
+impl TypeMapKey for ConfigContainer {
+    type Value = Arc<RwLock<Configs>>;
+}
+
+pub struct CensorContainer;
+impl TypeMapKey for CensorContainer {
+    type Value = Arc<RwLock<HashMap<GuildId, WordFilter>>>;
+}
+
+pub async fn get_config(data: &RwLock<TypeMap>, guild_id: GuildId) -> Config {
+    let lock = {
+        let data = data.read().await;
+        data.get::<ConfigContainer>()
+            .expect("Expected ConfigContainer in TypeMap")
+            .clone()
+    };
+    let configs = lock.read().await;
+    configs.guilds.get(&guild_id).cloned().unwrap_or_default()
+}
+
+pub async fn set_config<F>(data: &RwLock<TypeMap>, guild_id: GuildId, update: F)
+where
+    F: FnOnce(&mut Config),
+{
+    let (lock, censors) = {
+        let data = data.read().await;
+        (
+            data.get::<ConfigContainer>()
+                .expect("Expected ConfigContainer in TypeMap")
+                .clone(),
+            data.get::<CensorContainer>()
+                .expect("Expected CensorContainer in TypeMap")
+                .clone(),
+        )
+    };
+    {
+        let mut configs = lock.write().await;
+        let config = configs.guilds.entry(guild_id).or_default();
+        update(config);
+
+        censors
+            .write()
+            .await
+            .insert(guild_id, WordFilter::new(config));
+
+        storage(data).await.save_configs(&configs).await;
+    }
+}
+
+pub(crate) async fn config_lock(data: &RwLock<TypeMap>) -> Arc<RwLock<Configs>> {
+    let data = data.read().await;
+    data.get::<ConfigContainer>()
+        .expect("Expected ConfigContainer in TypeMap")
+        .clone()
+}
+
+pub async fn get_telegram_config(data: &RwLock<TypeMap>, chat_id: i64) -> Config {
+    let lock = config_lock(data).await;
+    let configs = lock.read().await;
+    configs.telegram.get(&chat_id).cloned().unwrap_or_default()
+}
+
+pub async fn set_telegram_config<F>(data: &RwLock<TypeMap>, chat_id: i64, update: F)
+where
+    F: FnOnce(&mut Config),
+{
+    let lock = config_lock(data).await;
+    let mut configs = lock.write().await;
+    update(configs.telegram.entry(chat_id).or_default());
+    storage(data).await.save_configs(&configs).await;
+}
+
+pub async fn get_matrix_config(data: &RwLock<TypeMap>, room_id: &str) -> Config {
+    let lock = config_lock(data).await;
+    let configs = lock.read().await;
+    configs.matrix.get(room_id).cloned().unwrap_or_default()
+}
+
+pub async fn set_matrix_config<F>(data: &RwLock<TypeMap>, room_id: &str, update: F)
+where
+    F: FnOnce(&mut Config),
+{
+    let lock = config_lock(data).await;
+    let mut configs = lock.write().await;
+    update(configs.matrix.entry(room_id.to_string()).or_default());
+    storage(data).await.save_configs(&configs).await;
+}
+
+pub async fn get_user_prefs(data: &RwLock<TypeMap>, user_id: UserId) -> UserPrefs {
+    let lock = config_lock(data).await;
+    let configs = lock.read().await;
+    configs.users.get(&user_id).cloned().unwrap_or_default()
+}
+
+pub async fn set_user_prefs<F>(data: &RwLock<TypeMap>, user_id: UserId, update: F)
+where
+    F: FnOnce(&mut UserPrefs),
+{
+    let lock = config_lock(data).await;
+    let mut configs = lock.write().await;
+    update(configs.users.entry(user_id).or_default());
+    storage(data).await.save_configs(&configs).await;
+}
+
+/// Finds the first guild whose config matches `f`.
+pub async fn find_guild<F>(data: &RwLock<TypeMap>, f: F) -> Option<(GuildId, Config)>
+where
+    F: Fn(&Config) -> bool,
+{
+    let lock = config_lock(data).await;
+    let configs = lock.read().await;
+    configs
+        .guilds
+        .iter()
+        .find(|(_, config)| f(config))
+        .map(|(id, config)| (*id, config.clone()))
+}
+
+/// The guild whose story channel `channel_id` is bridged to other platforms.
+pub async fn find_bridged_channel(
+    data: &RwLock<TypeMap>,
+    channel_id: ChannelId,
+) -> Option<(GuildId, Config)> {
+    find_guild(data, |config| {
+        config.bridge.is_some() && config.channel_id == channel_id
+    })
+    .await
+}
+
+/// Assigns a pre per-guild config to `guild` if its story channel lives there.
+pub async fn adopt_legacy_config(data: &RwLock<TypeMap>, guild: &Guild) {
+    let lock = {
+        let data = data.read().await;
+        data.get::<ConfigContainer>()
+            .expect("Expected ConfigContainer in TypeMap")
+            .clone()
+    };
+    let legacy = {
+        let mut configs = lock.write().await;
+        match &configs.legacy {
+            Some(legacy) if guild.channels.contains_key(&legacy.channel_id) => {
+                configs.legacy.take()
+            }
+            _ => None,
+        }
+    };
+
+    if let Some(legacy) = legacy {
+        println!("Moving legacy config to guild {}", guild.id);
+        set_config(data, guild.id, |config| *config = legacy).await;
+    }
+}
+
+/// Gives a guild the bot joined a config of its own, with no story channel
+/// yet and common profanity filtered. Returns false if it had one already.
+pub async fn register_guild(data: &RwLock<TypeMap>, guild_id: GuildId) -> bool {
+    let lock = config_lock(data).await;
+    if lock.read().await.guilds.contains_key(&guild_id) {
+        return false;
+    }
+    set_config(data, guild_id, |config| {
+        config.profanity_filter = true;
+    })
+    .await;
+    true
+}
+
+/// Forgets the settings of `guild_id`.
+pub async fn remove_config(data: &RwLock<TypeMap>, guild_id: GuildId) {
+    let censors = {
+        let data = data.read().await;
+        data.get::<CensorContainer>()
+            .expect("Expected CensorContainer in TypeMap")
+            .clone()
+    };
+    let lock = config_lock(data).await;
+    let mut configs = lock.write().await;
+    configs.guilds.remove(&guild_id);
+    censors.write().await.remove(&guild_id);
+    storage(data).await.save_configs(&configs).await;
+}
+
+pub fn censors_for(configs: &Configs) -> HashMap<GuildId, WordFilter> {
+    configs
+        .guilds
+        .iter()
+        .map(|(id, config)| (*id, WordFilter::new(config)))
+        .collect()
+}
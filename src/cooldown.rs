@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+/// Longest cooldown admins can pick, in seconds.
+pub const MAX_COOLDOWN: u64 = 60 * 60;
+
+/// When each author last contributed to each story channel.
+pub struct CooldownContainer;
+impl TypeMapKey for CooldownContainer {
+    type Value = Arc<Mutex<HashMap<(ChannelId, UserId), Instant>>>;
+}
+
+/// When a story was last compiled in each channel.
+pub struct CompileContainer;
+impl TypeMapKey for CompileContainer {
+    type Value = Arc<Mutex<HashMap<ChannelId, Instant>>>;
+}
+
+async fn contributions(
+    data: &RwLock<TypeMap>,
+) -> Arc<Mutex<HashMap<(ChannelId, UserId), Instant>>> {
+    let data = data.read().await;
+    data.get::<CooldownContainer>()
+        .expect("Expected CooldownContainer in TypeMap")
+        .clone()
+}
+
+/// Whether `user` contributed to `channel_id` less than `seconds` ago.
+pub async fn is_cooling_down(
+    data: &RwLock<TypeMap>,
+    channel_id: ChannelId,
+    user: UserId,
+    seconds: u64,
+) -> bool {
+    if seconds == 0 {
+        return false;
+    }
+    let contributions = contributions(data).await;
+    let contributions = contributions.lock().await;
+    contributions
+        .get(&(channel_id, user))
+        .is_some_and(|at| at.elapsed() < Duration::from_secs(seconds))
+}
+
+/// Starts the cooldown of `user` after an accepted contribution.
+pub async fn touch(data: &RwLock<TypeMap>, channel_id: ChannelId, user: UserId) {
+    let contributions = contributions(data).await;
+    let mut contributions = contributions.lock().await;
+    // Nobody waits longer than the longest cooldown.
+    contributions.retain(|_, at| at.elapsed().as_secs() < MAX_COOLDOWN);
+    contributions.insert((channel_id, user), Instant::now());
+}
+
+async fn compiles(data: &RwLock<TypeMap>) -> Arc<Mutex<HashMap<ChannelId, Instant>>> {
+    let data = data.read().await;
+    data.get::<CompileContainer>()
+        .expect("Expected CompileContainer in TypeMap")
+        .clone()
+}
+
+/// How long until a story may be compiled in `channel_id` again, if a
+/// story was compiled there less than `seconds` ago.
+pub async fn compile_wait(
+    data: &RwLock<TypeMap>,
+    channel_id: ChannelId,
+    seconds: u64,
+) -> Option<Duration> {
+    let compiles = compiles(data).await;
+    let compiles = compiles.lock().await;
+    let elapsed = compiles.get(&channel_id)?.elapsed();
+    Duration::from_secs(seconds)
+        .checked_sub(elapsed)
+        .filter(|wait| !wait.is_zero())
+}
+
+/// Forgets cooldowns that are over whatever the rules, returning how many.
+pub async fn prune(data: &RwLock<TypeMap>) -> usize {
+    let expired = |at: &Instant| at.elapsed().as_secs() >= MAX_COOLDOWN;
+    let contributions = contributions(data).await;
+    let mut contributions = contributions.lock().await;
+    let compiles = compiles(data).await;
+    let mut compiles = compiles.lock().await;
+    let before = contributions.len() + compiles.len();
+    contributions.retain(|_, at| !expired(at));
+    compiles.retain(|_, at| !expired(at));
+    before - contributions.len() - compiles.len()
+}
+
+/// Starts the compile cooldown of `channel_id`.
+pub async fn compiled(data: &RwLock<TypeMap>, channel_id: ChannelId) {
+    compiles(data)
+        .await
+        .lock()
+        .await
+        .insert(channel_id, Instant::now());
+}
@@ -0,0 +1,65 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+
+use censor::Censor;
+
+use crate::game::{phrase_words, Rejection};
+use crate::i18n::Locale;
+
+const LOCALES: [Locale; 4] = [Locale::En, Locale::De, Locale::Fr, Locale::Es];
+
+/// The base profanity list for guilds in `locale`. English profanity is
+/// common enough everywhere to always be on it.
+pub fn profanity(locale: Locale) -> Censor {
+    let words = match locale {
+        Locale::En => return Censor::Standard,
+        Locale::De => include_str!("../filters/de.txt"),
+        Locale::Fr => include_str!("../filters/fr.txt"),
+        Locale::Es => include_str!("../filters/es.txt"),
+    };
+    Censor::Standard + Censor::Custom(words.lines().map(str::to_string).collect())
+}
+
+/// Word lists read from `DICTIONARY_DIR`, one `<language>.txt` per
+/// language with a word on each line. Languages without a file have none.
+fn dictionaries() -> &'static HashMap<Locale, HashSet<String>> {
+    static DICTIONARIES: OnceLock<HashMap<Locale, HashSet<String>>> = OnceLock::new();
+    DICTIONARIES.get_or_init(|| {
+        let dir = match env::var("DICTIONARY_DIR") {
+            Ok(dir) => dir,
+            Err(_) => return HashMap::new(),
+        };
+        LOCALES
+            .into_iter()
+            .filter_map(|locale| {
+                let path = format!("{}/{}.txt", dir, locale.code());
+                match fs::read_to_string(&path) {
+                    Ok(words) => Some((locale, words.lines().map(|w| w.to_lowercase()).collect())),
+                    Err(why) => {
+                        println!("No dictionary at {}: {:?}", path, why);
+                        None
+                    }
+                }
+            })
+            .collect()
+    })
+}
+
+/// Checks that every word of `msg` is in the dictionary of `locale`. Words
+/// with digits, and languages without a dictionary, aren't checked.
+pub fn check(locale: Locale, msg: &str) -> Result<(), Rejection> {
+    let dictionary = match dictionaries().get(&locale) {
+        Some(dictionary) => dictionary,
+        None => return Ok(()),
+    };
+    let unknown = phrase_words(msg)
+        .iter()
+        .filter(|w| !w.chars().any(|c| c.is_ascii_digit()))
+        .any(|w| !dictionary.contains(w));
+    match unknown {
+        true => Err(Rejection::UnknownWord),
+        false => Ok(()),
+    }
+}
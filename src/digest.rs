@@ -0,0 +1,93 @@
+use serenity::http::Http;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::chart;
+use crate::config::{config_lock, set_config, Config};
+use crate::i18n::tr;
+use crate::scheduler::now;
+use crate::storage::storage;
+
+const WEEK: u64 = 7 * 24 * 60 * 60;
+/// Contributors named in the digest.
+const TOP_CONTRIBUTORS: usize = 3;
+
+/// Posts the weekly digest of every guild that wants one and hasn't had one
+/// for a week.
+pub async fn post_due(data: &RwLock<TypeMap>, http: &Http) {
+    let due: Vec<(GuildId, Config)> = {
+        let lock = config_lock(data).await;
+        let configs = lock.read().await;
+        configs
+            .guilds
+            .iter()
+            .filter(|(_, c)| c.digest_channel.is_some())
+            .filter(|(_, c)| now().saturating_sub(c.last_digest_at) >= WEEK)
+            .map(|(id, c)| (*id, c.clone()))
+            .collect()
+    };
+
+    for (guild_id, config) in due {
+        if post(data, http, guild_id, &config).await {
+            set_config(data, guild_id, |config| config.last_digest_at = now()).await;
+        }
+    }
+}
+
+/// Posts the digest of the past week in `guild_id`. Quiet weeks get no
+/// digest. Returns false if it should be tried again.
+async fn post(data: &RwLock<TypeMap>, http: &Http, guild_id: GuildId, config: &Config) -> bool {
+    let channel_id = match config.digest_channel {
+        Some(channel_id) => channel_id,
+        None => return true,
+    };
+    let since = now().saturating_sub(WEEK);
+    let storage = storage(data).await;
+    let stories = storage.stories(guild_id, since).await;
+    let contributions = storage.contributions(guild_id, since).await;
+    if stories.is_empty() && contributions.is_empty() {
+        return true;
+    }
+
+    let top: Vec<String> = chart::tally(contributions.iter().map(|c| c.author.as_str()))
+        .into_iter()
+        .take(TOP_CONTRIBUTORS)
+        .map(|(name, count)| format!("{} ({})", name, count))
+        .collect();
+    let longest = contributions
+        .iter()
+        .flat_map(|c| c.word.split_whitespace())
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+        .max_by_key(|w| w.chars().count())
+        .filter(|w| !w.is_empty());
+
+    let week = format!(
+        "{} – {}",
+        chart::day(config.local(since)),
+        chart::day(config.local(now()))
+    );
+    let locale = config.locale;
+    let sent = channel_id
+        .send_message(http, |m| {
+            m.embed(|e| {
+                e.title(tr(locale, "digest-title"))
+                    .field(tr(locale, "digest-stories"), stories.len(), true)
+                    .field(tr(locale, "digest-words"), contributions.len(), true);
+                if !top.is_empty() {
+                    e.field(tr(locale, "digest-top"), top.join(", "), false);
+                }
+                if let Some(longest) = longest {
+                    e.field(tr(locale, "digest-longest"), longest, false);
+                }
+                e.footer(|f| f.text(&week))
+            })
+        })
+        .await;
+    match sent {
+        Ok(_) => true,
+        Err(why) => {
+            println!("Error posting weekly digest {:?}", why);
+            false
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::borrow::Cow;
+
+use serenity::builder::CreateComponents;
+use serenity::model::application::component::{ActionRowComponent, ButtonStyle};
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::{InteractionResponseType, MessageFlags};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::config::get_config;
+use crate::i18n::tr;
+use crate::storage;
+
+/// `story-download`, on the first message of a compiled story, or
+/// `story-download:<first message id>` on the messages continuing it.
+const DOWNLOAD: &str = "story-download";
+
+/// Adds the button downloading the story. `story` is the first message of
+/// the story, unless the button goes on it.
+pub fn button<'a>(
+    components: &'a mut CreateComponents,
+    label: &str,
+    story: Option<MessageId>,
+) -> &'a mut CreateComponents {
+    let custom_id = match story {
+        Some(id) => format!("{}:{}", DOWNLOAD, id),
+        None => DOWNLOAD.to_string(),
+    };
+    components.create_action_row(|r| {
+        r.create_button(|b| {
+            b.custom_id(custom_id)
+                .label(label)
+                .style(ButtonStyle::Secondary)
+        })
+    })
+}
+
+pub fn is_download(custom_id: &str) -> bool {
+    custom_id.starts_with(DOWNLOAD)
+}
+
+/// Whether `msg` continues the story posted in `story_id`, going by its
+/// download button.
+pub fn continues(msg: &Message, story_id: MessageId) -> bool {
+    let custom_id = format!("{}:{}", DOWNLOAD, story_id);
+    msg.components.iter().flat_map(|row| &row.components).any(
+        |c| matches!(c, ActionRowComponent::Button(b) if b.custom_id.as_ref() == Some(&custom_id)),
+    )
+}
+
+/// Sends the clicking user the archived story as a text file.
+pub async fn handle(ctx: &Context, component: &MessageComponentInteraction) {
+    let guild_id = match component.guild_id {
+        Some(id) => id,
+        None => return,
+    };
+    let story_id = match component.data.custom_id.split_once(':') {
+        Some((_, id)) => match id.parse() {
+            Ok(id) => MessageId(id),
+            Err(_) => {
+                println!("Unknown download button {}", component.data.custom_id);
+                return;
+            }
+        },
+        None => component.message.id,
+    };
+    let story = storage::storage(&ctx.data)
+        .await
+        .stories(guild_id, 0)
+        .await
+        .into_iter()
+        .find(|s| s.message_id == Some(story_id));
+
+    let locale = get_config(&ctx.data, guild_id).await.locale;
+    let res = component
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| {
+                    match &story {
+                        Some(story) => d.add_file(AttachmentType::Bytes {
+                            data: Cow::Owned(
+                                format!("{}\n\n{}\n", story.title, story.text).into_bytes(),
+                            ),
+                            filename: "story.txt".to_string(),
+                        }),
+                        None => d.content(tr(locale, "archive-not-found")),
+                    };
+                    d.flags(MessageFlags::EPHEMERAL)
+                })
+        })
+        .await;
+    if let Err(why) = res {
+        println!("Error sending story download {:?}", why);
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+
+use crate::storage::{ContributionRecord, StoryRecord};
+
+/// `field` quoted for CSV, if it needs to be.
+fn escape(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+fn row(fields: &[String]) -> String {
+    let fields: Vec<String> = fields.iter().map(|f| escape(f)).collect();
+    fields.join(",") + "\n"
+}
+
+fn time(at: Option<u64>) -> String {
+    at.map(|at| at.to_string()).unwrap_or_default()
+}
+
+/// A CSV row per contributor, with their words, the stories they are
+/// credited in, and the unix times of their first and latest words.
+pub fn contributors(contributions: &[ContributionRecord], stories: &[StoryRecord]) -> String {
+    struct Contributor {
+        name: String,
+        words: usize,
+        first: u64,
+        last: u64,
+    }
+
+    // Words posted in a user's name before ids were kept only have a name.
+    let mut by_user: BTreeMap<String, Contributor> = BTreeMap::new();
+    for c in contributions {
+        let id = c.author_id.map_or_else(String::new, |id| id.to_string());
+        let contributor = by_user
+            .entry(format!("{}:{}", id, c.author))
+            .or_insert(Contributor {
+                name: c.author.clone(),
+                words: 0,
+                first: c.at,
+                last: c.at,
+            });
+        contributor.words += 1;
+        contributor.last = c.at;
+    }
+
+    let mut csv = row(&[
+        "user_id".into(),
+        "name".into(),
+        "words".into(),
+        "stories".into(),
+        "first_word_at".into(),
+        "last_word_at".into(),
+    ]);
+    for (key, c) in by_user {
+        let id = key.split(':').next().unwrap_or_default().to_string();
+        let credited = stories
+            .iter()
+            .filter(|s| s.contributors.contains(&c.name))
+            .count();
+        csv += &row(&[
+            id,
+            c.name,
+            c.words.to_string(),
+            credited.to_string(),
+            c.first.to_string(),
+            c.last.to_string(),
+        ]);
+    }
+    csv
+}
+
+/// A CSV row per compiled story.
+pub fn stories(stories: &[StoryRecord]) -> String {
+    let mut csv = row(&[
+        "number".into(),
+        "message_id".into(),
+        "channel_id".into(),
+        "title".into(),
+        "words".into(),
+        "contributors".into(),
+        "started_at".into(),
+        "ended_at".into(),
+        "compiled_at".into(),
+        "started_by".into(),
+        "ended_by".into(),
+    ]);
+    for s in stories {
+        csv += &row(&[
+            s.number.map(|n| n.to_string()).unwrap_or_default(),
+            s.message_id.map(|id| id.to_string()).unwrap_or_default(),
+            s.channel_id.to_string(),
+            s.title.clone(),
+            s.text.split_whitespace().count().to_string(),
+            s.contributors.len().to_string(),
+            time(s.started_at),
+            time(s.ended_at),
+            s.at.to_string(),
+            s.starter.clone().unwrap_or_default(),
+            s.ender.clone().unwrap_or_default(),
+        ]);
+    }
+    csv
+}
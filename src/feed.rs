@@ -0,0 +1,85 @@
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+/// The platform a word was written on.
+#[derive(serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Discord,
+    Telegram,
+    Matrix,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Source::Discord => "Discord",
+            Source::Telegram => "Telegram",
+            Source::Matrix => "Matrix",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Something that happened in a story channel, as streamed to live feed clients
+/// and to bridged frontends.
+///
+/// Stories bridged from Telegram or Matrix are keyed by the Discord channel
+/// they are bridged to.
+#[derive(serde::Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeedEvent {
+    Word {
+        channel_id: ChannelId,
+        word: String,
+        author: String,
+        source: Source,
+    },
+    /// Someone on a bridged platform asked for the story to be compiled.
+    Compile { channel_id: ChannelId },
+    End {
+        channel_id: ChannelId,
+        story: String,
+    },
+}
+
+impl FeedEvent {
+    pub fn channel_id(&self) -> ChannelId {
+        match self {
+            FeedEvent::Word { channel_id, .. }
+            | FeedEvent::Compile { channel_id }
+            | FeedEvent::End { channel_id, .. } => *channel_id,
+        }
+    }
+}
+
+pub struct FeedContainer;
+impl TypeMapKey for FeedContainer {
+    type Value = Arc<broadcast::Sender<FeedEvent>>;
+}
+
+pub fn new_feed() -> Arc<broadcast::Sender<FeedEvent>> {
+    let (tx, _) = broadcast::channel(256);
+    Arc::new(tx)
+}
+
+pub async fn subscribe(data: &RwLock<TypeMap>) -> broadcast::Receiver<FeedEvent> {
+    let data = data.read().await;
+    data.get::<FeedContainer>()
+        .expect("Expected FeedContainer in TypeMap")
+        .subscribe()
+}
+
+pub async fn publish(data: &RwLock<TypeMap>, event: FeedEvent) {
+    let tx = {
+        let data = data.read().await;
+        data.get::<FeedContainer>()
+            .expect("Expected FeedContainer in TypeMap")
+            .clone()
+    };
+    // Failing to send only means nobody is listening.
+    let _ = tx.send(event);
+}
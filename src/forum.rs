@@ -0,0 +1,31 @@
+use serenity::cache::Cache;
+use serenity::model::prelude::*;
+
+/// A post in a forum channel. Each post's thread holds a story of its own.
+pub struct Post {
+    pub forum: ChannelId,
+    pub title: String,
+}
+
+/// The forum post whose thread is `channel_id`, if it is one.
+pub fn post(cache: &Cache, guild_id: GuildId, channel_id: ChannelId) -> Option<Post> {
+    let thread = cache
+        .guild_field(guild_id, |g| {
+            g.threads.iter().find(|t| t.id == channel_id).cloned()
+        })
+        .flatten()?;
+    let forum = thread.parent_id?;
+    let is_forum = cache
+        .guild_channel_field(forum, |c| c.kind == ChannelType::Forum)
+        .unwrap_or(false);
+    is_forum.then_some(Post {
+        forum,
+        title: thread.name,
+    })
+}
+
+/// The story channel `channel_id` belongs to: its forum for forum posts,
+/// otherwise the channel itself.
+pub fn venue(cache: &Cache, guild_id: GuildId, channel_id: ChannelId) -> ChannelId {
+    post(cache, guild_id, channel_id).map_or(channel_id, |p| p.forum)
+}
@@ -0,0 +1,439 @@
+use std::collections::HashSet;
+
+use censor::Censor;
+
+use crate::config::{
+    CompoundPolicy, Config, EmojiPolicy, MarkupPolicy, MatchMode, Rules, Segmentation, Severity,
+    SeverityAction,
+};
+use crate::dictionary;
+
+/// Why a contribution was turned down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    TooManyWords,
+    BannedWord,
+    BannedPhrase,
+    Emoji,
+    Cooldown,
+    /// The author already added as many words to the story as one may.
+    StoryShare,
+    QuietHours,
+    /// A mod paused the game.
+    Paused,
+    /// A word isn't in the dictionary of the guild's language.
+    UnknownWord,
+    /// A hyphenated word or contraction, where the guild rejects them.
+    Compound,
+    /// Spoiler or code markup, where the guild rejects it, or nothing but
+    /// markup.
+    Markup,
+}
+
+impl Rejection {
+    /// Translation key describing the rejection.
+    pub fn key(self) -> &'static str {
+        match self {
+            Rejection::TooManyWords => "reason-too-many-words",
+            Rejection::BannedWord => "reason-banned-word",
+            Rejection::BannedPhrase => "reason-banned-phrase",
+            Rejection::Emoji => "reason-emoji",
+            Rejection::Cooldown => "reason-cooldown",
+            Rejection::StoryShare => "reason-story-share",
+            Rejection::QuietHours => "reason-quiet-hours",
+            Rejection::Paused => "reason-paused",
+            Rejection::UnknownWord => "reason-unknown-word",
+            Rejection::Compound => "reason-compound",
+            Rejection::Markup => "reason-markup",
+        }
+    }
+}
+
+/// Finds banned words, and words of the severity tiers, in contributions.
+pub struct WordFilter {
+    banned: WordList,
+    /// Tiers that don't allow their words, strictest action first.
+    tiers: Vec<(Severity, SeverityAction, WordList)>,
+}
+
+impl WordFilter {
+    pub fn new(config: &Config) -> Self {
+        let mut censor = Censor::Custom(config.banned_words.clone());
+        if config.profanity_filter {
+            censor += dictionary::profanity(config.locale);
+        }
+        let mut tiers: Vec<_> = config
+            .severity_tiers
+            .iter()
+            .filter(|(_, t)| t.action != SeverityAction::Allow && !t.words.is_empty())
+            .map(|(severity, t)| {
+                let words = WordList::new(Censor::Custom(t.words.clone()));
+                (*severity, t.action, words)
+            })
+            .collect();
+        tiers.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+        WordFilter {
+            banned: WordList::new(censor),
+            tiers,
+        }
+    }
+
+    /// Whether `msg` has a banned word.
+    pub fn check(&self, msg: &str, mode: MatchMode) -> bool {
+        self.banned.check(msg, mode)
+    }
+
+    /// The severity tier with the strictest action among the words in
+    /// `msg`, if it has any.
+    pub fn severity(&self, msg: &str, mode: MatchMode) -> Option<(Severity, SeverityAction)> {
+        self.tiers
+            .iter()
+            .find(|(_, _, words)| words.check(msg, mode))
+            .map(|(severity, action, _)| (*severity, *action))
+    }
+}
+
+/// Words looked for in contributions, in any of the match modes.
+struct WordList {
+    censor: Censor,
+    /// The lowercased words, for the modes not using `censor`.
+    words: Vec<String>,
+}
+
+impl WordList {
+    fn new(censor: Censor) -> Self {
+        WordList {
+            words: censor.list().map(|w| w.to_lowercase()).collect(),
+            censor,
+        }
+    }
+
+    fn check(&self, msg: &str, mode: MatchMode) -> bool {
+        match mode {
+            MatchMode::Substring => self.censor.check(msg),
+            MatchMode::WholeWord => phrase_words(msg).iter().any(|w| self.words.contains(w)),
+            MatchMode::WordBoundary => {
+                let msg = msg.to_lowercase();
+                self.words.iter().any(|w| matches_at_boundary(&msg, w))
+            }
+        }
+    }
+}
+
+/// Whether `word` occurs in `text` with no letters or digits right before
+/// or after it, like `\bword\b` in a regex.
+fn matches_at_boundary(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(i, m)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + m.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// The words of `text`. Ideographs are words of their own with
+/// [`Segmentation::Unicode`], as Chinese and Japanese don't put spaces
+/// between words.
+pub fn split_words(text: &str, segmentation: Segmentation) -> Vec<&str> {
+    let mut words = Vec::new();
+    for word in text.split_whitespace() {
+        if segmentation == Segmentation::Whitespace {
+            words.push(word);
+            continue;
+        }
+        let mut start = 0;
+        for (i, c) in word.char_indices() {
+            if is_ideograph(c) {
+                if start < i {
+                    words.push(&word[start..i]);
+                }
+                let end = i + c.len_utf8();
+                // Marks belong to the ideograph before them.
+                let marks = word[end..]
+                    .chars()
+                    .take_while(|c| joins_previous(*c))
+                    .map(char::len_utf8)
+                    .sum::<usize>();
+                words.push(&word[i..end + marks]);
+                start = end + marks;
+            }
+        }
+        if start < word.len() && !word[start..].chars().all(joins_previous) {
+            words.push(&word[start..]);
+        }
+    }
+    words
+}
+
+/// The length of `word` as a reader counts it: accents, emoji modifiers
+/// and the parts of joined emoji don't add to it.
+pub fn length(word: &str) -> usize {
+    let mut count = 0;
+    let mut joined = false;
+    for c in word.chars() {
+        if !joins_previous(c) && !joined {
+            count += 1;
+        }
+        joined = c == ZERO_WIDTH_JOINER;
+    }
+    count
+}
+
+const ZERO_WIDTH_JOINER: char = '\u{200d}';
+
+/// Whether `c` is part of the character before it, like an accent written
+/// as a combining mark or an emoji skin tone.
+fn joins_previous(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036f}'
+        | '\u{1ab0}'..='\u{1aff}'
+        | '\u{1dc0}'..='\u{1dff}'
+        | '\u{20d0}'..='\u{20ff}'
+        | '\u{fe00}'..='\u{fe0f}'
+        | '\u{fe20}'..='\u{fe2f}'
+        | '\u{1f3fb}'..='\u{1f3ff}'
+        | '\u{e0100}'..='\u{e01ef}'
+        | ZERO_WIDTH_JOINER)
+}
+
+/// Whether `c` is a Chinese or Japanese character that is a word, or part
+/// of one, without spaces around it. Katakana is left in runs.
+fn is_ideograph(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{309f}' // Hiragana
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{20000}'..='\u{2fa1f}')
+}
+
+/// A valid contribution as it appears in the compiled story.
+pub fn story_word(text: &str, emoji: EmojiPolicy) -> String {
+    let text = strip_markup(text);
+    match emoji {
+        EmojiPolicy::Strip => strip_emoji(&text).trim().to_string(),
+        _ => text,
+    }
+}
+
+/// Removes spoilers (`||word||`) and code (`` `word` `` or a code block)
+/// from `text`, keeping the words inside.
+pub fn strip_markup(text: &str) -> String {
+    let text = text.replace("||", "");
+    // The language of a code block is on its first line.
+    let text = match text
+        .trim()
+        .strip_prefix("```")
+        .and_then(|t| t.split_once('\n'))
+    {
+        Some((language, code)) if !language.contains(char::is_whitespace) => code,
+        _ => &text,
+    };
+    text.replace('`', "").trim().to_string()
+}
+
+/// Removes custom emoji (`<:name:id>`) and Unicode emoji from `text`.
+pub fn strip_emoji(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some((len, _)) = custom_emoji(rest) {
+            rest = &rest[len..];
+            continue;
+        }
+        if !is_emoji(c) {
+            stripped.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    stripped
+}
+
+/// Replaces custom emoji markup with the emoji's `:name:`, which reads
+/// better outside Discord.
+pub fn custom_emoji_names(text: &str) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some((len, name)) = custom_emoji(rest) {
+            replaced.push(':');
+            replaced.push_str(name);
+            replaced.push(':');
+            rest = &rest[len..];
+            continue;
+        }
+        replaced.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    replaced
+}
+
+/// Length and name of the custom emoji markup `<:name:id>` or
+/// `<a:name:id>` at the start of `text`, if there is one.
+fn custom_emoji(text: &str) -> Option<(usize, &str)> {
+    let inner = text
+        .strip_prefix("<:")
+        .or_else(|| text.strip_prefix("<a:"))?;
+    let end = inner.find('>')?;
+    let (name, id) = inner[..end].split_once(':')?;
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !id.is_empty()
+        && id.chars().all(|c| c.is_ascii_digit());
+    valid.then(|| (text.len() - inner.len() + end + 1, name))
+}
+
+/// Whether `c` is part of an emoji, including the joiners and modifiers
+/// combining emoji into one.
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF // Pictographs, emoticons, flags and skin tones
+        | 0x2300..=0x23FF // Technical symbols like ⌚
+        | 0x2600..=0x27BF // Miscellaneous symbols and dingbats
+        | 0x2B00..=0x2BFF // Arrows and shapes like ⭐
+        | 0x200D          // Zero width joiner
+        | 0x20E3          // Keycap
+        | 0xFE0F          // Emoji presentation selector
+        | 0xE0020..=0xE007F // Tags
+    )
+}
+
+pub async fn check_message(msg: &str, censor: &WordFilter, rules: &Rules) -> Result<(), Rejection> {
+    let unmarked = strip_markup(msg);
+    let msg = match rules.markup {
+        MarkupPolicy::Strip if unmarked.is_empty() => return Err(Rejection::Markup),
+        MarkupPolicy::Reject if unmarked != msg.trim() => return Err(Rejection::Markup),
+        _ => unmarked.as_str(),
+    };
+
+    let stripped;
+    let msg = match rules.emoji_policy {
+        EmojiPolicy::Allow => msg,
+        EmojiPolicy::Strip => {
+            stripped = strip_emoji(msg);
+            if stripped.trim().is_empty() {
+                return Err(Rejection::Emoji);
+            }
+            &stripped
+        }
+        EmojiPolicy::Reject if strip_emoji(msg) != msg => return Err(Rejection::Emoji),
+        EmojiPolicy::Reject => msg,
+    };
+
+    let mut words = Vec::new();
+    for word in split_words(msg, rules.segmentation) {
+        words.extend(split_compound(word, rules)?);
+    }
+
+    if words.len() > rules.max_words {
+        return Err(Rejection::TooManyWords);
+    }
+
+    // Only one of the words may be longer than a short word like "a" or "of".
+    if words.iter().filter(|w| length(w) > 2).count() > 1 {
+        return Err(Rejection::TooManyWords);
+    }
+
+    // Split compounds are checked word by word too, the way they are
+    // counted.
+    let split = words.join(" ");
+    if censor.check(msg, rules.match_mode) || censor.check(&split, rules.match_mode) {
+        return Err(Rejection::BannedWord);
+    }
+
+    Ok(())
+}
+
+const HYPHENS: &[char] = &['-', '\u{2010}', '\u{2011}'];
+const APOSTROPHES: &[char] = &['\'', '\u{2019}'];
+
+/// The words `word` counts as under the guild's rules for hyphenated words
+/// and contractions.
+fn split_compound<'a>(word: &'a str, rules: &Rules) -> Result<Vec<&'a str>, Rejection> {
+    let mut parts = vec![word];
+    for (separators, policy) in [(HYPHENS, rules.hyphens), (APOSTROPHES, rules.apostrophes)] {
+        if !joins_letters(word, separators) {
+            continue;
+        }
+        match policy {
+            CompoundPolicy::Join => {}
+            CompoundPolicy::Reject => return Err(Rejection::Compound),
+            CompoundPolicy::Split => {
+                parts = parts
+                    .into_iter()
+                    .flat_map(|p| p.split(separators))
+                    .filter(|p| !p.is_empty())
+                    .collect()
+            }
+        }
+    }
+    Ok(parts)
+}
+
+/// Whether one of `separators` in `word` has letters or digits on both
+/// sides, like the hyphen in "well-known".
+fn joins_letters(word: &str, separators: &[char]) -> bool {
+    let chars: Vec<char> = word.chars().collect();
+    chars
+        .windows(3)
+        .any(|w| separators.contains(&w[1]) && w[0].is_alphanumeric() && w[2].is_alphanumeric())
+}
+
+/// Lowercased words of `text`, without surrounding punctuation.
+pub fn phrase_words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Checks whether adding `msg` after the `previous` words of the story,
+/// in chronological order, completes one of the banned `phrases`.
+pub fn check_phrases(
+    previous: &[String],
+    msg: &str,
+    phrases: &HashSet<String>,
+) -> Result<(), Rejection> {
+    let before: Vec<String> = previous.iter().flat_map(|w| phrase_words(w)).collect();
+    let added = phrase_words(msg);
+
+    for phrase in phrases.iter().map(|p| phrase_words(p)) {
+        if phrase.is_empty() {
+            continue;
+        }
+        // Only look back far enough that every match includes a new word.
+        let start = before.len().saturating_sub(phrase.len() - 1);
+        let words: Vec<&String> = before[start..].iter().chain(added.iter()).collect();
+        if words
+            .windows(phrase.len())
+            .any(|w| w.iter().zip(phrase.iter()).all(|(a, b)| *a == b))
+        {
+            return Err(Rejection::BannedPhrase);
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits the story into pieces of at most `limit` bytes, breaking between words.
+pub fn story_chunks(words: &[String], limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+
+    for word in words {
+        if !chunk.is_empty() && chunk.len() + word.len() + 1 > limit {
+            chunks.push(std::mem::take(&mut chunk));
+        }
+        if !chunk.is_empty() {
+            chunk.push(' ');
+        }
+        chunk.push_str(word);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+
+    chunks
+}
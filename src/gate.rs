@@ -0,0 +1,58 @@
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::actions;
+use crate::config::Config;
+use crate::i18n::tr_args;
+use crate::modstats;
+use crate::raid;
+use crate::scheduler::now;
+
+/// Unix time from which the author of `msg` may contribute, if that is
+/// still ahead.
+fn allowed_from(msg: &Message, config: &Config) -> Option<u64> {
+    let created = msg.author.created_at().unix_timestamp() as u64;
+    let joined = msg
+        .member
+        .as_ref()
+        .and_then(|m| m.joined_at)
+        .map(|at| at.unix_timestamp() as u64);
+    let account = (config.min_account_age > 0).then_some(created + config.min_account_age);
+    let member = joined
+        .filter(|_| config.min_member_age > 0)
+        .map(|joined| joined + config.min_member_age);
+    account
+        .into_iter()
+        .chain(member)
+        .max()
+        .filter(|at| *at > now())
+}
+
+/// Deletes `msg` if its author's account, or membership, is too new to
+/// contribute, and tells them by DM when they can. Returns whether it may
+/// stay.
+pub async fn check(ctx: &Context, msg: &Message, config: &Config) -> bool {
+    let allowed_from = match allowed_from(msg, config) {
+        Some(at) => at,
+        None => return true,
+    };
+    actions::delete(ctx, msg.channel_id, msg.id).await;
+    if let Some(guild_id) = msg.guild_id {
+        modstats::count(&ctx.data, guild_id, "reason-new-account").await;
+    }
+    raid::invalid(ctx, msg, config).await;
+
+    let text = tr_args(
+        config.locale,
+        "new-account-dm",
+        &[("time", &format!("<t:{}:R>", allowed_from))],
+    );
+    if let Err(why) = msg
+        .author
+        .direct_message(&ctx.http, |m| m.content(text))
+        .await
+    {
+        println!("Error explaining new account gate {:?}", why);
+    }
+    false
+}
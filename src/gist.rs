@@ -0,0 +1,39 @@
+use serde_json::json;
+
+#[derive(serde::Deserialize)]
+struct GistResponse {
+    html_url: String,
+}
+
+/// Uploads `story` as a secret gist and returns its URL.
+pub async fn publish(token: &str, title: &str, story: &str) -> Option<String> {
+    let body = json!({
+        "description": title,
+        "public": false,
+        "files": { "story.md": { "content": story } },
+    });
+
+    let res = reqwest::Client::new()
+        .post("https://api.github.com/gists")
+        .bearer_auth(token)
+        .header(reqwest::header::USER_AGENT, "one-word-story")
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .json(&body)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status());
+
+    match res {
+        Ok(res) => match res.json::<GistResponse>().await {
+            Ok(gist) => Some(gist.html_url),
+            Err(why) => {
+                println!("Error reading gist response {:?}", why);
+                None
+            }
+        },
+        Err(why) => {
+            println!("Error publishing gist {:?}", why);
+            None
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::actions;
+use crate::config::Config;
+use crate::game::Rejection;
+use crate::i18n::{tr, tr_args};
+use crate::scheduler::now;
+
+const FLAG: char = '❌';
+
+/// Longest grace period admins can pick, in seconds.
+pub const MAX_GRACE_PERIOD: u64 = 300;
+
+/// Invalid messages waiting to be fixed, with the notice counting down to
+/// their deletion.
+pub struct GraceContainer;
+impl TypeMapKey for GraceContainer {
+    type Value = Arc<Mutex<HashMap<MessageId, Option<MessageId>>>>;
+}
+
+async fn pending(data: &RwLock<TypeMap>) -> Arc<Mutex<HashMap<MessageId, Option<MessageId>>>> {
+    let data = data.read().await;
+    data.get::<GraceContainer>()
+        .expect("Expected GraceContainer in TypeMap")
+        .clone()
+}
+
+pub async fn is_pending(data: &RwLock<TypeMap>, id: MessageId) -> bool {
+    pending(data).await.lock().await.contains_key(&id)
+}
+
+/// Flags the invalid `msg` and deletes it when the grace period is over,
+/// unless it has been edited into a valid contribution by then.
+pub async fn flag(ctx: &Context, msg: &Message, config: &Config, rejection: Rejection) {
+    let pending = pending(&ctx.data).await;
+    {
+        let mut pending = pending.lock().await;
+        if pending.contains_key(&msg.id) {
+            return;
+        }
+        pending.insert(msg.id, None);
+    }
+
+    actions::react(ctx, msg.channel_id, msg.id, FLAG).await;
+    let notice = tr_args(
+        config.locale,
+        "grace-notice",
+        &[
+            ("reason", &tr(config.locale, rejection.key())),
+            (
+                "deadline",
+                &format!("<t:{}:R>", now() + config.grace_period),
+            ),
+        ],
+    );
+    match msg.reply(&ctx.http, notice).await {
+        Ok(notice) => {
+            if let Some(entry) = pending.lock().await.get_mut(&msg.id) {
+                *entry = Some(notice.id);
+            }
+        }
+        Err(why) => println!("Error posting grace notice {:?}", why),
+    }
+
+    let (ctx, msg, config) = (ctx.clone(), msg.clone(), config.clone());
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(config.grace_period)).await;
+        let notice = match pending.lock().await.remove(&msg.id) {
+            Some(notice) => notice,
+            None => return,
+        };
+
+        if let Some(notice) = notice {
+            actions::delete(&ctx, msg.channel_id, notice).await;
+        }
+        actions::delete(&ctx, msg.channel_id, msg.id).await;
+        crate::notify_deletion(&ctx, &msg, &config, rejection.key()).await;
+    });
+}
+
+/// Lifts the flag on `msg` if it was fixed within its grace period.
+pub async fn clear(ctx: &Context, msg: &Message) {
+    if !forget(ctx, msg.channel_id, msg.id).await {
+        return;
+    }
+    if let Err(why) = msg
+        .channel_id
+        .delete_reaction(&ctx.http, msg.id, None, FLAG)
+        .await
+    {
+        println!("Error removing flag {:?}", why);
+    }
+}
+
+/// Stops waiting for the flagged message `id` to be fixed, e.g. because it
+/// was deleted. Returns whether it was flagged.
+pub async fn forget(ctx: &Context, channel_id: ChannelId, id: MessageId) -> bool {
+    let notice = match pending(&ctx.data).await.lock().await.remove(&id) {
+        Some(notice) => notice,
+        None => return false,
+    };
+
+    if let Some(notice) = notice {
+        actions::delete(ctx, channel_id, notice).await;
+    }
+    true
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Languages the bot's replies are translated to.
+#[derive(
+    serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Fr,
+    Es,
+}
+
+impl Locale {
+    pub fn parse(code: &str) -> Option<Locale> {
+        match code.to_lowercase().as_str() {
+            "en" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            "fr" => Some(Locale::Fr),
+            "es" => Some(Locale::Es),
+            _ => None,
+        }
+    }
+
+    /// The language code, as passed to [`Locale::parse`].
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+            Locale::Fr => "fr",
+            Locale::Es => "es",
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Locale::En => include_str!("../locales/en.ftl"),
+            Locale::De => include_str!("../locales/de.ftl"),
+            Locale::Fr => include_str!("../locales/fr.ftl"),
+            Locale::Es => include_str!("../locales/es.ftl"),
+        }
+    }
+}
+
+type Bundles = HashMap<Locale, HashMap<&'static str, &'static str>>;
+
+fn bundles() -> &'static Bundles {
+    static BUNDLES: OnceLock<Bundles> = OnceLock::new();
+    BUNDLES.get_or_init(|| {
+        [Locale::En, Locale::De, Locale::Fr, Locale::Es]
+            .into_iter()
+            .map(|locale| (locale, parse(locale.source())))
+            .collect()
+    })
+}
+
+/// Reads the `key = value` messages of a Fluent file.
+fn parse(source: &'static str) -> HashMap<&'static str, &'static str> {
+    source
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| l.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect()
+}
+
+/// The message `key` in `locale`, falling back to English when it isn't
+/// translated.
+pub fn tr(locale: Locale, key: &str) -> String {
+    tr_args(locale, key, &[])
+}
+
+/// Like [`tr`], replacing each `{ $name }` in the message with its value
+/// from `args`.
+pub fn tr_args(locale: Locale, key: &str, args: &[(&str, &str)]) -> String {
+    let bundles = bundles();
+    let message = bundles[&locale]
+        .get(key)
+        .or_else(|| bundles[&Locale::En].get(key));
+    let mut message = match message {
+        Some(message) => message.to_string(),
+        None => {
+            println!("Missing translation {}", key);
+            return key.to_string();
+        }
+    };
+    for (name, value) in args {
+        message = message.replace(&format!("{{ ${} }}", name), value);
+    }
+    message
+}
@@ -0,0 +1,71 @@
+use serde_json::json;
+
+/// An OpenAI compatible image generation API.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ImageConfig {
+    /// Base URL of the API, e.g. `https://api.openai.com/v1`.
+    pub endpoint: String,
+    pub api_key: String,
+    pub model: Option<String>,
+}
+
+const DEFAULT_MODEL: &str = "dall-e-3";
+
+/// Longest story excerpt used as the prompt when there is no summary.
+const MAX_PROMPT_CHARS: usize = 1000;
+
+#[derive(serde::Deserialize)]
+struct ImagesResponse {
+    data: Vec<Image>,
+}
+
+#[derive(serde::Deserialize)]
+struct Image {
+    b64_json: String,
+}
+
+/// Generates a PNG cover image for a story described by `description`.
+pub async fn cover(config: &ImageConfig, description: &str) -> Option<Vec<u8>> {
+    let description: String = description.chars().take(MAX_PROMPT_CHARS).collect();
+    let body = json!({
+        "model": config.model.as_deref().unwrap_or(DEFAULT_MODEL),
+        "prompt": format!("A storybook cover illustration, without any text, for this story: {}", description),
+        "n": 1,
+        "size": "1024x1024",
+        "response_format": "b64_json",
+    });
+
+    let res = reqwest::Client::new()
+        .post(format!(
+            "{}/images/generations",
+            config.endpoint.trim_end_matches('/')
+        ))
+        .bearer_auth(&config.api_key)
+        .json(&body)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status());
+
+    let images = match res {
+        Ok(res) => match res.json::<ImagesResponse>().await {
+            Ok(images) => images,
+            Err(why) => {
+                println!("Error reading generated image {:?}", why);
+                return None;
+            }
+        },
+        Err(why) => {
+            println!("Error generating image {:?}", why);
+            return None;
+        }
+    };
+
+    let image = images.data.into_iter().next()?;
+    match base64::decode(image.b64_json) {
+        Ok(png) => Some(png),
+        Err(why) => {
+            println!("Error decoding generated image {:?}", why);
+            None
+        }
+    }
+}
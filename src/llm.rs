@@ -0,0 +1,102 @@
+use serde_json::json;
+
+/// An OpenAI compatible chat completions API.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct LlmConfig {
+    /// Base URL of the API, e.g. `https://api.openai.com/v1`.
+    pub endpoint: String,
+    pub api_key: String,
+    pub model: Option<String>,
+}
+
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+#[derive(serde::Deserialize)]
+struct Completion {
+    choices: Vec<Choice>,
+}
+
+#[derive(serde::Deserialize)]
+struct Choice {
+    message: ChoiceMessage,
+}
+
+#[derive(serde::Deserialize)]
+struct ChoiceMessage {
+    content: String,
+}
+
+/// Sends a single prompt and returns the model's reply.
+pub async fn complete(config: &LlmConfig, system: &str, prompt: &str) -> Option<String> {
+    let body = json!({
+        "model": config.model.as_deref().unwrap_or(DEFAULT_MODEL),
+        "messages": [
+            { "role": "system", "content": system },
+            { "role": "user", "content": prompt },
+        ],
+    });
+
+    let res = reqwest::Client::new()
+        .post(format!(
+            "{}/chat/completions",
+            config.endpoint.trim_end_matches('/')
+        ))
+        .bearer_auth(&config.api_key)
+        .json(&body)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status());
+
+    match res {
+        Ok(res) => match res.json::<Completion>().await {
+            Ok(completion) => completion
+                .choices
+                .into_iter()
+                .next()
+                .map(|c| c.message.content.trim().to_string()),
+            Err(why) => {
+                println!("Error reading completion {:?}", why);
+                None
+            }
+        },
+        Err(why) => {
+            println!("Error requesting completion {:?}", why);
+            None
+        }
+    }
+}
+
+/// Asks for `count` title ideas for `story`.
+pub async fn suggest_titles(config: &LlmConfig, story: &str, count: usize) -> Vec<String> {
+    let system = format!(
+        "You name short collaborative stories written one word at a time. \
+         Reply with {} short title options, one per line, and nothing else.",
+        count
+    );
+    let reply = match complete(config, &system, story).await {
+        Some(reply) => reply,
+        None => return Vec::new(),
+    };
+
+    reply
+        .lines()
+        .map(|l| {
+            l.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == '-' || c == ' ')
+                .trim_matches('"')
+                .trim()
+                .to_string()
+        })
+        .filter(|l| !l.is_empty())
+        .take(count)
+        .collect()
+}
+
+pub async fn summarize(config: &LlmConfig, story: &str) -> Option<String> {
+    complete(
+        config,
+        "You summarize short collaborative stories written one word at a time. \
+         Reply with a 2-3 sentence summary and nothing else.",
+        story,
+    )
+    .await
+}
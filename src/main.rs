@@ -1,25 +1,144 @@
+mod actions;
+mod appeal;
+mod audit;
+mod backup;
+mod bridge;
+mod bus;
+mod card;
+mod chart;
+mod command;
+mod config;
+mod cooldown;
+mod dictionary;
+mod digest;
+mod download;
+mod export;
+mod feed;
+mod forum;
+mod game;
+mod gate;
+mod gist;
+mod grace;
+mod i18n;
+mod illustration;
+mod llm;
+mod maintenance;
+mod mastodon;
+mod matrix;
+mod migrate;
+mod modlog;
+mod modstats;
+mod nudge;
+mod pins;
+mod presence;
+mod prompt;
+mod raid;
+mod recompile;
+mod resync;
+mod retention;
+mod review;
+mod rules_embed;
+mod scheduler;
+mod settings;
+mod setup;
+mod shadowmute;
+mod slash;
+mod slowmode;
+mod sprint;
+mod storage;
+mod story;
+mod subscribe;
+mod telegram;
+mod templates;
+mod topic;
+mod toxicity;
+mod tts;
+mod web;
+mod welcome;
+mod xp;
+
+use std::borrow::Cow;
 use std::collections::HashSet;
 use std::env;
-use std::fs;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
-use censor::Censor;
 use serenity::async_trait;
+use serenity::builder::CreateEmbed;
+use serenity::cache::Cache;
+use serenity::http::Http;
 use serenity::model::{
+    application::interaction::Interaction,
     channel::Message,
     gateway::{GatewayIntents, Ready},
+    guild::Guild,
     permissions::Permissions,
     prelude::*,
 };
 use serenity::prelude::*;
+use serenity::utils::{content_safe, ContentSafeOptions};
+
+use actions::ActionContainer;
+use bus::{BusContainer, BusEvent};
+use command::{parse_command, with_prefix, Command, Tier, DEFAULT_PREFIX};
+use config::{
+    adopt_legacy_config, censors_for, get_config, get_user_prefs, register_guild, set_config,
+    set_user_prefs, Bridge, CardMode, CensorContainer, Config, ConfigContainer, EmojiPolicy,
+    Enforcement, Rules, Severity, SeverityAction, TitleMode,
+};
+use cooldown::{CompileContainer, CooldownContainer};
+use feed::{new_feed, publish, FeedContainer, FeedEvent, Source};
+use game::{check_message, check_phrases, Rejection, WordFilter};
+use grace::GraceContainer;
+use i18n::{tr, tr_args, Locale};
+use modstats::ModStatsContainer;
+use raid::RaidContainer;
+use resync::SeenContainer;
+use setup::SetupContainer;
+use sprint::SprintContainer;
+use storage::{Storage, StorageContainer};
+use story::StoryContainer;
+use topic::TopicContainer;
+use toxicity::{ToxicityAction, ToxicityConfig};
+
+const TITLE_VOTE_EMOJIS: [&str; 3] = [
+    "1\u{fe0f}\u{20e3}",
+    "2\u{fe0f}\u{20e3}",
+    "3\u{fe0f}\u{20e3}",
+];
+const TITLE_VOTE_DURATION: Duration = Duration::from_secs(5 * 60);
+/// How long notices about a refused end marker stay up.
+const NOTICE_DURATION: Duration = Duration::from_secs(15);
 
 struct Handler;
 
 #[async_trait]
 impl EventHandler for Handler {
-    async fn ready(&self, _ctx: Context, ready: Ready) {
+    async fn ready(&self, ctx: Context, ready: Ready) {
         println!("{} is connected!", ready.user.name);
+        slash::register(&ctx).await;
+        // Nothing was seen yet on the first connection.
+        resync::run(&ctx).await;
+    }
+
+    async fn resume(&self, ctx: Context, _: ResumedEvent) {
+        resync::run(&ctx).await;
+    }
+
+    async fn guild_create(&self, ctx: Context, guild: Guild, is_new: bool) {
+        adopt_legacy_config(&ctx.data, &guild).await;
+        retention::guild_returned(&ctx.data, guild.id).await;
+        if is_new && register_guild(&ctx.data, guild.id).await {
+            greet_guild(&ctx, &guild).await;
+        }
+    }
+
+    async fn guild_delete(&self, ctx: Context, incomplete: UnavailableGuild, _: Option<Guild>) {
+        // Outages make guilds unavailable too, but they come back.
+        if !incomplete.unavailable {
+            retention::guild_removed(&ctx.data, incomplete.id).await;
+        }
     }
 
     async fn message(&self, ctx: Context, msg: Message) {
@@ -27,304 +146,2893 @@ impl EventHandler for Handler {
             return;
         }
 
-        if let Some(cmd) = parse_command(&msg) {
-            match cmd {
-                Ok(cmd) => {
-                    run_command(cmd, &msg, &ctx).await;
-                }
-                Err(error) => {
-                    if let Err(why) = msg.reply(&ctx.http, error).await {
-                        println!("Error replying: {:?}", why);
-                    }
-                }
-            };
-            return;
+        // A message the bot trips over is reported, rather than lost in the
+        // console.
+        let handled = tokio::spawn({
+            let (ctx, msg) = (ctx.clone(), msg.clone());
+            async move { handle_message(&ctx, &msg).await }
+        })
+        .await;
+        if let Err(why) = handled {
+            if why.is_panic() {
+                report_panic(&ctx, &msg, why.into_panic()).await;
+            }
         }
+    }
 
-        parse_message(&ctx, &msg).await;
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        match interaction {
+            Interaction::MessageComponent(component)
+                if appeal::is_appeal(&component.data.custom_id) =>
+            {
+                appeal::handle(&ctx, &component).await;
+            }
+            Interaction::MessageComponent(component)
+                if review::is_review(&component.data.custom_id) =>
+            {
+                review::handle(&ctx, &component).await;
+            }
+            Interaction::MessageComponent(component)
+                if download::is_download(&component.data.custom_id) =>
+            {
+                download::handle(&ctx, &component).await;
+            }
+            Interaction::MessageComponent(component)
+                if setup::is_setup(&component.data.custom_id) =>
+            {
+                setup::handle(&ctx, &component).await;
+            }
+            Interaction::ModalSubmit(modal) if settings::is_settings(&modal.data.custom_id) => {
+                settings::submit(&ctx, &modal).await;
+            }
+            Interaction::ApplicationCommand(command) => slash::run(&ctx, &command).await,
+            Interaction::Autocomplete(autocomplete) => {
+                slash::autocomplete(&ctx, &autocomplete).await
+            }
+            _ => {}
+        }
     }
-}
 
-async fn parse_message(ctx: &Context, msg: &Message) {
-    let lock = {
-        let data = ctx.data.read().await;
-        data.get::<ConfigContainer>()
-            .expect("Expected ConfigContainer in TypeMap")
-            .clone()
-    };
-    let channel_id = lock.read().await.channel_id;
-    // println!("{:?}", channel_id);
-    if msg.channel_id != channel_id {
-        return;
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        end_by_reaction(&ctx, &reaction).await;
+        review::approve_by_reaction(&ctx, &reaction).await;
     }
 
-    if "." == msg.content {
-        generate_story(ctx, msg).await;
-        return;
+    async fn message_delete(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        deleted_message_id: MessageId,
+        _guild_id: Option<GuildId>,
+    ) {
+        grace::forget(&ctx, channel_id, deleted_message_id).await;
+        story::delete(&ctx.data, channel_id, &[deleted_message_id]).await;
+        let deleted = BusEvent::WordDeleted {
+            channel_id,
+            message_id: deleted_message_id,
+        };
+        bus::publish(&ctx.data, deleted).await;
     }
 
-    let lock = {
-        let data = ctx.data.read().await;
-        data.get::<CensorContainer>()
-            .expect("Expected CensorContaineronfigContainer in TypeMap")
-            .clone()
-    };
-    let censor = lock.read().await;
+    async fn message_delete_bulk(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        multiple_deleted_messages_ids: Vec<MessageId>,
+        _guild_id: Option<GuildId>,
+    ) {
+        for id in &multiple_deleted_messages_ids {
+            grace::forget(&ctx, channel_id, *id).await;
+        }
+        story::delete(&ctx.data, channel_id, &multiple_deleted_messages_ids).await;
+        for message_id in multiple_deleted_messages_ids {
+            bus::publish(
+                &ctx.data,
+                BusEvent::WordDeleted {
+                    channel_id,
+                    message_id,
+                },
+            )
+            .await;
+        }
+    }
 
-    if !is_valid_message(&msg.content, &censor).await {
-        if let Err(why) = msg.delete(&ctx.http).await {
-            println!("Error replying: {:?}", why);
+    async fn message_update(
+        &self,
+        ctx: Context,
+        _old_if_available: Option<Message>,
+        _new: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        // Only flagged messages can be fixed by editing them.
+        if !grace::is_pending(&ctx.data, event.id).await {
+            return;
+        }
+        match event.channel_id.message(&ctx.http, event.id).await {
+            Ok(mut msg) => {
+                msg.guild_id = event.guild_id;
+                parse_message(&ctx, &msg).await;
+            }
+            Err(why) => println!("Error fetching edited message {:?}", why),
         }
     }
 }
 
-async fn is_valid_message(msg: &str, censor: &Censor) -> bool {
-    let words: Vec<&str> = msg.split_whitespace().collect();
+/// Ends the story when its latest message is reacted to with the guild's
+/// end reaction, by posting the end marker. The compile cooldown and the
+/// contributors needed apply as they do to a marker posted by hand.
+async fn end_by_reaction(ctx: &Context, reaction: &Reaction) {
+    let guild_id = match reaction.guild_id {
+        Some(id) => id,
+        None => return,
+    };
+    let config = get_config(&ctx.data, guild_id).await;
+    if config.end_reaction != Some(reaction.emoji.to_string()) {
+        return;
+    }
+    let channel_id = reaction.channel_id;
+    let venue = forum::venue(&ctx.cache, guild_id, channel_id);
+    if !config.is_story_channel(venue) {
+        return;
+    }
+    let rules = config.rules(venue);
+    let seconds = rules.compile_cooldown;
+    if cooldown::compile_wait(&ctx.data, channel_id, seconds)
+        .await
+        .is_some()
+    {
+        return;
+    }
+    let compiled = config.compiled.get(&channel_id).copied();
+    let story = story_messages(&ctx.http, channel_id, None, compiled).await;
+    if story.first().map(|m| m.id) != Some(reaction.message_id) {
+        return;
+    }
+    let bridge = config.bridge.as_ref();
+    if story_contributors(&story, bridge).len() < rules.min_contributors {
+        return;
+    }
 
-    if words.len() > 2 {
-        return false;
+    match channel_id.say(&ctx.http, ".").await {
+        Ok(end) => {
+            let end = StoryEnd::Marker(end.id);
+            generate_story(&ctx.http, &ctx.cache, &ctx.data, channel_id, end, guild_id).await
+        }
+        Err(why) => println!("Error ending story by reaction {:?}", why),
     }
+}
 
-    if words.len() == 2 && !(words[0].len() <= 2 || words[1].len() <= 2) {
-        return false;
+async fn handle_message(ctx: &Context, msg: &Message) {
+    let prefix = match msg.guild_id {
+        Some(guild_id) => get_config(&ctx.data, guild_id).await.prefix().to_string(),
+        None => DEFAULT_PREFIX.to_string(),
+    };
+    if let Some(cmd) = parse_command(&msg.content, &prefix) {
+        match cmd {
+            Ok(cmd) => {
+                run_command(cmd, msg, ctx).await;
+            }
+            Err(error) => {
+                if let Err(why) = msg.reply(&ctx.http, with_prefix(error, &prefix)).await {
+                    println!("Error replying: {:?}", why);
+                }
+            }
+        };
+        return;
     }
 
-    if censor.check(msg) {
-        return false;
+    parse_message(ctx, msg).await;
+}
+
+/// Tells a guild that just added the bot how to set it up.
+async fn greet_guild(ctx: &Context, guild: &Guild) {
+    let channel_id = match guild.system_channel_id {
+        Some(id) => id,
+        None => return,
+    };
+    let locale = get_config(&ctx.data, guild.id).await.locale;
+    if let Err(why) = channel_id.say(&ctx.http, tr(locale, "guild-welcome")).await {
+        println!("Error greeting new guild {:?}", why);
     }
+}
 
-    true
+/// Counts and reports the panic handling `msg` ended in.
+async fn report_panic(ctx: &Context, msg: &Message, panic: Box<dyn std::any::Any + Send>) {
+    let queue = actions::queue(&ctx.data).await;
+    queue
+        .metrics
+        .panics
+        .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+    let reason = match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+        (Some(reason), _) => reason.to_string(),
+        (_, Some(reason)) => reason.clone(),
+        _ => "unknown panic".to_string(),
+    };
+    let notice = format!(
+        "Failed to handle a message from {} ({}): {} {}",
+        msg.author.tag(),
+        msg.author.id,
+        reason,
+        msg.link()
+    );
+    match msg.guild_id {
+        Some(guild_id) => {
+            let config = get_config(&ctx.data, guild_id).await;
+            modlog::post(&ctx.http, &config, &notice).await;
+        }
+        None => println!("{}", notice),
+    }
 }
 
-async fn generate_story(ctx: &Context, msg: &Message) {
-    // Get up to 250 words.
-    let req = msg
-        .channel_id
-        .messages(&ctx.http, |r| r.before(msg.id).limit(250))
-        .await;
+async fn parse_message(ctx: &Context, msg: &Message) {
+    let guild_id = match msg.guild_id {
+        Some(id) => id,
+        None => return,
+    };
+    let config = get_config(&ctx.data, guild_id).await;
+    // println!("{:?}", channel_id);
+    let venue = forum::venue(&ctx.cache, guild_id, msg.channel_id);
+    if !config.is_story_channel(venue) {
+        return;
+    }
+    // The opening message of a forum post isn't part of its story.
+    if msg.id.0 == msg.channel_id.0 {
+        return;
+    }
+    resync::seen(&ctx.data, msg, guild_id).await;
+    let rules = config.rules(venue);
 
-    if let Ok(messages) = req {
-        let mut char_count = 0;
-        let mut title = "Story so far";
-        let mut story: Vec<String> = Vec::new();
+    if shadowmute::is_muted(&config, msg.author.id) {
+        shadowmute::hide(ctx, msg);
+        return;
+    }
 
-        for m in messages.iter() {
-            if "." == m.content {
-                break;
-            }
+    // Not even the end marker goes through.
+    if config.paused.contains(&venue) {
+        actions::delete(ctx, msg.channel_id, msg.id).await;
+        notify_deletion(ctx, msg, &config, Rejection::Paused.key()).await;
+        return;
+    }
 
-            if m.author.bot {
-                continue;
-            }
+    if config
+        .quiet_hours
+        .is_some_and(|q| q.contains(config.local(scheduler::now())))
+    {
+        actions::delete(ctx, msg.channel_id, msg.id).await;
+        notify_deletion(ctx, msg, &config, Rejection::QuietHours.key()).await;
+        return;
+    }
+    if !gate::check(ctx, msg, &config).await {
+        return;
+    }
 
-            char_count += m.content.len() + 1; // +1 for space
-            if char_count > 4096 {
-                send_story(ctx, msg, &mut story, title).await;
-                char_count = m.content.len();
-                story.clear();
-                story.push(m.content.clone());
-                title = "continued";
-                continue;
+    if "." == msg.content {
+        let seconds = rules.compile_cooldown;
+        if let Some(wait) = cooldown::compile_wait(&ctx.data, msg.channel_id, seconds).await {
+            compile_cooling_down(ctx, msg, &config, wait).await;
+            return;
+        }
+        if rules.min_contributors > 1 {
+            let bridge = config.bridge.as_ref();
+            let compiled = config.compiled.get(&msg.channel_id).copied();
+            let story = story_messages(&ctx.http, msg.channel_id, Some(msg.id), compiled).await;
+            let count = story_contributors(&story, bridge).len();
+            if count < rules.min_contributors {
+                too_few_contributors(ctx, msg, &config, count, rules.min_contributors).await;
+                return;
             }
-
-            story.push(m.content.clone());
         }
-        send_story(ctx, msg, &mut story, title).await;
+        generate_story(
+            &ctx.http,
+            &ctx.cache,
+            &ctx.data,
+            msg.channel_id,
+            StoryEnd::Marker(msg.id),
+            guild_id,
+        )
+        .await;
+        return;
+    }
+
+    let lock = {
+        let data = ctx.data.read().await;
+        data.get::<CensorContainer>()
+            .expect("Expected CensorContainer in TypeMap")
+            .clone()
+    };
+    let censors = lock.read().await;
+    let censor = match censors.get(&guild_id) {
+        Some(censor) => censor,
+        None => return,
     };
-}
 
-async fn send_story(ctx: &Context, msg: &Message, story: &mut [String], title: &str) {
-    if story.is_empty() {
+    let cooldown = match sprint::is_running(&ctx.data, msg.channel_id).await {
+        true => 0,
+        false => rules.cooldown,
+    };
+    if cooldown::is_cooling_down(&ctx.data, msg.channel_id, msg.author.id, cooldown).await {
+        actions::delete(ctx, msg.channel_id, msg.id).await;
+        notify_deletion(ctx, msg, &config, Rejection::Cooldown.key()).await;
         return;
     }
+    if rules.max_per_user > 0 {
+        let added = match story::added_by(&ctx.data, msg.channel_id, msg.author.id).await {
+            Some(added) => added,
+            // The story started before the bot did, so count its messages.
+            None => {
+                let bridge = config.bridge.as_ref();
+                let compiled = config.compiled.get(&msg.channel_id).copied();
+                story_messages(&ctx.http, msg.channel_id, Some(msg.id), compiled)
+                    .await
+                    .iter()
+                    .take_while(|m| "." != m.content)
+                    .filter(|m| is_contribution(m, bridge) && m.author.id == msg.author.id)
+                    .count()
+            }
+        };
+        if added >= rules.max_per_user {
+            actions::delete(ctx, msg.channel_id, msg.id).await;
+            notify_deletion(ctx, msg, &config, Rejection::StoryShare.key()).await;
+            return;
+        }
+    }
 
-    story.reverse();
-    match msg
-        .channel_id
-        .send_message(&ctx.http, |m| {
-            m.embed(|e| e.title(title).description(story.join(" ")))
-        })
+    let content = game::strip_markup(&msg.content);
+    let lookback = config.phrase_lookback();
+    let phrases = match lookback {
+        0 => Ok(()),
+        _ => check_phrases(
+            &recent_words(ctx, msg, &config, lookback as u64).await,
+            &content,
+            &config.banned_phrases,
+        ),
+    };
+    // Events ban whole words and phrases within the contribution.
+    let event_words = match config.active_event(&config.month_day()) {
+        Some(event) => {
+            check_phrases(&[], &content, &event.banned_words).map_err(|_| Rejection::BannedWord)
+        }
+        None => Ok(()),
+    };
+    let dictionary = match config.dictionary {
+        true => dictionary::check(config.locale, &content),
+        false => Ok(()),
+    };
+    if let Err(rejection) = check_message(&msg.content, censor, &rules)
         .await
+        .and(phrases)
+        .and(event_words)
+        .and(dictionary)
     {
-        Ok(m) => {
-            if let Err(why) = m.pin(&ctx.http).await {
-                println!("Failed to pin message {:?}", why);
-            }
+        if rejection == Rejection::TooManyWords
+            && rules.enforcement == Enforcement::Sanitize
+            && sanitize(ctx, msg, &config, &rules, censor).await
+        {
+            return;
         }
-        Err(why) => println!("Error generating story: {:?}", why),
-    };
-}
-
-#[derive(Debug)]
-enum Command {
-    SetChannel(ChannelId),
-    BanWord(String),
-    UnbanWord(String),
-}
-
-fn parse_command(msg: &Message) -> Option<Result<Command, &'static str>> {
-    if !msg.content.starts_with("one-word") {
-        return None;
+        if config.grace_period > 0 {
+            grace::flag(ctx, msg, &config, rejection).await;
+            return;
+        }
+        actions::delete(ctx, msg.channel_id, msg.id).await;
+        notify_deletion(ctx, msg, &config, rejection.key()).await;
+        return;
     }
+    grace::clear(ctx, msg).await;
 
-    let words: Vec<&str> = msg.content.split_whitespace().collect();
-    if words.len() < 3 {
-        return Some(Err("Usage: one-word <set-channel|ban|unban> <arg>"));
+    if let Some((severity, action)) = censor.severity(&content, rules.match_mode) {
+        let reason = format!("{:?} word", severity);
+        if action == SeverityAction::Review && review::quarantine(ctx, msg, &config, &reason).await
+        {
+            return;
+        }
+        // Without a review channel, words up for review are deleted.
+        if action >= SeverityAction::Review {
+            actions::delete(ctx, msg.channel_id, msg.id).await;
+            notify_deletion(ctx, msg, &config, Rejection::BannedWord.key()).await;
+        }
+        if action == SeverityAction::DeleteWarn {
+            warn_severity(ctx, msg, &config, severity).await;
+        }
+        if action >= SeverityAction::Review {
+            return;
+        }
     }
 
-    let arg = words[2].to_string();
-
-    match words[1].to_lowercase().as_str() {
-        "set-channel" => {
-            let channel_id = arg
-                .replace("<#", "")
-                .replace('>', "")
-                .parse::<u64>()
-                .map(ChannelId);
-            match channel_id {
-                Ok(id) => Some(Ok(Command::SetChannel(id))),
-                _ => Some(Err("Invalid channel")),
-            }
+    if let Some(toxicity) = &config.toxicity {
+        if !check_toxicity(ctx, msg, &config, toxicity).await {
+            return;
         }
-        "ban" => Some(Ok(Command::BanWord(arg))),
-        "unban" => Some(Ok(Command::UnbanWord(arg))),
-        _ => Some(Err("Invalid command")),
     }
+
+    if review::needs_approval(msg.author.id, &config)
+        && review::quarantine(ctx, msg, &config, "first words of a new contributor").await
+    {
+        return;
+    }
+
+    cooldown::touch(&ctx.data, msg.channel_id, msg.author.id).await;
+    story::accept(&ctx.data, msg).await;
+    topic::touch(&ctx.data, guild_id, msg.channel_id).await;
+    bus::publish(&ctx.data, BusEvent::word(guild_id, msg)).await;
+    storage::record_contribution(&ctx.data, guild_id, msg, None).await;
+    review::count_accepted(&ctx.data, guild_id, msg.author.id).await;
+    complete_challenges(ctx, msg, guild_id, &config).await;
+    xp::award(&ctx.data, &ctx.http, msg, guild_id, &config).await;
+    welcome::greet(ctx, msg, guild_id, &config, venue).await;
+    publish(
+        &ctx.data,
+        FeedEvent::Word {
+            channel_id: msg.channel_id,
+            word: msg.content.clone(),
+            author: msg.author.name.clone(),
+            source: Source::Discord,
+        },
+    )
+    .await;
 }
 
-async fn run_command(cmd: Command, msg: &Message, ctx: &Context) {
-    if !msg_member_has_perm(ctx, msg, Permissions::ADMINISTRATOR).await {
-        if let Err(why) = msg
-            .reply(&ctx.http, "Only admins are allowed to update settings.")
-            .await
-        {
-            println!("Error replying: {:?}", why);
-        }
+/// Congratulates the author of the accepted `msg` on each challenge word
+/// it works into the story, and closes those challenges.
+async fn complete_challenges(ctx: &Context, msg: &Message, guild_id: GuildId, config: &Config) {
+    let words = game::phrase_words(&msg.content);
+    let completed: Vec<String> = config
+        .challenges
+        .iter()
+        .filter(|c| words.contains(c))
+        .cloned()
+        .collect();
+    if completed.is_empty() {
         return;
     }
+    set_config(&ctx.data, guild_id, |config| {
+        config.challenges.retain(|c| !completed.contains(c));
+    })
+    .await;
 
-    match cmd {
-        Command::SetChannel(id) => {
-            set_config(ctx, |config: &mut Config| {
-                config.channel_id = id;
-            })
-            .await;
-        }
-        Command::BanWord(word) => {
-            set_config(ctx, |config| {
-                config.banned_words.insert(word);
-            })
+    for word in completed {
+        let record = storage::ChallengeRecord {
+            guild_id,
+            channel_id: msg.channel_id,
+            message_id: msg.id,
+            word: word.clone(),
+            author: msg.author.name.clone(),
+            author_id: Some(msg.author.id),
+            at: scheduler::now(),
+        };
+        storage::storage(&ctx.data)
+            .await
+            .record_challenge(record)
             .await;
+        let congrats = tr_args(
+            config.locale,
+            "challenge-complete",
+            &[("user", &msg.author.mention().to_string()), ("word", &word)],
+        );
+        if let Err(why) = msg.channel_id.say(&ctx.http, congrats).await {
+            println!("Error congratulating on challenge {:?}", why);
         }
-        Command::UnbanWord(word) => {
-            set_config(ctx, |config| {
-                config.banned_words.remove(&word);
-            })
-            .await;
+    }
+}
+
+/// Warns the author of the deleted `msg` in the story channel, and reports
+/// it to the mod log.
+async fn warn_severity(ctx: &Context, msg: &Message, config: &Config, severity: Severity) {
+    let warning = tr_args(
+        config.locale,
+        "severity-warning",
+        &[("user", &msg.author.mention().to_string())],
+    );
+    if let Err(why) = msg.channel_id.say(&ctx.http, warning).await {
+        println!("Error warning author {:?}", why);
+    }
+    let notice = format!(
+        "Warned {} for a {:?} word, \"{}\", in <#{}>",
+        msg.author.tag(),
+        severity,
+        msg.content,
+        msg.channel_id
+    );
+    modlog::post(&ctx.http, config, &notice).await;
+}
+
+/// Removes the end marker `msg`, which came too soon after the last
+/// compiled story, and shows a notice until the story can be compiled.
+async fn compile_cooling_down(ctx: &Context, msg: &Message, config: &Config, wait: Duration) {
+    actions::delete(ctx, msg.channel_id, msg.id).await;
+    let deadline = format!("<t:{}:R>", scheduler::now() + wait.as_secs() + 1);
+    let notice = tr_args(
+        config.locale,
+        "compile-cooldown",
+        &[("deadline", &deadline)],
+    );
+    let notice = match msg.channel_id.say(&ctx.http, notice).await {
+        Ok(notice) => notice,
+        Err(why) => {
+            println!("Error posting compile cooldown notice {:?}", why);
+            return;
         }
     };
 
-    if let Err(why) = msg.reply(&ctx.http, "Settings updated").await {
-        println!("Error replying: {:?}", why);
-    }
+    let ctx = ctx.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(wait).await;
+        actions::delete(&ctx, notice.channel_id, notice.id).await;
+    });
 }
 
-async fn msg_member_has_perm(ctx: &Context, msg: &Message, required_perm: Permissions) -> bool {
-    if let Ok(member) = msg.member(&ctx.http).await {
-        if let Ok(perms) = member.permissions(&ctx.cache) {
-            return perms.contains(required_perm);
+/// Removes the end marker `msg`, which came before enough different
+/// contributors joined the story, and says how many are still missing.
+async fn too_few_contributors(
+    ctx: &Context,
+    msg: &Message,
+    config: &Config,
+    count: usize,
+    needed: usize,
+) {
+    actions::delete(ctx, msg.channel_id, msg.id).await;
+    let notice = tr_args(
+        config.locale,
+        "story-needs-contributors",
+        &[
+            ("count", &count.to_string()),
+            ("needed", &needed.to_string()),
+        ],
+    );
+    let notice = match msg.channel_id.say(&ctx.http, notice).await {
+        Ok(notice) => notice,
+        Err(why) => {
+            println!("Error posting contributor notice {:?}", why);
+            return;
         }
-    }
+    };
 
-    false
+    let ctx = ctx.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(NOTICE_DURATION).await;
+        actions::delete(&ctx, notice.channel_id, notice.id).await;
+    });
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-struct Config {
+/// Up to 250 of the latest messages in `channel_id`, before `before` if
+/// given, newest first, leaving out those up to `compiled`, the last word
+/// of the previous story. The story in progress starts after the first end
+/// marker among them.
+async fn story_messages(
+    http: &Http,
     channel_id: ChannelId,
-    banned_words: HashSet<String>,
+    before: Option<MessageId>,
+    compiled: Option<MessageId>,
+) -> Vec<Message> {
+    let req = channel_id
+        .messages(http, |r| {
+            if let Some(before) = before {
+                r.before(before);
+            }
+            r.limit(250)
+        })
+        .await;
+    match req {
+        Ok(messages) => messages
+            .into_iter()
+            .filter(|m| Some(m.id) > compiled)
+            .collect(),
+        Err(why) => {
+            println!("Error reading story channel {:?}", why);
+            Vec::new()
+        }
+    }
+}
+
+/// Replaces `msg` with just its first word, if that is a valid
+/// contribution on its own. Returns whether it did.
+async fn sanitize(
+    ctx: &Context,
+    msg: &Message,
+    config: &Config,
+    rules: &Rules,
+    censor: &WordFilter,
+) -> bool {
+    let first = game::split_words(&msg.content, rules.segmentation)
+        .first()
+        .copied();
+    let (guild_id, first) = match (msg.guild_id, first) {
+        (Some(guild_id), Some(first)) => (guild_id, first),
+        _ => return false,
+    };
+    let lookback = config.phrase_lookback();
+    let phrases = match lookback {
+        0 => Ok(()),
+        _ => check_phrases(
+            &recent_words(ctx, msg, config, lookback as u64).await,
+            first,
+            &config.banned_phrases,
+        ),
+    };
+    if check_message(first, censor, rules)
+        .await
+        .and(phrases)
+        .is_err()
+    {
+        return false;
+    }
+
+    if let Err(why) = msg.delete(&ctx.http).await {
+        println!("Error deleting message {:?}", why);
+        return false;
+    }
+    let word = first.to_string();
+    bridge::post_as(
+        &ctx.http,
+        &ctx.data,
+        guild_id,
+        msg.channel_id,
+        word,
+        &msg.author,
+    )
+    .await
 }
 
-struct ConfigContainer;
-impl TypeMapKey for ConfigContainer {
-    type Value = Arc<RwLock<Config>>;
+/// Up to `count` contributions to the story before `msg`, oldest first.
+async fn recent_words(ctx: &Context, msg: &Message, config: &Config, count: u64) -> Vec<String> {
+    if let Some(recent) = story::recent(&ctx.data, msg.channel_id, count as usize).await {
+        return recent
+            .into_iter()
+            .filter(|c| c.id < msg.id)
+            .map(|c| c.word)
+            .collect();
+    }
+
+    let bridge = config.bridge.as_ref();
+    let messages = msg
+        .channel_id
+        .messages(&ctx.http, |r| r.before(msg.id).limit(count))
+        .await
+        .unwrap_or_default();
+    let mut words: Vec<String> = messages
+        .iter()
+        .take_while(|m| "." != m.content)
+        .filter(|m| is_contribution(m, bridge))
+        .map(|m| m.content.clone())
+        .collect();
+    words.reverse();
+    words
 }
 
-async fn set_config<F>(ctx: &Context, update: F)
-where
-    F: FnOnce(&mut Config),
-{
-    let lock = {
-        let data = ctx.data.read().await;
-        data.get::<ConfigContainer>()
-            .expect("Expected ConfigContainer in TypeMap")
-            .clone()
+/// Scores `msg` together with the words before it and acts on it if it is
+/// too toxic. Returns whether the contribution is kept.
+async fn check_toxicity(
+    ctx: &Context,
+    msg: &Message,
+    config: &Config,
+    toxicity: &ToxicityConfig,
+) -> bool {
+    let mut words = recent_words(ctx, msg, config, toxicity::CONTEXT_WORDS as u64).await;
+    words.push(msg.content.clone());
+
+    let score = match toxicity::score(toxicity, &words.join(" ")).await {
+        Some(score) if score >= toxicity.threshold => score,
+        _ => return true,
     };
-    {
-        let mut config = lock.write().await;
-        update(&mut config);
-
-        let mut data = ctx.data.write().await;
-        let censor = censor::Censor::Custom(config.banned_words.clone());
-        data.insert::<CensorContainer>(Arc::new(RwLock::new(censor)));
-
-        match env::var("CONFIG_FILE") {
-            Ok(path) => {
-                let c = config.clone();
-                if let Err(why) = fs::write(path, serde_json::to_string(&c).unwrap()) {
-                    println!("Error wriring config {:?}", why);
-                }
-            }
-            _ => {
-                println!("Mising CONFIG_FILE env. Configuration not saved.");
+
+    let notice = format!(
+        "Toxicity {:.2} for \"{}\" by {} in <#{}>",
+        score,
+        msg.content,
+        msg.author.tag(),
+        msg.channel_id
+    );
+    match toxicity.action {
+        ToxicityAction::Delete => {
+            actions::delete(ctx, msg.channel_id, msg.id).await;
+            modlog::post(&ctx.http, config, &format!("Deleted: {}", notice)).await;
+            notify_deletion(ctx, msg, config, "reason-toxicity").await;
+            return false;
+        }
+        ToxicityAction::Flag => {
+            actions::react(ctx, msg.channel_id, msg.id, '⚠').await;
+            modlog::post(
+                &ctx.http,
+                config,
+                &format!("Flagged: {} {}", notice, msg.link()),
+            )
+            .await;
+        }
+        ToxicityAction::Review => {
+            let reason = format!("toxicity {:.2}", score);
+            if review::quarantine(ctx, msg, config, &reason).await {
+                return false;
             }
-        };
+            // Without a review channel, it is flagged instead.
+            actions::react(ctx, msg.channel_id, msg.id, '⚠').await;
+            modlog::post(
+                &ctx.http,
+                config,
+                &format!("Flagged: {} {}", notice, msg.link()),
+            )
+            .await;
+        }
+        ToxicityAction::Log => {
+            modlog::post(&ctx.http, config, &format!("{} {}", notice, msg.link())).await;
+        }
     }
-}
 
-struct CensorContainer;
-impl TypeMapKey for CensorContainer {
-    type Value = Arc<RwLock<Censor>>;
+    true
 }
 
-fn read_config() -> Option<Config> {
-    match env::var("CONFIG_FILE") {
-        Ok(path) => match fs::read_to_string(path) {
-            Ok(contents) => match serde_json::from_str::<Config>(&contents) {
-                Ok(config) => Some(config),
-                _ => None,
-            },
-            _ => None,
-        },
-        _ => None,
+/// Tells the author of a deleted contribution why it was removed, if the
+/// guild has a deletion DM template. `reason` is a translation key.
+async fn notify_deletion(ctx: &Context, msg: &Message, config: &Config, reason: &'static str) {
+    if let Some(guild_id) = msg.guild_id {
+        modstats::count(&ctx.data, guild_id, reason).await;
+    }
+    raid::invalid(ctx, msg, config).await;
+    let template = match &config.templates.deletion_dm {
+        Some(template) => template,
+        None => return,
+    };
+    if !get_user_prefs(&ctx.data, msg.author.id).await.deletion_dms {
+        return;
+    }
+    let text = templates::render(
+        template,
+        &[
+            ("user", &msg.author.name),
+            ("word", &msg.content),
+            ("reason", &tr(config.locale, reason)),
+        ],
+    );
+    let sent = msg
+        .author
+        .direct_message(&ctx.http, |m| {
+            m.content(text);
+            // Appeals are decided in the mod log, so there must be one.
+            if let (Some(_), Some(guild_id)) = (config.mod_log_channel, msg.guild_id) {
+                m.embed(|e| e.description(&msg.content)).components(|c| {
+                    appeal::button(
+                        c,
+                        &tr(config.locale, "appeal-button"),
+                        guild_id,
+                        msg.channel_id,
+                    )
+                });
+            }
+            m
+        })
+        .await;
+    if let Err(why) = sent {
+        println!("Error sending deletion DM {:?}", why);
     }
 }
 
-#[tokio::main]
-async fn main() {
-    let token = env::var("DISCORD_TOKEN").expect("Missing discord token.");
-    let intents = GatewayIntents::GUILDS
-        | GatewayIntents::GUILD_MEMBERS
-        | GatewayIntents::GUILD_MESSAGES
-        | GatewayIntents::MESSAGE_CONTENT;
-    let mut client = Client::builder(token, intents)
-        .event_handler(Handler)
-        .await
-        .expect("Error creating client");
+/// Compiles the story written in `channel_id` up to `end`.
+async fn generate_story(
+    http: &Arc<Http>,
+    cache: &Cache,
+    data: &RwLock<TypeMap>,
+    channel_id: ChannelId,
+    end: StoryEnd,
+    guild_id: GuildId,
+) {
+    let before = match &end {
+        StoryEnd::Marker(id) => *id,
+        StoryEnd::Through(last, _) => last.id,
+    };
+    // Get up to 250 words.
+    let req = channel_id
+        .messages(http, |r| r.before(before).limit(250))
+        .await;
 
-    {
-        let mut data = client.data.write().await;
-        let default_config = read_config().unwrap_or_else(|| Config {
-            channel_id: ChannelId(0),
-            banned_words: HashSet::new(),
-        });
-        data.insert::<ConfigContainer>(Arc::new(RwLock::new(default_config.clone())));
+    if let Ok(mut messages) = req {
+        if let StoryEnd::Through(last, _) = &end {
+            messages.insert(0, (**last).clone());
+        }
+        let config = get_config(data, guild_id).await;
+        // Without an end marker after it, the last story ends at the word
+        // it was compiled up to.
+        if let Some(compiled) = config.compiled.get(&channel_id) {
+            messages.retain(|m| m.id > *compiled);
+        }
+        let bridge = config.bridge.as_ref();
 
-        let censor = censor::Censor::Custom(default_config.banned_words);
-        data.insert::<CensorContainer>(Arc::new(RwLock::new(censor)));
-    };
+        // Nothing was added since the last end marker, or the story was
+        // compiled already.
+        let newest = match newest_contribution(&messages, bridge) {
+            Some(newest) if config.compiled.get(&channel_id) != Some(&newest) => newest,
+            _ => return,
+        };
+        set_config(data, guild_id, |config| {
+            config.compiled.insert(channel_id, newest);
+        })
+        .await;
+        cooldown::compiled(data, channel_id).await;
 
-    if let Err(why) = client.start().await {
-        println!("Client error: {:?}", why);
+        let post = forum::post(cache, guild_id, channel_id);
+        let venue = post.as_ref().map_or(channel_id, |p| p.forum);
+        let rules = config.rules(venue);
+        let emoji = rules.emoji_policy;
+        let deleted = story::finish(data, channel_id).await;
+        let gaps = match config.mark_gaps {
+            true => deleted.as_slice(),
+            false => &[],
+        };
+        let words = story_words(&messages, &config, emoji, gaps, cache, guild_id);
+        let filter = WordFilter::new(&config);
+        let text = words.iter().rev().cloned().collect::<Vec<_>>().join(" ");
+        let word_count = text.split_whitespace().count().to_string();
+        let contributors = story_contributors(&messages, bridge);
+        let trivia = story_trivia(data, guild_id, config.locale, &messages, bridge).await;
+
+        let default_title = match (post, &config.templates.story_title) {
+            (Some(post), _) => post.title,
+            (None, Some(template)) => templates::render(
+                template,
+                &[
+                    ("word_count", &word_count),
+                    ("contributors", &contributors.join(", ")),
+                ],
+            ),
+            (None, None) => tr(config.locale, "story-title"),
+        };
+        let mut vote_options = Vec::new();
+        let story_title = match (&config.next_title, &config.llm, config.ai_titles) {
+            (Some(title), _, _) => title.clone(),
+            (None, Some(llm), TitleMode::Suggest) => llm::suggest_titles(llm, &text, 1)
+                .await
+                .pop()
+                .unwrap_or(default_title),
+            (None, Some(llm), TitleMode::Vote) => {
+                vote_options = llm::suggest_titles(llm, &text, TITLE_VOTE_EMOJIS.len()).await;
+                default_title
+            }
+            _ => default_title,
+        };
+        if config.next_title.is_some() {
+            set_config(data, guild_id, |config| config.next_title = None).await;
+        }
+
+        let gist_url = match &config.gist_token {
+            Some(token) => gist::publish(token, &story_title, &text).await,
+            None => None,
+        };
+        let gist_url = gist_url.as_deref();
+        let warnings = config.content_warnings(&text);
+        let warning = match warnings.is_empty() {
+            true => None,
+            false => {
+                let warning = tr_args(
+                    config.locale,
+                    "content-warning",
+                    &[("topics", &warnings.join(", "))],
+                );
+                // Longest embed author name.
+                Some(warning.chars().take(256).collect::<String>())
+            }
+        };
+        let download = tr(config.locale, "story-download");
+        let thumbnail = match config.embed.thumbnail {
+            true => cache.guild_field(guild_id, |g| g.icon_url()).flatten(),
+            false => None,
+        };
+        let style = StoryStyle {
+            pin: !config.skip_pins,
+            color: config
+                .active_event(&config.month_day())
+                .and_then(|e| e.color)
+                .or(config.embed.color),
+            spoiler: !warnings.is_empty(),
+            thumbnail: thumbnail.as_deref(),
+            footer: config.embed.footer.as_deref(),
+            download: &download,
+        };
+        let summary = match &config.llm {
+            Some(llm) if config.summaries => llm::summarize(llm, &text).await,
+            _ => None,
+        };
+        let cover = match &config.illustrations {
+            Some(images) => illustration::cover(images, summary.as_deref().unwrap_or(&text)).await,
+            None => None,
+        };
+        let card = match config.story_cards {
+            CardMode::Off => None,
+            _ => Some(card::render(&story_title, &text, &contributors)),
+        };
+        let extras = StoryExtras {
+            summary: summary
+                .as_deref()
+                .map(|s| (tr(config.locale, "story-summary"), s)),
+            trivia: trivia
+                .as_deref()
+                .map(|t| (tr(config.locale, "story-trivia"), t)),
+            warning: warning.as_deref(),
+            cover: cover.as_deref(),
+            card: card.as_deref(),
+        };
+
+        let continued = tr(config.locale, "story-continued");
+        let archived = storage::storage(data)
+            .await
+            .stories(guild_id, 0)
+            .await
+            .iter()
+            .filter_map(|s| s.number)
+            .max()
+            .unwrap_or(0);
+        // Taken under the config lock, so stories compiled at the same time
+        // get numbers of their own.
+        let mut number = 0;
+        set_config(data, guild_id, |config| {
+            number = config.next_story_number.max(archived + 1);
+            config.next_story_number = number + 1;
+        })
+        .await;
+        let embed_title = embed_title(&config, &story_title, number);
+
+        // The card stands in for the story text.
+        let words = match config.story_cards {
+            CardMode::Only => &[],
+            _ => words.as_slice(),
+        };
+        let words: Vec<String> = words
+            .iter()
+            .rev()
+            .map(|word| match filter.severity(word, rules.match_mode) {
+                // Spoilering the whole story already hides the word.
+                Some((_, SeverityAction::Spoiler)) if !style.spoiler => format!("||{}||", word),
+                _ => word.clone(),
+            })
+            .collect();
+        let lengths = EmbedLengths {
+            first: embed_title.len() + extras.len(),
+            rest: continued.len(),
+            footer: style.footer.map_or(0, str::len),
+            // Spoiler markup counts towards the length of the description.
+            markup: if style.spoiler { 4 } else { 0 },
+        };
+
+        let mut posted: Option<Message> = None;
+        for (i, embeds) in paginate(&words, lengths).iter().enumerate() {
+            let (title, extras, style) = match i {
+                0 => (embed_title.as_str(), extras.clone(), style),
+                // Only the start of the story is pinned.
+                _ => (
+                    continued.as_str(),
+                    StoryExtras::default(),
+                    StoryStyle {
+                        pin: false,
+                        ..style
+                    },
+                ),
+            };
+            let story_id = posted.as_ref().map(|m| m.id);
+            let sent = send_story(
+                http, channel_id, embeds, title, &continued, gist_url, style, extras, story_id,
+            )
+            .await;
+            posted = posted.or(sent);
+        }
+
+        let first = messages
+            .iter()
+            .take_while(|m| "." != m.content)
+            .filter(|m| is_contribution(m, bridge))
+            .last();
+        let (ender, ended_at) = match end {
+            StoryEnd::Marker(id) => match channel_id.message(http, id).await {
+                Ok(end) => (Some(end.author.name), id.created_at()),
+                Err(why) => {
+                    println!("Error fetching end marker {:?}", why);
+                    (None, id.created_at())
+                }
+            },
+            StoryEnd::Through(last, by) => (Some(by), last.timestamp),
+        };
+        let record = storage::StoryRecord {
+            guild_id,
+            channel_id,
+            message_id: posted.as_ref().map(|m| m.id),
+            number: Some(number),
+            title: story_title.clone(),
+            text: text.clone(),
+            contributors: contributors.clone(),
+            at: scheduler::now(),
+            started_at: first.map(|m| m.timestamp.unix_timestamp() as u64),
+            ended_at: Some(ended_at.unix_timestamp() as u64),
+            starter: first.map(|m| m.author.name.clone()),
+            ender,
+            rules: Some(rules),
+            revisions: Vec::new(),
+        };
+        storage::storage(data).await.archive_story(record).await;
+
+        if let (Some(posted), false) = (&posted, config.subscribers.is_empty()) {
+            let delivery = subscribe::Delivery {
+                guild: cache
+                    .guild_field(guild_id, |g| g.name.clone())
+                    .unwrap_or_default(),
+                title: story_title.clone(),
+                text: text.clone(),
+                link: gist_url
+                    .map(str::to_string)
+                    .unwrap_or_else(|| posted.link()),
+                locale: config.locale,
+            };
+            let mut subscribers = HashSet::new();
+            for user in &config.subscribers {
+                if get_user_prefs(data, *user).await.story_dms {
+                    subscribers.insert(*user);
+                }
+            }
+            tokio::spawn(subscribe::notify(http.clone(), subscribers, delivery));
+        }
+
+        let completed = BusEvent::StoryCompleted {
+            guild_id,
+            channel_id,
+            message_id: posted.as_ref().map(|m| m.id),
+            number,
+            title: story_title.clone(),
+            story: text.clone(),
+        };
+        bus::publish(data, completed).await;
+        topic::touch(data, guild_id, channel_id).await;
+        publish(
+            data,
+            FeedEvent::End {
+                channel_id,
+                story: text.clone(),
+            },
+        )
+        .await;
+
+        let announcement = match (&config.templates.completion, &posted) {
+            (Some(template), Some(posted)) => {
+                let link = gist_url
+                    .map(str::to_string)
+                    .unwrap_or_else(|| posted.link());
+                Some(templates::render(
+                    template,
+                    &[
+                        ("title", &story_title),
+                        ("word_count", &word_count),
+                        ("contributors", &contributors.join(", ")),
+                        ("link", &link),
+                    ],
+                ))
+            }
+            _ => None,
+        };
+        let chart = match (&posted, config.story_charts) {
+            (Some(_), true) => Some(story_chart(&messages, &config)),
+            _ => None,
+        };
+        if announcement.is_some() || chart.is_some() {
+            let sent = channel_id
+                .send_message(http, |m| {
+                    if let Some(announcement) = &announcement {
+                        m.content(announcement);
+                    }
+                    if let Some(chart) = &chart {
+                        m.add_file(AttachmentType::Bytes {
+                            data: Cow::Borrowed(chart),
+                            filename: "stats.png".to_string(),
+                        });
+                    }
+                    m
+                })
+                .await;
+            if let Err(why) = sent {
+                println!("Error announcing story {:?}", why);
+            }
+        }
+
+        if let (Some(posted), true) = (&posted, vote_options.len() > 1) {
+            tokio::spawn(run_title_vote(
+                http.clone(),
+                storage::storage(data).await,
+                guild_id,
+                posted.clone(),
+                number,
+                vote_options,
+                config.clone(),
+            ));
+        }
+
+        if let (Some(mastodon), Some(posted)) = (&config.mastodon, posted) {
+            let link = gist_url
+                .map(str::to_string)
+                .unwrap_or_else(|| posted.link());
+            mastodon::toot(mastodon, &text, &link).await;
+        }
+    };
+}
+
+/// The title of the embed of story `number`, in the guild's style.
+fn embed_title(config: &Config, title: &str, number: u64) -> String {
+    match &config.embed.title {
+        // Longest embed title.
+        Some(template) => templates::render(
+            template,
+            &[("title", title), ("number", &number.to_string())],
+        )
+        .chars()
+        .take(256)
+        .collect(),
+        None => numbered_title(config.locale, title, number),
+    }
+}
+
+/// `title` with the story's number, as in "Story so far #42".
+fn numbered_title(locale: Locale, title: &str, number: u64) -> String {
+    tr_args(
+        locale,
+        "story-numbered",
+        &[("title", title), ("number", &number.to_string())],
+    )
+}
+
+/// Asks the channel to pick a title for story `number` between `options`,
+/// then renames it, and its archive record, to the winner.
+async fn run_title_vote(
+    http: Arc<Http>,
+    storage: Arc<dyn Storage>,
+    guild_id: GuildId,
+    mut story: Message,
+    number: u64,
+    options: Vec<String>,
+    config: Config,
+) {
+    let locale = config.locale;
+    let mut content = tr(locale, "title-vote");
+    for (emoji, option) in TITLE_VOTE_EMOJIS.iter().zip(options.iter()) {
+        content += &format!("\n{} {}", emoji, option);
+    }
+    let vote = match story.reply(&http, content).await {
+        Ok(vote) => vote,
+        Err(why) => {
+            println!("Error starting title vote {:?}", why);
+            return;
+        }
+    };
+    for emoji in TITLE_VOTE_EMOJIS.iter().take(options.len()) {
+        if let Err(why) = vote
+            .react(&http, ReactionType::Unicode(emoji.to_string()))
+            .await
+        {
+            println!("Error reacting {:?}", why);
+        }
+    }
+
+    tokio::time::sleep(TITLE_VOTE_DURATION).await;
+
+    let vote = match vote.channel_id.message(&http, vote.id).await {
+        Ok(vote) => vote,
+        Err(why) => {
+            println!("Error fetching title vote {:?}", why);
+            return;
+        }
+    };
+    let winner = TITLE_VOTE_EMOJIS
+        .iter()
+        .zip(options.iter())
+        .max_by_key(|(emoji, _)| {
+            vote.reactions
+                .iter()
+                .find(|r| r.reaction_type == ReactionType::Unicode(emoji.to_string()))
+                .map_or(0, |r| r.count)
+        })
+        .map(|(_, option)| option.clone());
+
+    if let (Some(winner), Some(embed)) = (winner, story.embeds.first().cloned()) {
+        let mut embed = CreateEmbed::from(embed);
+        embed.title(embed_title(&config, &winner, number));
+        if let Err(why) = story.edit(&http, |m| m.set_embed(embed)).await {
+            println!("Error renaming story {:?}", why);
+        }
+        let archived = storage
+            .stories(guild_id, 0)
+            .await
+            .into_iter()
+            .find(|s| s.message_id == Some(story.id));
+        if let Some(mut archived) = archived {
+            archived.title = winner;
+            storage.update_story(archived).await;
+        }
+        if let Err(why) = vote.delete(&http).await {
+            println!("Error deleting title vote {:?}", why);
+        }
+    }
+}
+
+/// Whether `m` is part of the story: written by a person, or relayed from a
+/// bridged platform through the bridge webhook. The opening message of a
+/// forum post, which shares the post's id, isn't.
+fn is_contribution(m: &Message, bridge: Option<&Bridge>) -> bool {
+    let bridge_webhook = bridge.and_then(|b| b.webhook(m.channel_id));
+    m.id.0 != m.channel_id.0
+        && (!m.author.bot || (m.webhook_id.is_some() && m.webhook_id == bridge_webhook))
+}
+
+/// The last contribution to the story in `messages`, which are ordered
+/// newest first.
+fn newest_contribution(messages: &[Message], bridge: Option<&Bridge>) -> Option<MessageId> {
+    messages
+        .iter()
+        .take_while(|m| "." != m.content)
+        .find(|m| is_contribution(m, bridge))
+        .map(|m| m.id)
+}
+
+/// Names of everyone who added to the story in `messages`, in the order
+/// they first joined in.
+fn story_contributors(messages: &[Message], bridge: Option<&Bridge>) -> Vec<String> {
+    let story: Vec<&Message> = messages
+        .iter()
+        .take_while(|m| "." != m.content)
+        .filter(|m| is_contribution(m, bridge))
+        .collect();
+    let mut names: Vec<String> = Vec::new();
+    for m in story.into_iter().rev() {
+        if !names.contains(&m.author.name) {
+            names.push(m.author.name.clone());
+        }
+    }
+    names
+}
+
+/// New words shown in a story's trivia before the rest are only counted.
+const TRIVIA_NEW_WORDS: usize = 10;
+
+/// The notable words of the story in `messages`: its longest, and those
+/// never used in the guild before.
+async fn story_trivia(
+    data: &RwLock<TypeMap>,
+    guild_id: GuildId,
+    locale: Locale,
+    messages: &[Message],
+    bridge: Option<&Bridge>,
+) -> Option<String> {
+    let story: Vec<&Message> = messages
+        .iter()
+        .take_while(|m| "." != m.content)
+        .filter(|m| is_contribution(m, bridge))
+        .collect();
+    let started = story.last()?.timestamp.unix_timestamp() as u64;
+    let known = storage::storage(data)
+        .await
+        .vocabulary(guild_id, started)
+        .await;
+
+    let mut longest: Option<(String, &str)> = None;
+    let mut new_words: Vec<String> = Vec::new();
+    for m in story.into_iter().rev() {
+        for word in game::phrase_words(&m.content) {
+            let length = word.chars().count();
+            if longest
+                .as_ref()
+                .is_none_or(|(l, _)| length > l.chars().count())
+            {
+                longest = Some((word.clone(), &m.author.name));
+            }
+            if !known.contains(&word) && !new_words.contains(&word) {
+                new_words.push(word);
+            }
+        }
+    }
+
+    let mut lines = Vec::new();
+    if let Some((word, author)) = longest {
+        lines.push(tr_args(
+            locale,
+            "trivia-longest",
+            &[("word", &word), ("user", author)],
+        ));
+    }
+    if !new_words.is_empty() {
+        let mut words = new_words
+            .iter()
+            .take(TRIVIA_NEW_WORDS)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if new_words.len() > TRIVIA_NEW_WORDS {
+            words = format!("{} +{}", words, new_words.len() - TRIVIA_NEW_WORDS);
+        }
+        lines.push(tr_args(locale, "trivia-new", &[("words", &words)]));
+    }
+    match lines.is_empty() {
+        true => None,
+        false => Some(lines.join("\n")),
+    }
+}
+
+/// Chart of how the story in `messages` was written: the words added each
+/// day, and by each contributor.
+fn story_chart(messages: &[Message], config: &Config) -> Vec<u8> {
+    let bridge = config.bridge.as_ref();
+    let story: Vec<&Message> = messages
+        .iter()
+        .take_while(|m| "." != m.content)
+        .filter(|m| is_contribution(m, bridge))
+        .collect();
+    chart::render(&[
+        chart::Panel {
+            title: "Words per day".to_string(),
+            bars: chart::per_day(
+                story
+                    .iter()
+                    .map(|m| config.local(m.timestamp.unix_timestamp() as u64)),
+            ),
+        },
+        chart::Panel {
+            title: "Words per contributor".to_string(),
+            bars: chart::tally(story.iter().map(|m| m.author.name.as_str())),
+        },
+    ])
+}
+
+/// A contribution as it appears in the compiled story. Mentions and custom
+/// emoji are written out, so the story reads well outside Discord too.
+fn contribution_text(m: &Message, emoji: EmojiPolicy, cache: &Cache, guild_id: GuildId) -> String {
+    let options = ContentSafeOptions::default()
+        .show_discriminator(false)
+        .display_as_member_from(guild_id)
+        .clean_here(false)
+        .clean_everyone(false);
+    let text = content_safe(cache, &m.content, &options, &m.mentions);
+    let text = game::story_word(&text, emoji);
+    game::custom_emoji_names(&text)
+}
+
+/// The words of the story in `messages`, newest first like the messages.
+/// Each run of `gaps`, words deleted from the story, is marked with
+/// [`story::GAP`].
+fn story_words(
+    messages: &[Message],
+    config: &Config,
+    emoji: EmojiPolicy,
+    gaps: &[MessageId],
+    cache: &Cache,
+    guild_id: GuildId,
+) -> Vec<String> {
+    let bridge = config.bridge.as_ref();
+    let mut words = Vec::new();
+    let mut newer: Option<MessageId> = None;
+    let story = messages
+        .iter()
+        .take_while(|m| "." != m.content)
+        .filter(|m| is_contribution(m, bridge));
+    for m in story {
+        if gaps
+            .iter()
+            .any(|g| *g > m.id && newer.is_none_or(|n| *g < n))
+        {
+            words.push(story::GAP.to_string());
+        }
+        let word = contribution_text(m, emoji, cache, guild_id);
+        if !word.is_empty() {
+            words.push(word);
+        }
+        newer = Some(m.id);
+    }
+    if newer.is_some_and(|oldest| gaps.iter().any(|g| *g < oldest)) {
+        words.push(story::GAP.to_string());
+    }
+    words
+}
+
+/// Where a story being compiled ends.
+enum StoryEnd {
+    /// The end marker posted after the last word.
+    Marker(MessageId),
+    /// The last word, picked by the named user.
+    Through(Box<Message>, String),
+}
+
+/// How every message of a compiled story looks.
+#[derive(Clone, Copy)]
+struct StoryStyle<'a> {
+    pin: bool,
+    color: Option<u32>,
+    /// Hide the text, and the card, behind spoilers.
+    spoiler: bool,
+    /// URL of the guild icon.
+    thumbnail: Option<&'a str>,
+    footer: Option<&'a str>,
+    /// Label of the download button.
+    download: &'a str,
+}
+
+/// Extra content shown only on the first embed of a compiled story.
+#[derive(Default, Clone)]
+struct StoryExtras<'a> {
+    /// Localized field name and the summary.
+    summary: Option<(String, &'a str)>,
+    /// Localized field name and the story's notable words.
+    trivia: Option<(String, &'a str)>,
+    /// Localized content warning shown above the story.
+    warning: Option<&'a str>,
+    /// PNG cover illustration.
+    cover: Option<&'a [u8]>,
+    /// PNG story card, shown instead of the text if there is none.
+    card: Option<&'a [u8]>,
+}
+
+/// Characters of a story embed besides its description.
+#[derive(Clone, Copy)]
+struct EmbedLengths {
+    /// The title and extras of the first embed.
+    first: usize,
+    /// The title of the embeds continuing the story.
+    rest: usize,
+    /// The footer, shown once per message.
+    footer: usize,
+    /// Markup around each description.
+    markup: usize,
+}
+
+impl StoryExtras<'_> {
+    /// Characters the extras add to an embed.
+    fn len(&self) -> usize {
+        let field = |f: &Option<(String, &str)>| f.as_ref().map_or(0, |(n, v)| n.len() + v.len());
+        field(&self.summary) + field(&self.trivia) + self.warning.map_or(0, str::len)
+    }
+}
+
+/// Splits the words of a story, in reading order, into messages of up to 10
+/// embeds each. Discord allows 4096 characters in a description and 6000
+/// in all the embeds of a message.
+fn paginate(words: &[String], lengths: EmbedLengths) -> Vec<Vec<Vec<String>>> {
+    const MAX_DESCRIPTION: usize = 4096;
+    const MAX_TOTAL: usize = 6000;
+    const MAX_EMBEDS: usize = 10;
+
+    let mut messages = vec![vec![Vec::new()]];
+    let mut total = lengths.footer + lengths.first + lengths.markup;
+    let mut description = lengths.markup;
+    for word in words {
+        let len = word.len() + 1; // +1 for space
+        if description + len > MAX_DESCRIPTION || total + len > MAX_TOTAL {
+            let embed = lengths.rest + lengths.markup;
+            let message = messages.last_mut().expect("Expected a message");
+            if message.len() < MAX_EMBEDS && total + embed + len <= MAX_TOTAL {
+                message.push(Vec::new());
+                total += embed;
+            } else {
+                messages.push(vec![Vec::new()]);
+                total = lengths.footer + embed;
+            }
+            description = lengths.markup;
+        }
+        let message = messages.last_mut().expect("Expected a message");
+        message
+            .last_mut()
+            .expect("Expected an embed")
+            .push(word.clone());
+        description += len;
+        total += len;
+    }
+    messages
+}
+
+/// Sends one message of a compiled story, with an embed for each list of
+/// words in `embeds`. The first one is titled `title`, the others
+/// `continued`. `story_id` is the first message of the story, if this isn't
+/// it.
+#[allow(clippy::too_many_arguments)]
+async fn send_story(
+    http: &Http,
+    channel_id: ChannelId,
+    embeds: &[Vec<String>],
+    title: &str,
+    continued: &str,
+    url: Option<&str>,
+    style: StoryStyle<'_>,
+    extras: StoryExtras<'_>,
+    story_id: Option<MessageId>,
+) -> Option<Message> {
+    if embeds.iter().all(Vec::is_empty) && extras.card.is_none() {
+        return None;
+    }
+
+    // Discord blurs attachments named like this.
+    let card_name = match style.spoiler {
+        true => "SPOILER_card.png",
+        false => "card.png",
+    };
+    let last = embeds.len() - 1;
+    match channel_id
+        .send_message(http, |m| {
+            if let Some(cover) = extras.cover {
+                m.add_file(AttachmentType::Bytes {
+                    data: Cow::Borrowed(cover),
+                    filename: "cover.png".to_string(),
+                });
+            }
+            if let Some(card) = extras.card {
+                m.add_file(AttachmentType::Bytes {
+                    data: Cow::Borrowed(card),
+                    filename: card_name.to_string(),
+                });
+            }
+            for (i, story) in embeds.iter().enumerate() {
+                m.add_embed(|e| {
+                    if i == 0 {
+                        // Discord merges embeds sharing a URL into one.
+                        if let Some(url) = url {
+                            e.url(url);
+                        }
+                        if let Some(warning) = extras.warning {
+                            e.author(|a| a.name(warning));
+                        }
+                        if let Some((name, summary)) = &extras.summary {
+                            e.field(name, summary, false);
+                        }
+                        if let Some((name, trivia)) = &extras.trivia {
+                            e.field(name, trivia, false);
+                        }
+                        if let Some(thumbnail) = style.thumbnail {
+                            e.thumbnail(thumbnail);
+                        }
+                        if story.is_empty() {
+                            e.image(format!("attachment://{}", card_name));
+                        } else if extras.cover.is_some() {
+                            e.image("attachment://cover.png");
+                        }
+                    }
+                    if !story.is_empty() {
+                        match style.spoiler {
+                            true => e.description(format!("||{}||", story.join(" "))),
+                            false => e.description(story.join(" ")),
+                        };
+                    }
+                    if let Some(color) = style.color {
+                        e.color(color);
+                    }
+                    if let (Some(footer), true) = (style.footer, i == last) {
+                        e.footer(|f| f.text(footer));
+                    }
+                    e.title(if i == 0 { title } else { continued })
+                });
+            }
+            m.components(|c| download::button(c, style.download, story_id))
+        })
+        .await
+    {
+        Ok(m) => {
+            if style.pin {
+                if let Err(why) = m.pin(http).await {
+                    println!("Failed to pin message {:?}", why);
+                }
+            }
+            Some(m)
+        }
+        Err(why) => {
+            println!("Error generating story: {:?}", why);
+            None
+        }
+    }
+}
+
+async fn run_command(cmd: Command, msg: &Message, ctx: &Context) {
+    let guild_id = match msg.guild_id {
+        Some(id) => id,
+        None => return,
+    };
+
+    let config = get_config(&ctx.data, guild_id).await;
+    if let Some(required) = cmd.tier() {
+        if member_tier(ctx, msg, guild_id, &config).await < Some(required) {
+            let reply = tr(config.locale, required.denied_key());
+            if let Err(why) = msg.reply(&ctx.http, reply).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+    }
+
+    if let Command::Prompt = cmd {
+        let prompt = prompt::for_config(&config).await;
+        if let Err(why) = msg.reply(&ctx.http, format!("📝 {}", prompt)).await {
+            println!("Error replying: {:?}", why);
+        }
+        return;
+    }
+    if let Command::Narrate(story_id) = cmd {
+        narrate(ctx, msg, guild_id, story_id).await;
+        return;
+    }
+    // Subscriptions are the members' own business, not a setting to audit.
+    if let Command::Prefs(change) = cmd {
+        let user = msg.author.id;
+        if let Some((pref, on)) = change {
+            set_user_prefs(&ctx.data, user, |prefs| prefs.set(pref, on)).await;
+        }
+        let prefs = get_user_prefs(&ctx.data, user).await;
+        let on_off = |on: bool| match on {
+            true => tr(config.locale, "prefs-on"),
+            false => tr(config.locale, "prefs-off"),
+        };
+        let reply = tr_args(
+            config.locale,
+            "prefs",
+            &[
+                ("deletion", &on_off(prefs.deletion_dms)),
+                ("stories", &on_off(prefs.story_dms)),
+            ],
+        );
+        if let Err(why) = msg.reply(&ctx.http, reply).await {
+            println!("Error replying: {:?}", why);
+        }
+        return;
+    }
+
+    if let Command::Subscribe | Command::Unsubscribe = cmd {
+        let subscribe = matches!(cmd, Command::Subscribe);
+        let user = msg.author.id;
+        set_config(&ctx.data, guild_id, |config| {
+            match subscribe {
+                true => config.subscribers.insert(user),
+                false => config.subscribers.remove(&user),
+            };
+        })
+        .await;
+        let key = match subscribe {
+            true => "subscribed",
+            false => "unsubscribed",
+        };
+        if let Err(why) = msg.reply(&ctx.http, tr(config.locale, key)).await {
+            println!("Error replying: {:?}", why);
+        }
+        return;
+    }
+
+    let new_channel = match cmd {
+        Command::SetChannel(id) | Command::AddChannel(id) => Some(id),
+        _ => None,
+    };
+    let is_secret = matches!(
+        cmd,
+        Command::SetGistToken(_)
+            | Command::SetApiToken(_)
+            | Command::SetMastodon(_)
+            | Command::SetLlm(_)
+            | Command::SetToxicity(_)
+            | Command::SetIllustrations(_)
+            | Command::SetTts(_)
+            | Command::Restore
+    );
+    let venue = forum::venue(&ctx.cache, guild_id, msg.channel_id);
+    if matches!(cmd, Command::PinRules | Command::UnpinRules)
+        && !config.is_story_channel(msg.channel_id)
+        || matches!(cmd, Command::Pause | Command::Resume) && !config.is_story_channel(venue)
+    {
+        if let Err(why) = msg
+            .reply(&ctx.http, tr(config.locale, "not-story-channel"))
+            .await
+        {
+            println!("Error replying: {:?}", why);
+        }
+        return;
+    }
+
+    let before = config;
+
+    match cmd {
+        Command::SetChannel(id) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.channel_id = id;
+            })
+            .await;
+        }
+        Command::AddChannel(id) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.channels.entry(id).or_default();
+            })
+            .await;
+        }
+        Command::RemoveChannel(id) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.channels.remove(&id);
+            })
+            .await;
+        }
+        Command::SetModRole(role) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.mod_role = role;
+            })
+            .await;
+        }
+        Command::AddAdmin(user) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.admins.insert(user);
+            })
+            .await;
+        }
+        Command::RemoveAdmin(user) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.admins.remove(&user);
+            })
+            .await;
+        }
+        Command::AddChallenge(word) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.challenges.insert(word);
+            })
+            .await;
+        }
+        Command::Severity(severity, change) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.change_severity(severity, change)
+            })
+            .await;
+        }
+        Command::AddSensitiveWord(word) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.sensitive_words.insert(word);
+            })
+            .await;
+        }
+        Command::RemoveSensitiveWord(word) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.sensitive_words.remove(&word);
+            })
+            .await;
+        }
+        Command::CancelChallenge(word) => {
+            let mut found = false;
+            set_config(&ctx.data, guild_id, |config| {
+                found = config.challenges.remove(&word);
+            })
+            .await;
+            if !found {
+                let reply = tr(before.locale, "challenge-not-found");
+                if let Err(why) = msg.reply(&ctx.http, reply).await {
+                    println!("Error replying: {:?}", why);
+                }
+                return;
+            }
+        }
+        Command::ListChallenges => {
+            let mut words: Vec<&str> = before.challenges.iter().map(String::as_str).collect();
+            words.sort();
+            let reply = match words.is_empty() {
+                true => tr(before.locale, "challenges-empty"),
+                false => tr_args(before.locale, "challenges", &[("words", &words.join(", "))]),
+            };
+            if let Err(why) = msg.reply(&ctx.http, reply).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+        Command::Event(name, change) => {
+            let mut found = false;
+            set_config(&ctx.data, guild_id, |config| {
+                found = config.change_event(&name, change);
+            })
+            .await;
+            if !found {
+                let reply = tr(before.locale, "event-not-found");
+                if let Err(why) = msg.reply(&ctx.http, reply).await {
+                    println!("Error replying: {:?}", why);
+                }
+                return;
+            }
+        }
+        Command::SetRule(channel, rule) => {
+            set_config(&ctx.data, guild_id, |config| config.set_rule(channel, rule)).await;
+        }
+        Command::BanWord(word) => {
+            set_config(&ctx.data, guild_id, |config| config.ban(word)).await;
+        }
+        Command::UnbanWord(word) => {
+            set_config(&ctx.data, guild_id, |config| config.unban(&word)).await;
+        }
+        Command::SetGistToken(token) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.gist_token = token;
+            })
+            .await;
+        }
+        Command::SetApiToken(token) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.api_token = token;
+            })
+            .await;
+        }
+        Command::SetRetention(kind, keep) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.retention.set(kind, keep)
+            })
+            .await;
+        }
+        Command::SetMastodon(mastodon) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.mastodon = mastodon;
+            })
+            .await;
+        }
+        Command::SetLlm(llm) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.llm = llm;
+            })
+            .await;
+        }
+        Command::SetSummaries(enabled) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.summaries = enabled;
+            })
+            .await;
+        }
+        Command::SetTitleMode(mode) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.ai_titles = mode;
+            })
+            .await;
+        }
+        Command::SetNextTitle(title) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.next_title = Some(title);
+            })
+            .await;
+        }
+        Command::SetXp(xp) => {
+            set_config(&ctx.data, guild_id, |config| config.xp = xp).await;
+        }
+        Command::SetTimezone(utc_offset) => {
+            set_config(&ctx.data, guild_id, |config| config.utc_offset = utc_offset).await;
+        }
+        Command::SetQuietHours(quiet_hours) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.quiet_hours = quiet_hours;
+            })
+            .await;
+        }
+        Command::SetStoryCharts(enabled) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.story_charts = enabled;
+            })
+            .await;
+        }
+        Command::SetMarkGaps(enabled) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.mark_gaps = enabled;
+            })
+            .await;
+        }
+        Command::SetDailyPrompt(enabled) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.daily_prompt = enabled;
+            })
+            .await;
+        }
+        Command::Prompt
+        | Command::Narrate(_)
+        | Command::Subscribe
+        | Command::Unsubscribe
+        | Command::Prefs(_) => {}
+        Command::SetNudge(after, role) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.nudge_after = after;
+                config.nudge_role = role;
+            })
+            .await;
+        }
+        Command::SetDigestChannel(channel) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.digest_channel = channel;
+            })
+            .await;
+        }
+        Command::SetReviewChannel(channel_id) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.review_channel = channel_id;
+            })
+            .await;
+        }
+        Command::SetModLogChannel(id) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.mod_log_channel = Some(id);
+            })
+            .await;
+        }
+        Command::SetIllustrations(illustrations) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.illustrations = illustrations;
+            })
+            .await;
+        }
+        Command::SetTemplate(kind, template) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.templates.set(kind, template);
+            })
+            .await;
+        }
+        Command::SetPrefix(prefix) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.prefix = Some(prefix);
+            })
+            .await;
+        }
+        Command::SetEmojiPolicy(policy) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.emoji_policy = policy;
+            })
+            .await;
+        }
+        Command::SetEnforcement(enforcement) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.enforcement = enforcement;
+            })
+            .await;
+        }
+        Command::SetGracePeriod(seconds) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.grace_period = seconds;
+            })
+            .await;
+        }
+        Command::SetMatchMode(mode) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.match_mode = mode;
+            })
+            .await;
+        }
+        Command::SetDictionary(enabled) => {
+            set_config(&ctx.data, guild_id, |config| config.dictionary = enabled).await;
+        }
+        Command::Pause | Command::Resume => {
+            let pause = matches!(cmd, Command::Pause);
+            set_config(&ctx.data, guild_id, |config| {
+                match pause {
+                    true => config.paused.insert(venue),
+                    false => config.paused.remove(&venue),
+                };
+            })
+            .await;
+        }
+        Command::SetApproval(count) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.approval_contributions = count;
+            })
+            .await;
+            if count > before.approval_contributions {
+                review::count_history(&ctx.data, guild_id).await;
+            }
+        }
+        Command::ShadowMute(user_id, seconds) => {
+            set_config(&ctx.data, guild_id, |config| match seconds {
+                Some(seconds) => {
+                    config
+                        .shadow_muted
+                        .insert(user_id, scheduler::now() + seconds);
+                }
+                None => {
+                    config.shadow_muted.remove(&user_id);
+                }
+            })
+            .await;
+        }
+        Command::SetMinAccountAge(seconds) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.min_account_age = seconds
+            })
+            .await;
+        }
+        Command::SetMinMemberAge(seconds) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.min_member_age = seconds
+            })
+            .await;
+        }
+        Command::SetRaidProtection(enabled) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.skip_raid_protection = !enabled;
+            })
+            .await;
+        }
+        Command::SetNativeSlowmode(enabled) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.native_slowmode = enabled
+            })
+            .await;
+        }
+        Command::SetTopicUpdates(enabled) => {
+            set_config(&ctx.data, guild_id, |config| config.topic_updates = enabled).await;
+            if enabled {
+                let channels = std::iter::once(before.channel_id)
+                    .chain(before.channels.keys().copied())
+                    .filter(|id| id.0 != 0);
+                for channel_id in channels {
+                    topic::touch(&ctx.data, guild_id, channel_id).await;
+                }
+            }
+        }
+        Command::SetLocale(locale) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.locale = locale;
+            })
+            .await;
+        }
+        Command::SetStoryCards(mode) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.story_cards = mode;
+            })
+            .await;
+        }
+        Command::PinRules => {
+            if let Some(pin) = rules_embed::pin(&ctx.http, &before, msg.channel_id).await {
+                set_config(&ctx.data, guild_id, |config| {
+                    config.rules_pins.insert(msg.channel_id, pin);
+                })
+                .await;
+            }
+        }
+        Command::UnpinRules => {
+            rules_embed::unpin(&ctx.http, &before, msg.channel_id).await;
+            set_config(&ctx.data, guild_id, |config| {
+                config.rules_pins.remove(&msg.channel_id);
+            })
+            .await;
+        }
+        Command::SetWelcome(mode) => {
+            set_config(&ctx.data, guild_id, |config| config.welcome = mode).await;
+        }
+        Command::SetEndReaction(emoji) => {
+            set_config(&ctx.data, guild_id, |config| config.end_reaction = emoji).await;
+        }
+        Command::Embed(change) => {
+            set_config(&ctx.data, guild_id, |config| config.change_embed(change)).await;
+        }
+        Command::SetTts(tts) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.tts = tts;
+            })
+            .await;
+        }
+        Command::SetToxicity(toxicity) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.toxicity = toxicity;
+            })
+            .await;
+        }
+        Command::BridgeTelegram(chat_id) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.bridge.get_or_insert_with(Default::default).telegram = Some(chat_id);
+            })
+            .await;
+        }
+        Command::BridgeMatrix(room_id) => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.bridge.get_or_insert_with(Default::default).matrix = Some(room_id);
+            })
+            .await;
+        }
+        Command::Audit => {
+            let log = storage::storage(&ctx.data)
+                .await
+                .audit_log(guild_id, audit::AUDIT_LIMIT)
+                .await;
+            let reply = match log.is_empty() {
+                true => tr(before.locale, "audit-empty"),
+                false => audit::render(&log),
+            };
+            if let Err(why) = msg.reply(&ctx.http, reply).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+        Command::ModStats => {
+            let counts = modstats::guild(&ctx.data, guild_id).await;
+            let reply = match counts.is_empty() {
+                true => tr(before.locale, "modstats-empty"),
+                false => format!(
+                    "{}\n{}",
+                    tr(before.locale, "modstats"),
+                    modstats::render(&counts)
+                ),
+            };
+            if let Err(why) = msg.reply(&ctx.http, reply).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+        Command::Recompile(id) => {
+            let reply = match recompile::recompile(ctx, guild_id, id).await {
+                Ok(()) => tr(before.locale, "recompile-done"),
+                Err(key) => tr(before.locale, key),
+            };
+            if let Err(why) = msg.reply(&ctx.http, reply).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+        Command::Merge(a, b) => {
+            let reply = match recompile::merge(ctx, guild_id, (a, b), &msg.author.name).await {
+                Ok(()) => tr(before.locale, "merge-done"),
+                Err(key) => tr(before.locale, key),
+            };
+            if let Err(why) = msg.reply(&ctx.http, reply).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+        Command::CleanupPins => {
+            let stories = storage::storage(&ctx.data).await.stories(guild_id, 0).await;
+            let bot_id = ctx.cache.current_user_id();
+            let deleted = pins::cleanup(&ctx.http, bot_id, &before, &stories, msg.channel_id).await;
+            let reply = tr_args(
+                before.locale,
+                "cleanup-pins",
+                &[("count", &deleted.to_string())],
+            );
+            if let Err(why) = msg.reply(&ctx.http, reply).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+        Command::Sprint(seconds) => {
+            sprint::start(ctx, msg, guild_id, seconds).await;
+            return;
+        }
+        Command::Activity(days) => {
+            send_activity(ctx, msg, guild_id, days).await;
+            return;
+        }
+        Command::ArchiveGet(story_id, None) => {
+            send_archived(ctx, msg, guild_id, story_id).await;
+            return;
+        }
+        Command::ArchiveGet(story_id, Some(revision)) => {
+            send_revision(ctx, msg, guild_id, story_id, revision).await;
+            return;
+        }
+        Command::EditStory(id, edit) => {
+            let reply = match recompile::edit(ctx, guild_id, id, &edit, &msg.author.name).await {
+                Ok(()) => tr(before.locale, "archive-edited"),
+                Err(key) => tr(before.locale, key),
+            };
+            if let Err(why) = msg.reply(&ctx.http, reply).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+        Command::Status => {
+            send_status(ctx, msg, guild_id).await;
+            return;
+        }
+        Command::ShowRules => {
+            let venue = forum::venue(&ctx.cache, guild_id, msg.channel_id);
+            let channel_id = match before.is_story_channel(venue) {
+                true => venue,
+                false => before.channel_id,
+            };
+            let sent = msg
+                .channel_id
+                .send_message(&ctx.http, |m| {
+                    m.reference_message(msg)
+                        .embed(|e| rules_embed::embed(e, &before, channel_id))
+                })
+                .await;
+            if let Err(why) = sent {
+                println!("Error sending rules {:?}", why);
+            }
+            return;
+        }
+        Command::Stats => {
+            send_stats(ctx, msg, guild_id).await;
+            return;
+        }
+        Command::Rank(user) => {
+            send_rank(ctx, msg, guild_id, user.unwrap_or(msg.author.id)).await;
+            return;
+        }
+        Command::Backup => {
+            send_backup(ctx, msg, guild_id).await;
+            return;
+        }
+        Command::ExportStats => {
+            send_stats_export(ctx, msg, guild_id).await;
+            return;
+        }
+        Command::Setup => {
+            setup::start(ctx, msg, guild_id).await;
+            return;
+        }
+        Command::Restore => {
+            if !restore_backup(ctx, msg, guild_id).await {
+                return;
+            }
+        }
+        Command::Unbridge => {
+            set_config(&ctx.data, guild_id, |config| {
+                config.bridge = None;
+            })
+            .await;
+        }
+        Command::SetMastodonTemplate(template) => {
+            set_config(&ctx.data, guild_id, |config| {
+                if let Some(mastodon) = &mut config.mastodon {
+                    mastodon.template = Some(template.replace("\\n", "\n"));
+                }
+            })
+            .await;
+        }
+    };
+
+    let after = get_config(&ctx.data, guild_id).await;
+    audit::record(
+        &ctx.data,
+        guild_id,
+        &msg.author.tag(),
+        Some(msg.author.id),
+        &before,
+        &after,
+    )
+    .await;
+    rules_embed::refresh(&ctx.http, &before, &after).await;
+    slowmode::refresh(&ctx.data, &ctx.http, &before, &after).await;
+
+    // Read after the update, so a language change is confirmed in the new language.
+    let locale = after.locale;
+    let mut reply = tr(locale, "settings-updated");
+    if let Some(channel_id) = new_channel {
+        reply = setup::with_permission_check(ctx, guild_id, channel_id, locale, reply).await;
+    }
+    if let Err(why) = msg.reply(&ctx.http, reply).await {
+        println!("Error replying: {:?}", why);
+    }
+
+    // Don't leave tokens lying around in the channel history.
+    if is_secret {
+        actions::delete(ctx, msg.channel_id, msg.id).await;
+    }
+}
+
+/// Replies with how the archived story posted in `story_id`, or the
+/// guild's latest, came about.
+async fn send_archived(ctx: &Context, msg: &Message, guild_id: GuildId, story_id: Option<u64>) {
+    let locale = get_config(&ctx.data, guild_id).await.locale;
+    let stories = storage::storage(&ctx.data).await.stories(guild_id, 0).await;
+    let story = match story_id {
+        Some(id) => stories.into_iter().find(|s| s.is(id)),
+        None => stories.into_iter().last(),
+    };
+    let story = match story {
+        Some(story) => story,
+        None => {
+            if let Err(why) = msg.reply(&ctx.http, tr(locale, "archive-not-found")).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+    };
+
+    let time = |at: Option<u64>| at.map_or("?".to_string(), |at| format!("<t:{}:f>", at));
+    let unknown = || "?".to_string();
+    let rules = story.rules.as_ref().map(|rules| {
+        let rules = serde_json::to_value(rules).unwrap_or_default();
+        rules
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(rule, value)| format!("{}: {}", rule, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+    let sent = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.reference_message(msg).embed(|e| {
+                if let Some(id) = story.message_id {
+                    e.url(id.link(story.channel_id, Some(guild_id)));
+                }
+                e.title(&story.title)
+                    .field(
+                        tr(locale, "status-channel"),
+                        story.channel_id.mention(),
+                        false,
+                    )
+                    .field(tr(locale, "archive-started"), time(story.started_at), true)
+                    .field(tr(locale, "archive-ended"), time(story.ended_at), true)
+                    .field(tr(locale, "archive-compiled"), time(Some(story.at)), true)
+                    .field(
+                        tr(locale, "archive-starter"),
+                        story.starter.clone().unwrap_or_else(unknown),
+                        true,
+                    )
+                    .field(
+                        tr(locale, "archive-ender"),
+                        story.ender.clone().unwrap_or_else(unknown),
+                        true,
+                    )
+                    .field(
+                        tr(locale, "stats-words"),
+                        story.text.split_whitespace().count(),
+                        true,
+                    )
+                    .field(
+                        tr(locale, "stats-contributors"),
+                        story.contributors.len(),
+                        true,
+                    );
+                if let Some(rules) = &rules {
+                    e.field(tr(locale, "archive-rules"), rules, false);
+                }
+                if !story.revisions.is_empty() {
+                    let edits: Vec<String> = story
+                        .revisions
+                        .iter()
+                        .enumerate()
+                        .map(|(i, r)| format!("{}. {} <t:{}:f>", i + 2, r.editor, r.at))
+                        .collect();
+                    e.field(tr(locale, "archive-edits"), edits.join("\n"), false);
+                }
+                e
+            })
+        })
+        .await;
+    if let Err(why) = sent {
+        println!("Error sending archived story {:?}", why);
+    }
+}
+
+/// Replies with `revision` of the text of the archived story with the
+/// number, or posted in the message, or the latest one.
+async fn send_revision(
+    ctx: &Context,
+    msg: &Message,
+    guild_id: GuildId,
+    story_id: Option<u64>,
+    revision: usize,
+) {
+    let locale = get_config(&ctx.data, guild_id).await.locale;
+    let stories = storage::storage(&ctx.data).await.stories(guild_id, 0).await;
+    let story = match story_id {
+        Some(id) => stories.into_iter().find(|s| s.is(id)),
+        None => stories.into_iter().last(),
+    };
+    let (story, text) = match story.as_ref().map(|s| (s, s.revision(revision))) {
+        Some((story, Some(text))) => (story, text),
+        Some((_, None)) => {
+            let reply = tr(locale, "archive-revision-not-found");
+            if let Err(why) = msg.reply(&ctx.http, reply).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+        None => {
+            if let Err(why) = msg.reply(&ctx.http, tr(locale, "archive-not-found")).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+    };
+
+    let footer = tr_args(
+        locale,
+        "archive-revision",
+        &[
+            ("revision", &revision.to_string()),
+            ("revisions", &(story.revisions.len() + 1).to_string()),
+        ],
+    );
+    let sent = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.reference_message(msg).embed(|e| {
+                // Longest embed description.
+                e.title(&story.title)
+                    .description(text.chars().take(4096).collect::<String>())
+                    .footer(|f| f.text(footer))
+            })
+        })
+        .await;
+    if let Err(why) = sent {
+        println!("Error sending story revision {:?}", why);
+    }
+}
+
+/// Replies with how far the story in the channel of `msg`, or the
+/// guild's main one, has come.
+async fn send_status(ctx: &Context, msg: &Message, guild_id: GuildId) {
+    let config = get_config(&ctx.data, guild_id).await;
+    let locale = config.locale;
+    let venue = forum::venue(&ctx.cache, guild_id, msg.channel_id);
+    let (channel_id, venue) = match config.is_story_channel(venue) {
+        true => (msg.channel_id, venue),
+        false => (config.channel_id, config.channel_id),
+    };
+    let bridge = config.bridge.as_ref();
+    let compiled = config.compiled.get(&channel_id).copied();
+    let story = story_messages(&ctx.http, channel_id, None, compiled).await;
+    let words = story
+        .iter()
+        .take_while(|m| "." != m.content)
+        .filter(|m| is_contribution(m, bridge))
+        .count();
+    let count = story_contributors(&story, bridge).len();
+    let contributors = match config.rules(venue).min_contributors {
+        min if min > 1 => format!("{} / {}", count, min),
+        _ => count.to_string(),
+    };
+
+    let sent = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.reference_message(msg).embed(|e| {
+                e.title(tr(locale, "status-title"))
+                    .field(tr(locale, "status-channel"), channel_id.mention(), false)
+                    .field(tr(locale, "stats-words"), words, true)
+                    .field(tr(locale, "stats-contributors"), contributors, true)
+            })
+        })
+        .await;
+    if let Err(why) = sent {
+        println!("Error sending status {:?}", why);
+    }
+}
+
+/// Replies with the guild's all-time stats.
+async fn send_stats(ctx: &Context, msg: &Message, guild_id: GuildId) {
+    let locale = get_config(&ctx.data, guild_id).await.locale;
+    let stats = storage::storage(&ctx.data)
+        .await
+        .stats(Some(guild_id))
+        .await;
+    let sent = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.reference_message(msg).embed(|e| {
+                e.title(tr(locale, "stats-title"))
+                    .field(tr(locale, "stats-stories"), stats.stories, true)
+                    .field(tr(locale, "stats-words"), stats.words, true)
+                    .field(tr(locale, "stats-contributors"), stats.contributors, true)
+                    .field(tr(locale, "stats-vocabulary"), stats.vocabulary, true)
+                    .field(tr(locale, "stats-challenges"), stats.challenges, true);
+                if let Some(longest) = &stats.longest_word {
+                    e.field(
+                        tr(locale, "stats-longest"),
+                        tr_args(
+                            locale,
+                            "stats-longest-by",
+                            &[("word", &longest.word), ("user", &longest.author)],
+                        ),
+                        false,
+                    );
+                }
+                e
+            })
+        })
+        .await;
+    if let Err(why) = sent {
+        println!("Error sending stats {:?}", why);
+    }
+}
+
+/// Replies with the level card of `user_id`.
+async fn send_rank(ctx: &Context, msg: &Message, guild_id: GuildId, user_id: UserId) {
+    let config = get_config(&ctx.data, guild_id).await;
+    let user = xp::user(&ctx.data, guild_id, user_id, &config).await;
+    let user = match (&config.xp, user) {
+        (None, _) => {
+            if let Err(why) = msg.reply(&ctx.http, tr(config.locale, "xp-off")).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+        (Some(_), None) => {
+            if let Err(why) = msg.reply(&ctx.http, tr(config.locale, "rank-empty")).await {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+        (Some(_), Some(user)) => user,
+    };
+    let png = xp::card(&user.name, &user.progress(scheduler::now()));
+    let sent = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.reference_message(msg).add_file(AttachmentType::Bytes {
+                data: Cow::Owned(png),
+                filename: "rank.png".to_string(),
+            })
+        })
+        .await;
+    if let Err(why) = sent {
+        println!("Error sending rank card {:?}", why);
+    }
+}
+
+/// Replies with a chart of the words added in the guild each of the last
+/// `days` days.
+async fn send_activity(ctx: &Context, msg: &Message, guild_id: GuildId, days: usize) {
+    let config = get_config(&ctx.data, guild_id).await;
+    let today = scheduler::now();
+    let since = today.saturating_sub(days as u64 * 24 * 60 * 60);
+    let contributions = storage::storage(&ctx.data)
+        .await
+        .contributions(guild_id, since)
+        .await;
+    let png = chart::render(&[chart::Panel {
+        title: format!("Words per day, last {} days", days),
+        bars: chart::daily(
+            contributions.iter().map(|c| config.local(c.at)),
+            config.local(today),
+            days,
+        ),
+    }]);
+
+    let sent = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.reference_message(msg).add_file(AttachmentType::Bytes {
+                data: Cow::Owned(png),
+                filename: "activity.png".to_string(),
+            })
+        })
+        .await;
+    if let Err(why) = sent {
+        println!("Error sending activity chart {:?}", why);
+    }
+}
+
+/// Sends the guild's backup to the admin asking for it. It holds API
+/// tokens, so it goes to their DMs.
+async fn send_backup(ctx: &Context, msg: &Message, guild_id: GuildId) {
+    let config = get_config(&ctx.data, guild_id).await;
+    let locale = config.locale;
+    let bot_id = ctx.cache.current_user_id();
+    let stats = storage::storage(&ctx.data)
+        .await
+        .stats(Some(guild_id))
+        .await;
+    let backup = backup::create(&ctx.http, bot_id, guild_id, config, stats).await;
+    let file = AttachmentType::Bytes {
+        data: Cow::Owned(serde_json::to_vec_pretty(&backup).unwrap()),
+        filename: format!("one-word-backup-{}.json", guild_id),
+    };
+
+    let sent = match msg.author.create_dm_channel(&ctx.http).await {
+        Ok(dm) => dm.send_message(&ctx.http, |m| m.add_file(file)).await,
+        Err(why) => Err(why),
+    };
+    let reply = match sent {
+        Ok(_) => tr(locale, "backup-sent"),
+        Err(why) => {
+            println!("Error sending backup {:?}", why);
+            tr(locale, "backup-failed")
+        }
+    };
+    if let Err(why) = msg.reply(&ctx.http, reply).await {
+        println!("Error replying: {:?}", why);
+    }
+}
+
+/// DMs the author of `msg` the guild's contributors and stories as CSV.
+async fn send_stats_export(ctx: &Context, msg: &Message, guild_id: GuildId) {
+    let locale = get_config(&ctx.data, guild_id).await.locale;
+    let storage = storage::storage(&ctx.data).await;
+    let contributions = storage.contributions(guild_id, 0).await;
+    let stories = storage.stories(guild_id, 0).await;
+    let files = [
+        AttachmentType::Bytes {
+            data: Cow::Owned(export::contributors(&contributions, &stories).into_bytes()),
+            filename: format!("one-word-contributors-{}.csv", guild_id),
+        },
+        AttachmentType::Bytes {
+            data: Cow::Owned(export::stories(&stories).into_bytes()),
+            filename: format!("one-word-stories-{}.csv", guild_id),
+        },
+    ];
+
+    let sent = match msg.author.create_dm_channel(&ctx.http).await {
+        Ok(dm) => dm.send_message(&ctx.http, |m| m.add_files(files)).await,
+        Err(why) => Err(why),
+    };
+    let reply = match sent {
+        Ok(_) => tr(locale, "export-sent"),
+        Err(why) => {
+            println!("Error sending stats export {:?}", why);
+            tr(locale, "export-failed")
+        }
+    };
+    if let Err(why) = msg.reply(&ctx.http, reply).await {
+        println!("Error replying: {:?}", why);
+    }
+}
+
+/// Replaces the guild's config with the one in the backup attached to
+/// `msg`. Returns whether it did.
+async fn restore_backup(ctx: &Context, msg: &Message, guild_id: GuildId) -> bool {
+    let locale = get_config(&ctx.data, guild_id).await.locale;
+    let file = match msg.attachments.first() {
+        Some(attachment) => attachment.download().await.map_err(|why| {
+            println!("Error downloading backup {:?}", why);
+            "backup-invalid"
+        }),
+        None => Err("backup-missing"),
+    };
+    match file.and_then(|file| backup::parse(&file, guild_id)) {
+        Ok(backup) => {
+            set_config(&ctx.data, guild_id, |config| *config = backup.config).await;
+            storage::storage(&ctx.data)
+                .await
+                .restore_stats(guild_id, backup.stats)
+                .await;
+            true
+        }
+        Err(key) => {
+            if let Err(why) = msg.reply(&ctx.http, tr(locale, key)).await {
+                println!("Error replying: {:?}", why);
+            }
+            false
+        }
+    }
+}
+
+/// Replies with a recording of the compiled story posted as `story_id`.
+async fn narrate(ctx: &Context, msg: &Message, guild_id: GuildId, story_id: MessageId) {
+    let config = get_config(&ctx.data, guild_id).await;
+    let tts = match &config.tts {
+        Some(tts) => tts,
+        None => {
+            if let Err(why) = msg
+                .reply(&ctx.http, tr(config.locale, "narration-unavailable"))
+                .await
+            {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+    };
+
+    // The archive knows where the story was posted, older stories are
+    // looked for in every story channel.
+    let archived = storage::storage(&ctx.data)
+        .await
+        .stories(guild_id, 0)
+        .await
+        .into_iter()
+        .find(|s| s.message_id == Some(story_id))
+        .map(|s| s.channel_id);
+    let channels: Vec<ChannelId> = match archived {
+        Some(channel_id) => vec![channel_id],
+        None => std::iter::once(config.channel_id)
+            .chain(config.channels.keys().copied())
+            .filter(|c| c.0 != 0)
+            .collect(),
+    };
+    let mut found = None;
+    for channel_id in channels {
+        match channel_id.message(&ctx.http, story_id).await {
+            Ok(story) if story.author.id == ctx.cache.current_user_id() => {
+                found = Some(story);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let story = match found {
+        Some(story) => story,
+        None => {
+            if let Err(why) = msg
+                .reply(&ctx.http, tr(config.locale, "story-not-found"))
+                .await
+            {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+    };
+    let text: Vec<&str> = story
+        .embeds
+        .iter()
+        .flat_map(|e| {
+            e.title
+                .as_deref()
+                .into_iter()
+                .chain(e.description.as_deref())
+        })
+        .collect();
+
+    let _typing = msg.channel_id.start_typing(&ctx.http);
+    let audio = match tts::speak(tts, &text.join(".\n")).await {
+        Some(audio) => audio,
+        None => {
+            if let Err(why) = msg
+                .reply(&ctx.http, tr(config.locale, "narration-failed"))
+                .await
+            {
+                println!("Error replying: {:?}", why);
+            }
+            return;
+        }
+    };
+
+    let sent = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.reference_message(msg).add_file(AttachmentType::Bytes {
+                data: Cow::Owned(audio),
+                filename: "story.mp3".to_string(),
+            })
+        })
+        .await;
+    if let Err(why) = sent {
+        println!("Error sending narration {:?}", why);
+    }
+}
+
+/// The highest tier the author of `msg` belongs to, if any.
+async fn member_tier(
+    ctx: &Context,
+    msg: &Message,
+    guild_id: GuildId,
+    config: &Config,
+) -> Option<Tier> {
+    let member = msg.member(&ctx.http).await.ok();
+    user_tier(ctx, guild_id, msg.author.id, member.as_ref(), config).await
+}
+
+/// The highest tier `user` belongs to, if any. `member` is the user's
+/// membership in the guild, if known.
+///
+/// Guilds missing from the cache are fetched, so this works with the
+/// cache trimmed, see [`main`].
+async fn user_tier(
+    ctx: &Context,
+    guild_id: GuildId,
+    user: UserId,
+    member: Option<&Member>,
+    config: &Config,
+) -> Option<Tier> {
+    let cached_owner = ctx.cache.guild_field(guild_id, |g| g.owner_id);
+    let guild = match cached_owner {
+        Some(_) => None,
+        None => match guild_id.to_partial_guild(&ctx.http).await {
+            Ok(guild) => Some(guild),
+            Err(why) => {
+                println!("Error fetching guild {:?}", why);
+                None
+            }
+        },
+    };
+    if cached_owner.or(guild.as_ref().map(|g| g.owner_id)) == Some(user) {
+        return Some(Tier::Owner);
+    }
+    if config.admins.contains(&user) {
+        return Some(Tier::Admin);
+    }
+    let member = member?;
+    // Interactions come with the member's permissions.
+    let is_admin = match (member.permissions, &guild) {
+        (Some(perms), _) => perms.contains(Permissions::ADMINISTRATOR),
+        (None, None) => member
+            .permissions(&ctx.cache)
+            .is_ok_and(|perms| perms.contains(Permissions::ADMINISTRATOR)),
+        (None, Some(guild)) => member
+            .roles
+            .iter()
+            .chain([&RoleId(guild_id.0)])
+            .filter_map(|id| guild.roles.get(id))
+            .any(|role| role.permissions.contains(Permissions::ADMINISTRATOR)),
+    };
+    if is_admin {
+        return Some(Tier::Admin);
+    }
+    match config.mod_role {
+        Some(role) if member.roles.contains(&role) => Some(Tier::Mod),
+        _ => None,
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    if env::args().nth(1).as_deref() == Some("migrate") {
+        migrate::run().await;
+        return;
+    }
+
+    let frontends: Vec<String> = env::var("FRONTENDS")
+        .unwrap_or_else(|_| "discord".to_string())
+        .split(',')
+        .map(|f| f.trim().to_lowercase())
+        .collect();
+
+    let mut data = TypeMap::new();
+    let storage = storage::from_env();
+    let configs = storage.load_configs().await.unwrap_or_default();
+    data.insert::<StorageContainer>(storage);
+    data.insert::<CensorContainer>(Arc::new(RwLock::new(censors_for(&configs))));
+    data.insert::<ConfigContainer>(Arc::new(RwLock::new(configs)));
+    data.insert::<FeedContainer>(new_feed());
+    data.insert::<BusContainer>(bus::from_env());
+    data.insert::<GraceContainer>(Default::default());
+    data.insert::<SetupContainer>(Default::default());
+    data.insert::<SprintContainer>(Default::default());
+    data.insert::<CooldownContainer>(Default::default());
+    data.insert::<CompileContainer>(Default::default());
+    data.insert::<StoryContainer>(Default::default());
+    data.insert::<SeenContainer>(Default::default());
+    data.insert::<ActionContainer>(Default::default());
+    data.insert::<ModStatsContainer>(Default::default());
+    data.insert::<TopicContainer>(Default::default());
+    data.insert::<RaidContainer>(Default::default());
+
+    let mut tasks = Vec::new();
+    let data = if frontends.iter().any(|f| f == "discord") {
+        let token = env::var("DISCORD_TOKEN").expect("Missing discord token.");
+        let mut intents = GatewayIntents::GUILDS
+            | GatewayIntents::GUILD_MEMBERS
+            | GatewayIntents::GUILD_MESSAGES
+            | GatewayIntents::GUILD_MESSAGE_REACTIONS
+            | GatewayIntents::MESSAGE_CONTENT;
+        // Large guilds can do without the member list, members are fetched
+        // when needed.
+        if env::var("MEMBERS_INTENT").as_deref() == Ok("off") {
+            intents.remove(GatewayIntents::GUILD_MEMBERS);
+        }
+        let message_cache_size = env::var("MESSAGE_CACHE_SIZE")
+            .ok()
+            .and_then(|size| size.parse().ok())
+            .unwrap_or(0);
+        let mut client = Client::builder(token, intents)
+            .cache_settings(|s| s.max_messages(message_cache_size))
+            .event_handler(Handler)
+            .type_map(data)
+            .await
+            .expect("Error creating client");
+
+        tokio::spawn(bridge::relay(
+            client.data.clone(),
+            client.cache_and_http.clone(),
+        ));
+        tokio::spawn(scheduler::run(
+            client.data.clone(),
+            client.cache_and_http.clone(),
+        ));
+        tokio::spawn(maintenance::run(
+            client.data.clone(),
+            client.cache_and_http.clone(),
+        ));
+        tokio::spawn(topic::run(
+            client.data.clone(),
+            client.cache_and_http.clone(),
+        ));
+        tokio::spawn(presence::run(
+            client.data.clone(),
+            client.shard_manager.clone(),
+        ));
+        tokio::spawn(web::serve(
+            client.data.clone(),
+            client.cache_and_http.clone(),
+        ));
+
+        let data = client.data.clone();
+        tasks.push(tokio::spawn(async move {
+            if let Err(why) = client.start().await {
+                println!("Client error: {:?}", why);
+            }
+        }));
+        data
+    } else {
+        Arc::new(RwLock::new(data))
+    };
+
+    if frontends.iter().any(|f| f == "telegram") {
+        tasks.push(tokio::spawn(telegram::run(data.clone())));
+    }
+    if frontends.iter().any(|f| f == "matrix") {
+        tasks.push(tokio::spawn(matrix::run(data.clone())));
+    }
+
+    // Stop as soon as any frontend does, like the bot always has for Discord.
+    if !tasks.is_empty() {
+        let (res, _, _) = futures::future::select_all(tasks).await;
+        if let Err(why) = res {
+            println!("Frontend error: {:?}", why);
+        }
     }
 }
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::http::Http;
+use serenity::prelude::*;
+use serenity::CacheAndHttp;
+
+use crate::config::get_config;
+use crate::cooldown;
+use crate::modlog;
+use crate::retention;
+use crate::shadowmute;
+use crate::storage::{storage, HistoryKind};
+
+const INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Tidies up stored and in-memory data once a day, until the process exits.
+pub async fn run(data: Arc<RwLock<TypeMap>>, cache_and_http: Arc<CacheAndHttp>) {
+    loop {
+        maintain(&data, &cache_and_http.http).await;
+        tokio::time::sleep(INTERVAL).await;
+    }
+}
+
+async fn maintain(data: &RwLock<TypeMap>, http: &Http) {
+    for (guild_id, kind, count) in retention::prune_history(data).await {
+        if count == 0 {
+            continue;
+        }
+        let records = match kind {
+            HistoryKind::Contributions => "contribution",
+            HistoryKind::Audit => "audit",
+        };
+        let config = get_config(data, guild_id).await;
+        let notice = format!(
+            "Maintenance: deleted {} {} records past their retention period.",
+            count, records
+        );
+        modlog::post(http, &config, &notice).await;
+    }
+    retention::purge_removed_guilds(data).await;
+    shadowmute::lift_expired(data).await;
+
+    let compaction = storage(data).await.compact().await;
+    let cooldowns = cooldown::prune(data).await;
+    println!(
+        "Maintenance: {} records in the history ({} KiB), dropped {} unreadable lines and {} expired cooldowns.",
+        compaction.records,
+        compaction.bytes / 1024,
+        compaction.dropped,
+        cooldowns
+    );
+}
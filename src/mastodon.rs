@@ -0,0 +1,55 @@
+/// A Mastodon account completed stories are tooted to.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct MastodonConfig {
+    /// Base URL of the instance, e.g. `https://mastodon.social`.
+    pub instance: String,
+    pub access_token: String,
+    /// Toot text. `{story}` and `{link}` are replaced with the story and a link to it.
+    pub template: Option<String>,
+    /// Character limit of the instance.
+    pub max_chars: Option<usize>,
+}
+
+const DEFAULT_TEMPLATE: &str = "{story}\n\n{link}";
+const DEFAULT_MAX_CHARS: usize = 500;
+
+/// Fills in `template`, shortening the story so the toot fits in `max_chars`.
+fn render(template: &str, story: &str, link: &str, max_chars: usize) -> String {
+    let without_story = template.replace("{link}", link).replace("{story}", "");
+    let available = max_chars.saturating_sub(without_story.chars().count());
+
+    let story = if story.chars().count() <= available {
+        story.to_string()
+    } else {
+        let mut truncated: String = story.chars().take(available.saturating_sub(1)).collect();
+        truncated.push('…');
+        truncated
+    };
+
+    template.replace("{link}", link).replace("{story}", &story)
+}
+
+/// Toots `story` using the account in `config`.
+pub async fn toot(config: &MastodonConfig, story: &str, link: &str) {
+    let status = render(
+        config.template.as_deref().unwrap_or(DEFAULT_TEMPLATE),
+        story,
+        link,
+        config.max_chars.unwrap_or(DEFAULT_MAX_CHARS),
+    );
+
+    let res = reqwest::Client::new()
+        .post(format!(
+            "{}/api/v1/statuses",
+            config.instance.trim_end_matches('/')
+        ))
+        .bearer_auth(&config.access_token)
+        .form(&[("status", status)])
+        .send()
+        .await
+        .and_then(|res| res.error_for_status());
+
+    if let Err(why) = res {
+        println!("Error tooting story {:?}", why);
+    }
+}
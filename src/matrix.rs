@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::RwLock;
+
+use serde_json::json;
+use serenity::prelude::*;
+
+use crate::command::{parse_command, with_prefix, Command};
+use crate::config::{
+    find_bridged_channel, find_guild, get_matrix_config, set_matrix_config, Config, Rule,
+};
+use crate::feed::{publish, subscribe, FeedEvent, Source};
+use crate::game::{check_message, check_phrases, story_chunks, story_word, WordFilter};
+use crate::gist;
+use crate::i18n::{tr, tr_args};
+
+/// Keeps compiled stories readable; Matrix itself allows much longer messages.
+const MESSAGE_LIMIT: usize = 4096;
+
+/// Power level needed to change settings, the default for room admins.
+const ADMIN_POWER_LEVEL: i64 = 100;
+
+/// Transaction ids must be unique per access token, across restarts too.
+static TXN: AtomicU64 = AtomicU64::new(0);
+
+#[derive(serde::Deserialize)]
+struct SyncResponse {
+    next_batch: String,
+    #[serde(default)]
+    rooms: Rooms,
+}
+
+#[derive(serde::Deserialize, Default)]
+struct Rooms {
+    #[serde(default)]
+    join: HashMap<String, JoinedRoom>,
+    #[serde(default)]
+    invite: HashMap<String, serde_json::Value>,
+}
+
+#[derive(serde::Deserialize)]
+struct JoinedRoom {
+    timeline: Timeline,
+}
+
+#[derive(serde::Deserialize)]
+struct Timeline {
+    events: Vec<Event>,
+}
+
+#[derive(serde::Deserialize)]
+struct Event {
+    #[serde(rename = "type")]
+    kind: String,
+    event_id: String,
+    sender: String,
+    content: serde_json::Value,
+}
+
+#[derive(serde::Deserialize)]
+struct WhoAmI {
+    user_id: String,
+}
+
+#[derive(serde::Deserialize)]
+struct PowerLevels {
+    #[serde(default)]
+    users: HashMap<String, i64>,
+    #[serde(default)]
+    users_default: i64,
+}
+
+struct Matrix {
+    homeserver: String,
+    token: String,
+    user_id: String,
+    http: reqwest::Client,
+    data: Arc<RwLock<TypeMap>>,
+    /// Words of the story in progress in each room, keyed by room id.
+    stories: HashMap<String, Vec<String>>,
+}
+
+/// Runs the Matrix frontend until the process exits.
+pub async fn run(data: Arc<RwLock<TypeMap>>) {
+    let homeserver = env::var("MATRIX_HOMESERVER").expect("Missing matrix homeserver.");
+    let token = env::var("MATRIX_ACCESS_TOKEN").expect("Missing matrix access token.");
+
+    let mut bot = Matrix {
+        homeserver: homeserver.trim_end_matches('/').to_string(),
+        token,
+        user_id: String::new(),
+        http: reqwest::Client::new(),
+        data,
+        stories: HashMap::new(),
+    };
+
+    match bot.get::<WhoAmI>("/account/whoami").await {
+        Some(whoami) => bot.user_id = whoami.user_id,
+        None => {
+            println!("Matrix login failed.");
+            return;
+        }
+    }
+    println!("{} is connected to Matrix!", bot.user_id);
+    tokio::spawn(
+        Matrix {
+            homeserver: bot.homeserver.clone(),
+            token: bot.token.clone(),
+            user_id: bot.user_id.clone(),
+            http: bot.http.clone(),
+            data: bot.data.clone(),
+            stories: HashMap::new(),
+        }
+        .forward_bridged(),
+    );
+
+    // Skip whatever happened while the bot was offline.
+    let mut since = None;
+    loop {
+        let path = match &since {
+            Some(since) => format!("/sync?timeout=30000&since={}", since),
+            None => "/sync?timeout=0".to_string(),
+        };
+        let sync: SyncResponse = match bot.get(&path).await {
+            Some(sync) => sync,
+            None => {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        if since.is_some() {
+            for room_id in sync.rooms.invite.keys() {
+                bot.join(room_id).await;
+            }
+            for (room_id, room) in sync.rooms.join {
+                for event in room.timeline.events {
+                    bot.handle(&room_id, event).await;
+                }
+            }
+        }
+        since = Some(sync.next_batch);
+    }
+}
+
+impl Matrix {
+    fn url(&self, path: &str) -> String {
+        format!("{}/_matrix/client/v3{}", self.homeserver, path)
+    }
+
+    fn next_txn_id(&self) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        format!("ows{}-{}", now, TXN.fetch_add(1, Ordering::Relaxed))
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Option<T> {
+        let res = self
+            .http
+            .get(self.url(path))
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .and_then(|res| res.error_for_status());
+
+        match res {
+            Ok(res) => match res.json().await {
+                Ok(body) => Some(body),
+                Err(why) => {
+                    println!("Error reading Matrix response {:?}", why);
+                    None
+                }
+            },
+            Err(why) => {
+                println!("Error calling Matrix {:?}", why);
+                None
+            }
+        }
+    }
+
+    async fn request(&self, method: reqwest::Method, path: &str, body: serde_json::Value) {
+        let res = self
+            .http
+            .request(method, self.url(path))
+            .bearer_auth(&self.token)
+            .json(&body)
+            .send()
+            .await
+            .and_then(|res| res.error_for_status());
+
+        if let Err(why) = res {
+            println!("Error calling Matrix {:?}", why);
+        }
+    }
+
+    async fn join(&self, room_id: &str) {
+        let path = format!("/join/{}", urlencode(room_id));
+        self.request(reqwest::Method::POST, &path, json!({})).await;
+    }
+
+    async fn send(&self, room_id: &str, text: &str) {
+        let path = format!(
+            "/rooms/{}/send/m.room.message/{}",
+            urlencode(room_id),
+            self.next_txn_id()
+        );
+        let body = json!({ "msgtype": "m.text", "body": text });
+        self.request(reqwest::Method::PUT, &path, body).await;
+    }
+
+    async fn redact(&self, room_id: &str, event_id: &str) {
+        let path = format!(
+            "/rooms/{}/redact/{}/{}",
+            urlencode(room_id),
+            urlencode(event_id),
+            self.next_txn_id()
+        );
+        self.request(reqwest::Method::PUT, &path, json!({})).await;
+    }
+
+    async fn handle(&mut self, room_id: &str, event: Event) {
+        if event.kind != "m.room.message" || event.sender == self.user_id {
+            return;
+        }
+        let text = match event.content.get("body").and_then(|b| b.as_str()) {
+            Some(text) => text.to_string(),
+            None => return,
+        };
+
+        let prefix = get_matrix_config(&self.data, room_id)
+            .await
+            .prefix()
+            .to_string();
+        if let Some(cmd) = parse_command(&text, &prefix) {
+            match cmd {
+                Ok(cmd) => self.run_command(cmd, room_id, &event.sender).await,
+                Err(error) => self.send(room_id, &with_prefix(error, &prefix)).await,
+            }
+            return;
+        }
+
+        let bridged = find_guild(&self.data, |config| {
+            config
+                .bridge
+                .as_ref()
+                .is_some_and(|b| b.matrix.as_deref() == Some(room_id))
+        })
+        .await;
+        if let Some((_, config)) = bridged {
+            self.handle_bridged(room_id, &event, &text, config).await;
+            return;
+        }
+
+        if "." == text {
+            self.generate_story(room_id).await;
+            return;
+        }
+
+        let config = get_matrix_config(&self.data, room_id).await;
+        let censor = WordFilter::new(&config);
+        let rules = config.rules(config.channel_id);
+        let story = self.stories.get(room_id).map(Vec::as_slice);
+        if check_message(&text, &censor, &rules).await.is_err()
+            || check_phrases(story.unwrap_or_default(), &text, &config.banned_phrases).is_err()
+        {
+            self.redact(room_id, &event.event_id).await;
+            return;
+        }
+
+        self.stories
+            .entry(room_id.to_string())
+            .or_default()
+            .push(story_word(&text, rules.emoji_policy));
+    }
+
+    /// Handles a message in a room bridged to a Discord story channel. The
+    /// story lives on Discord, so accepted words are only passed along.
+    async fn handle_bridged(&self, room_id: &str, event: &Event, text: &str, config: Config) {
+        let channel_id = config.channel_id;
+        if "." == text {
+            publish(&self.data, FeedEvent::Compile { channel_id }).await;
+            return;
+        }
+
+        let censor = WordFilter::new(&config);
+        let rules = config.rules(config.channel_id);
+        if check_message(text, &censor, &rules).await.is_err() {
+            self.redact(room_id, &event.event_id).await;
+            return;
+        }
+
+        publish(
+            &self.data,
+            FeedEvent::Word {
+                channel_id,
+                word: text.to_string(),
+                author: event.sender.clone(),
+                source: Source::Matrix,
+            },
+        )
+        .await;
+    }
+
+    /// Echoes words and compiled stories from bridged platforms into their room.
+    async fn forward_bridged(self) {
+        let mut rx = subscribe(&self.data).await;
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return,
+            };
+
+            let room_id = match find_bridged_channel(&self.data, event.channel_id()).await {
+                Some((_, config)) => match config.bridge.and_then(|b| b.matrix) {
+                    Some(room_id) => room_id,
+                    None => continue,
+                },
+                None => continue,
+            };
+
+            match event {
+                FeedEvent::Word {
+                    word,
+                    author,
+                    source,
+                    ..
+                } if source != Source::Matrix => {
+                    self.send(&room_id, &format!("{} ({}): {}", author, source, word))
+                        .await;
+                }
+                FeedEvent::End { story, .. } => {
+                    let words: Vec<String> = story.split_whitespace().map(str::to_string).collect();
+                    for chunk in story_chunks(&words, MESSAGE_LIMIT) {
+                        self.send(&room_id, &chunk).await;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    async fn generate_story(&mut self, room_id: &str) {
+        let words = match self.stories.remove(room_id) {
+            Some(words) if !words.is_empty() => words,
+            _ => return,
+        };
+
+        let config = get_matrix_config(&self.data, room_id).await;
+        if let Some(token) = &config.gist_token {
+            if let Some(url) = gist::publish(token, "Story", &words.join(" ")).await {
+                self.send(room_id, &url).await;
+            }
+        }
+
+        for chunk in story_chunks(&words, MESSAGE_LIMIT) {
+            self.send(room_id, &chunk).await;
+        }
+    }
+
+    async fn is_admin(&self, room_id: &str, user_id: &str) -> bool {
+        let path = format!("/rooms/{}/state/m.room.power_levels", urlencode(room_id));
+        match self.get::<PowerLevels>(&path).await {
+            Some(levels) => {
+                let level = levels
+                    .users
+                    .get(user_id)
+                    .copied()
+                    .unwrap_or(levels.users_default);
+                level >= ADMIN_POWER_LEVEL
+            }
+            None => false,
+        }
+    }
+
+    async fn run_command(&self, cmd: Command, room_id: &str, user_id: &str) {
+        let locale = get_matrix_config(&self.data, room_id).await.locale;
+        if !self.is_admin(room_id, user_id).await {
+            self.send(room_id, &tr(locale, "admins-only")).await;
+            return;
+        }
+
+        match cmd {
+            Command::BanWord(word) => {
+                set_matrix_config(&self.data, room_id, |config| config.ban(word)).await;
+            }
+            Command::UnbanWord(word) => {
+                set_matrix_config(&self.data, room_id, |config| config.unban(&word)).await;
+            }
+            Command::SetEmojiPolicy(policy) => {
+                set_matrix_config(&self.data, room_id, |config| {
+                    config.emoji_policy = policy;
+                })
+                .await;
+            }
+            Command::SetMatchMode(mode) => {
+                set_matrix_config(&self.data, room_id, |config| {
+                    config.match_mode = mode;
+                })
+                .await;
+            }
+            Command::SetRule(
+                None,
+                rule @ (Rule::MatchMode(_) | Rule::MaxWords(_) | Rule::EmojiPolicy(_)),
+            ) => {
+                set_matrix_config(&self.data, room_id, |config| config.set_rule(None, rule)).await;
+            }
+            Command::SetPrefix(prefix) => {
+                set_matrix_config(&self.data, room_id, |config| {
+                    config.prefix = Some(prefix);
+                })
+                .await;
+            }
+            Command::SetLocale(locale) => {
+                set_matrix_config(&self.data, room_id, |config| {
+                    config.locale = locale;
+                })
+                .await;
+            }
+            _ => {
+                let reply = tr_args(locale, "setting-unavailable", &[("platform", "Matrix")]);
+                self.send(room_id, &reply).await;
+                return;
+            }
+        }
+
+        self.send(room_id, &tr(locale, "settings-updated")).await;
+    }
+}
+
+fn urlencode(s: &str) -> String {
+    form_urlencoded::byte_serialize(s.as_bytes()).collect()
+}
@@ -0,0 +1,60 @@
+use std::env;
+use std::fs;
+
+use serenity::http::Http;
+use serenity::model::prelude::*;
+
+use crate::storage::{self, FileStorage, Storage};
+
+/// Imports `CONFIG_FILE` into the storage picked with `STORAGE`, run as
+/// `one-word-story migrate`. A config from before settings were kept per
+/// guild is moved to the guild of its story channel, which is looked up
+/// with `DISCORD_TOKEN`. The original file is kept next to it as `.bak`.
+pub async fn run() {
+    let path = match env::var("CONFIG_FILE") {
+        Ok(path) => path,
+        Err(_) => {
+            println!("Mising CONFIG_FILE env. Nothing to migrate.");
+            return;
+        }
+    };
+    let mut configs = match FileStorage::from_env().load_configs().await {
+        Some(configs) => configs,
+        None => {
+            println!("Could not read {}.", path);
+            return;
+        }
+    };
+
+    if let Some(legacy) = configs.legacy.take() {
+        let token = env::var("DISCORD_TOKEN").expect("Missing discord token.");
+        match Http::new(&token).get_channel(legacy.channel_id.0).await {
+            Ok(Channel::Guild(channel)) => {
+                println!("Moving legacy config to guild {}", channel.guild_id);
+                configs.guilds.insert(channel.guild_id, legacy);
+            }
+            other => {
+                println!(
+                    "Could not find the guild of channel {}, keeping the legacy config {:?}",
+                    legacy.channel_id,
+                    other.err()
+                );
+                configs.legacy = Some(legacy);
+            }
+        }
+    }
+
+    let backup = format!("{}.bak", path);
+    if let Err(why) = fs::copy(&path, &backup) {
+        println!("Error backing up {} {:?}", path, why);
+        return;
+    }
+    storage::from_env().save_configs(&configs).await;
+    println!(
+        "Migrated {} guilds, {} Telegram groups and {} Matrix rooms. The old file is {}.",
+        configs.guilds.len(),
+        configs.telegram.len(),
+        configs.matrix.len(),
+        backup
+    );
+}
@@ -0,0 +1,15 @@
+use serenity::http::Http;
+
+use crate::config::Config;
+
+/// Reports `text` to the guild's mod log channel, or the console if it has none.
+pub async fn post(http: &Http, config: &Config, text: &str) {
+    match config.mod_log_channel {
+        Some(channel_id) => {
+            if let Err(why) = channel_id.say(http, text).await {
+                println!("Error posting to mod log {:?}", why);
+            }
+        }
+        None => println!("{}", text),
+    }
+}
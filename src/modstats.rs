@@ -0,0 +1,75 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+/// Deleted contributions per reason, by the translation key of the reason.
+pub type Counts = BTreeMap<&'static str, u64>;
+
+/// Contributions deleted in each guild since the bot started.
+pub struct ModStatsContainer;
+impl TypeMapKey for ModStatsContainer {
+    type Value = Arc<Mutex<HashMap<GuildId, Counts>>>;
+}
+
+async fn counts(data: &RwLock<TypeMap>) -> Arc<Mutex<HashMap<GuildId, Counts>>> {
+    let data = data.read().await;
+    data.get::<ModStatsContainer>()
+        .expect("Expected ModStatsContainer in TypeMap")
+        .clone()
+}
+
+/// Counts a contribution deleted for `reason`.
+pub async fn count(data: &RwLock<TypeMap>, guild_id: GuildId, reason: &'static str) {
+    let counts = counts(data).await;
+    let mut counts = counts.lock().await;
+    *counts
+        .entry(guild_id)
+        .or_default()
+        .entry(reason)
+        .or_default() += 1;
+}
+
+pub async fn guild(data: &RwLock<TypeMap>, guild_id: GuildId) -> Counts {
+    let counts = counts(data).await;
+    let counts = counts.lock().await;
+    counts.get(&guild_id).cloned().unwrap_or_default()
+}
+
+/// `reason-too-many-words` as `too-many-words`.
+fn name(reason: &str) -> &str {
+    reason.strip_prefix("reason-").unwrap_or(reason)
+}
+
+/// `counts` as lines of `one-word modstats`, most common first.
+pub fn render(counts: &Counts) -> String {
+    let mut counts: Vec<(&&str, &u64)> = counts.iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(a.1));
+    counts
+        .into_iter()
+        .map(|(reason, count)| format!("`{}` {}", name(reason), count))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Deletions of all guilds in the Prometheus text format.
+pub async fn metrics(data: &RwLock<TypeMap>) -> String {
+    let mut totals = Counts::new();
+    for counts in counts(data).await.lock().await.values() {
+        for (reason, count) in counts {
+            *totals.entry(reason).or_default() += count;
+        }
+    }
+    let mut text = String::from(
+        "# HELP one_word_deletions_total Contributions deleted, by reason.\n# TYPE one_word_deletions_total counter\n",
+    );
+    for (reason, count) in totals {
+        text += &format!(
+            "one_word_deletions_total{{reason=\"{}\"}} {}\n",
+            name(reason),
+            count
+        );
+    }
+    text
+}
@@ -0,0 +1,86 @@
+use serenity::cache::Cache;
+use serenity::http::Http;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::config::{config_lock, Config};
+use crate::i18n::{tr, tr_args};
+use crate::scheduler::now;
+
+/// Latest words of the story quoted in a nudge.
+const NUDGE_WORDS: usize = 5;
+/// Messages read to find them.
+const LOOKBACK: u64 = 25;
+
+/// Nudges every story channel that has been quiet for longer than its
+/// guild allows. The nudge itself restarts the clock, so a silent channel
+/// gets at most one per period.
+pub async fn post_due(data: &RwLock<TypeMap>, cache: &Cache, http: &Http) {
+    let configs: Vec<Config> = {
+        let lock = config_lock(data).await;
+        let configs = lock.read().await;
+        configs
+            .guilds
+            .values()
+            .filter(|c| c.nudge_after > 0)
+            .cloned()
+            .collect()
+    };
+
+    for config in configs {
+        let channels = std::iter::once(config.channel_id)
+            .chain(config.channels.keys().copied())
+            .filter(|id| id.0 != 0)
+            // Forums keep their stories in posts, which aren't nudged.
+            .filter(|id| cache.channel(*id).is_none_or(|c| !is_forum(&c)));
+        for channel_id in channels {
+            nudge(http, &config, channel_id).await;
+        }
+    }
+}
+
+fn is_forum(channel: &Channel) -> bool {
+    matches!(channel, Channel::Guild(c) if c.kind == ChannelType::Forum)
+}
+
+async fn nudge(http: &Http, config: &Config, channel_id: ChannelId) {
+    let messages = match channel_id.messages(http, |r| r.limit(LOOKBACK)).await {
+        Ok(messages) => messages,
+        Err(why) => {
+            println!("Error reading story channel to nudge {:?}", why);
+            return;
+        }
+    };
+    let idle = match messages.first() {
+        Some(newest) => now().saturating_sub(newest.timestamp.unix_timestamp() as u64),
+        // Nothing to restart.
+        None => return,
+    };
+    if idle < config.nudge_after {
+        return;
+    }
+
+    let bridge = config.bridge.as_ref();
+    let mut words: Vec<&str> = messages
+        .iter()
+        .take_while(|m| "." != m.content)
+        .filter(|m| crate::is_contribution(m, bridge))
+        .take(NUDGE_WORDS)
+        .map(|m| m.content.as_str())
+        .collect();
+    words.reverse();
+    let mut content = match words.is_empty() {
+        true => tr(config.locale, "nudge-new"),
+        false => tr_args(
+            config.locale,
+            "nudge",
+            &[("words", &format!("…{}", words.join(" ")))],
+        ),
+    };
+    if let Some(role) = config.nudge_role {
+        content = format!("{} {}", role.mention(), content);
+    }
+    if let Err(why) = channel_id.say(http, content).await {
+        println!("Error nudging story channel {:?}", why);
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use serenity::http::Http;
+use serenity::model::prelude::*;
+
+use crate::config::Config;
+use crate::i18n::tr;
+use crate::storage::StoryRecord;
+
+/// Deletes the bot's pins in `channel_id` that are clutter: rules pinned
+/// before the current ones, and stories pinned more than once, as compiles
+/// could post before they were idempotent. Of the copies of a story, the
+/// one in the archive is kept, otherwise the oldest. Returns how many pins
+/// were deleted.
+pub async fn cleanup(
+    http: &Http,
+    bot_id: UserId,
+    config: &Config,
+    stories: &[StoryRecord],
+    channel_id: ChannelId,
+) -> usize {
+    let pins = match channel_id.pins(http).await {
+        Ok(pins) => pins,
+        Err(why) => {
+            println!("Error fetching pins {:?}", why);
+            return 0;
+        }
+    };
+    let rules_title = tr(config.locale, "rules-title");
+    let rules_pin = config.rules_pins.get(&channel_id);
+    let archived = |id: MessageId| stories.iter().any(|s| s.message_id == Some(id));
+
+    let mut obsolete = Vec::new();
+    // The copy of each story kept so far, by its text.
+    let mut kept: HashMap<String, &Message> = HashMap::new();
+    let mut pins: Vec<&Message> = pins
+        .iter()
+        .filter(|m| m.author.id == bot_id && !m.embeds.is_empty())
+        .collect();
+    pins.sort_by_key(|m| m.id);
+    for m in pins {
+        if m.embeds[0].title.as_deref() == Some(rules_title.as_str()) {
+            if rules_pin != Some(&m.id) {
+                obsolete.push(m.id);
+            }
+            continue;
+        }
+        let text: Vec<&str> = m
+            .embeds
+            .iter()
+            .filter_map(|e| e.description.as_deref())
+            .collect();
+        let text = text.join(" ");
+        if text.is_empty() {
+            continue;
+        }
+        match kept.get(&text) {
+            Some(copy) if archived(m.id) && !archived(copy.id) => {
+                obsolete.push(copy.id);
+                kept.insert(text, m);
+            }
+            Some(_) => obsolete.push(m.id),
+            None => {
+                kept.insert(text, m);
+            }
+        }
+    }
+
+    let mut deleted = 0;
+    for id in obsolete {
+        // Deleting the message takes it out of the pins.
+        match channel_id.delete_message(http, id).await {
+            Ok(()) => deleted += 1,
+            Err(why) => println!("Error deleting pin {:?}", why),
+        }
+    }
+    deleted
+}
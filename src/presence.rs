@@ -0,0 +1,58 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::client::bridge::gateway::ShardManager;
+use serenity::model::gateway::Activity;
+use serenity::prelude::*;
+
+use crate::storage::{storage, Stats};
+
+/// How often the stats in the bot's status are refreshed.
+const REFRESH: Duration = Duration::from_secs(10 * 60);
+/// How soon to try again while no shard is connected yet.
+const RETRY: Duration = Duration::from_secs(10);
+
+/// Keeps the bot's status showing how much was written in every guild,
+/// until the process exits.
+pub async fn run(data: Arc<RwLock<TypeMap>>, shard_manager: Arc<Mutex<ShardManager>>) {
+    loop {
+        let stats = storage(&data).await.stats(None).await;
+        let activity = Activity::playing(status(&stats));
+        let updated = {
+            let manager = shard_manager.lock().await;
+            let runners = manager.runners.lock().await;
+            for runner in runners.values() {
+                runner.runner_tx.set_activity(Some(activity.clone()));
+            }
+            !runners.is_empty()
+        };
+        tokio::time::sleep(if updated { REFRESH } else { RETRY }).await;
+    }
+}
+
+fn status(stats: &Stats) -> String {
+    format!(
+        "📖 {} {} across {} {}",
+        thousands(stats.words),
+        if stats.words == 1 { "word" } else { "words" },
+        thousands(stats.stories),
+        if stats.stories == 1 {
+            "story"
+        } else {
+            "stories"
+        },
+    )
+}
+
+/// `n` with thousands separated by commas, like 1,243.
+fn thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
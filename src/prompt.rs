@@ -0,0 +1,61 @@
+use rand::seq::SliceRandom;
+
+use crate::config::Config;
+use crate::i18n::tr_args;
+use crate::llm::{self, LlmConfig};
+
+/// Used when no LLM is configured, or it fails to answer.
+const BUILTIN_PROMPTS: &[&str] = &[
+    "Once upon a time, in a kingdom made entirely of cheese,",
+    "The last robot on Earth woke up and",
+    "Nobody believed the cat when it said",
+    "Deep beneath the ocean, a tiny submarine",
+    "It was the worst birthday party ever because",
+    "The wizard's apprentice accidentally",
+    "On the first day of summer, the town's only dragon",
+    "Theme: a heist gone hilariously wrong.",
+    "Theme: a mystery on a night train.",
+    "Theme: first contact with very polite aliens.",
+    "The map was clearly wrong, because",
+    "Every morning, the lighthouse keeper found",
+];
+
+/// A prompt from the guild's running event, if it has any, or else from
+/// [`generate`]. The event's required words are added to it.
+pub async fn for_config(config: &Config) -> String {
+    let event = config.active_event(&config.month_day());
+    let themed = event.and_then(|e| e.prompts.choose(&mut rand::thread_rng()).cloned());
+    let prompt = match themed {
+        Some(prompt) => prompt,
+        None => generate(config.llm.as_ref()).await,
+    };
+    match event {
+        Some(event) if !event.required_words.is_empty() => {
+            let words = event.required_words.join(", ");
+            let required = tr_args(config.locale, "event-required", &[("words", &words)]);
+            format!("{} {}", prompt, required)
+        }
+        _ => prompt,
+    }
+}
+
+/// An opening line or theme to start a new story with.
+pub async fn generate(llm: Option<&LlmConfig>) -> String {
+    if let Some(llm) = llm {
+        let prompt = llm::complete(
+            llm,
+            "You start collaborative stories that a group continues one word at a time. \
+             Reply with a single short opening line or theme and nothing else.",
+            "Give me a new story prompt.",
+        )
+        .await;
+        if let Some(prompt) = prompt {
+            return prompt;
+        }
+    }
+
+    BUILTIN_PROMPTS
+        .choose(&mut rand::thread_rng())
+        .unwrap()
+        .to_string()
+}
@@ -0,0 +1,104 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::audit;
+use crate::config::{get_config, set_config, Config};
+use crate::forum;
+use crate::modlog;
+use crate::scheduler::now;
+
+/// Accounts created, or members who joined, less than this long ago count
+/// as new.
+const NEW_ACCOUNT: u64 = 7 * 24 * 60 * 60;
+/// Time the burst has to happen in.
+const WINDOW: Duration = Duration::from_secs(60);
+/// Invalid messages from new accounts in the window that make a raid...
+const RAID_MESSAGES: usize = 10;
+/// ...when they came from at least this many of them.
+const RAID_ACCOUNTS: usize = 4;
+
+/// Recent invalid messages from new accounts in each story channel, oldest
+/// first.
+pub struct RaidContainer;
+impl TypeMapKey for RaidContainer {
+    type Value = Arc<Mutex<HashMap<ChannelId, VecDeque<(Instant, UserId)>>>>;
+}
+
+async fn recent(
+    data: &RwLock<TypeMap>,
+) -> Arc<Mutex<HashMap<ChannelId, VecDeque<(Instant, UserId)>>>> {
+    let data = data.read().await;
+    data.get::<RaidContainer>()
+        .expect("Expected RaidContainer in TypeMap")
+        .clone()
+}
+
+fn is_new(msg: &Message) -> bool {
+    let joined_at = msg.member.as_ref().and_then(|m| m.joined_at);
+    [Some(msg.author.created_at()), joined_at]
+        .into_iter()
+        .flatten()
+        .any(|at| now().saturating_sub(at.unix_timestamp() as u64) < NEW_ACCOUNT)
+}
+
+/// Notes that the invalid `msg` was deleted, and pauses the game in its
+/// story channel if new accounts are flooding it. Only a mod resuming the
+/// game lets contributions in again.
+pub async fn invalid(ctx: &Context, msg: &Message, config: &Config) {
+    let guild_id = match msg.guild_id {
+        Some(id) => id,
+        None => return,
+    };
+    let venue = forum::venue(&ctx.cache, guild_id, msg.channel_id);
+    if config.skip_raid_protection || config.paused.contains(&venue) || !is_new(msg) {
+        return;
+    }
+
+    let raid = {
+        let recent = recent(&ctx.data).await;
+        let mut recent = recent.lock().await;
+        let messages = recent.entry(venue).or_default();
+        messages.push_back((Instant::now(), msg.author.id));
+        while messages
+            .front()
+            .is_some_and(|(at, _)| at.elapsed() > WINDOW)
+        {
+            messages.pop_front();
+        }
+        let accounts: HashSet<UserId> = messages.iter().map(|(_, user)| *user).collect();
+        let raid = messages.len() >= RAID_MESSAGES && accounts.len() >= RAID_ACCOUNTS;
+        if raid {
+            recent.remove(&venue);
+        }
+        raid
+    };
+    if !raid {
+        return;
+    }
+
+    let before = get_config(&ctx.data, guild_id).await;
+    set_config(&ctx.data, guild_id, |config| {
+        config.paused.insert(venue);
+    })
+    .await;
+    let after = get_config(&ctx.data, guild_id).await;
+    audit::record(
+        &ctx.data,
+        guild_id,
+        "raid protection",
+        None,
+        &before,
+        &after,
+    )
+    .await;
+    let notice = format!(
+        "Paused the game in {}: {} invalid messages from new accounts within a minute. Run `one-word resume` there once it's safe.",
+        venue.mention(),
+        RAID_MESSAGES
+    );
+    modlog::post(&ctx.http, config, &notice).await;
+}
@@ -0,0 +1,275 @@
+use serenity::builder::CreateEmbed;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::config::{get_config, SeverityAction};
+use crate::game::WordFilter;
+use crate::i18n::tr;
+use crate::storage::{storage, Revision, StoryEdit, StoryRecord};
+use crate::{download, embed_title, paginate, EmbedLengths};
+
+/// The text of `story` from the words recorded while it was written, or
+/// as archived if mods edited it, they were pruned or it predates them.
+async fn story_text(data: &RwLock<TypeMap>, story: &StoryRecord) -> String {
+    let started_at = match (story.started_at, story.revisions.is_empty()) {
+        (Some(at), true) => at,
+        _ => return story.text.clone(),
+    };
+    let words: Vec<String> = storage(data)
+        .await
+        .contributions(story.guild_id, started_at)
+        .await
+        .into_iter()
+        .filter(|c| c.channel_id == story.channel_id && c.at <= story.at)
+        .map(|c| c.word)
+        .collect();
+    match words.is_empty() {
+        true => story.text.clone(),
+        false => words.join(" "),
+    }
+}
+
+async fn find_story(
+    data: &RwLock<TypeMap>,
+    guild_id: GuildId,
+    id: u64,
+) -> Result<StoryRecord, &'static str> {
+    storage(data)
+        .await
+        .stories(guild_id, 0)
+        .await
+        .into_iter()
+        .find(|s| s.is(id))
+        .ok_or("archive-not-found")
+}
+
+/// The messages `story` was posted in, in order.
+async fn posted_messages(ctx: &Context, story: &StoryRecord) -> Result<Vec<Message>, &'static str> {
+    let story_id = story.message_id.ok_or("archive-not-found")?;
+    let channel_id = story.channel_id;
+    let first = channel_id
+        .message(&ctx.http, story_id)
+        .await
+        .map_err(|_| "archive-not-found")?;
+    let bot_id = ctx.cache.current_user_id();
+    let mut rest: Vec<Message> = match channel_id
+        .messages(&ctx.http, |r| r.after(story_id).limit(100))
+        .await
+    {
+        Ok(messages) => messages
+            .into_iter()
+            .filter(|m| m.author.id == bot_id && download::continues(m, story_id))
+            .collect(),
+        Err(why) => {
+            println!("Error fetching story messages {:?}", why);
+            return Err("archive-not-found");
+        }
+    };
+    rest.sort_by_key(|m| m.id);
+    rest.insert(0, first);
+    Ok(rest)
+}
+
+/// Edits `messages` to show `text` as `story` in the guild's current
+/// style, deleting those left over. The first one becomes the start of the
+/// story.
+async fn restyle(
+    ctx: &Context,
+    story: &StoryRecord,
+    text: &str,
+    messages: Vec<Message>,
+) -> Result<(), &'static str> {
+    let config = get_config(&ctx.data, story.guild_id).await;
+    let rules = config.rules(story.channel_id);
+    let filter = WordFilter::new(&config);
+    let spoiler = !config.content_warnings(text).is_empty();
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| match filter.severity(word, rules.match_mode) {
+            Some((_, SeverityAction::Spoiler)) if !spoiler => format!("||{}||", word),
+            _ => word.to_string(),
+        })
+        .collect();
+
+    // The summary, trivia, warning and images are kept as they were.
+    let first = messages.first().and_then(|m| m.embeds.first());
+    let kept = first.cloned().map(CreateEmbed::from).unwrap_or_default();
+    let extras = first.map_or(0, |e| {
+        e.fields
+            .iter()
+            .map(|f| f.name.len() + f.value.len())
+            .sum::<usize>()
+            + e.author.as_ref().map_or(0, |a| a.name.len())
+    });
+    let title = embed_title(&config, &story.title, story.number.unwrap_or_default());
+    let continued = tr(config.locale, "story-continued");
+    let footer = config.embed.footer.as_deref();
+    let lengths = EmbedLengths {
+        first: title.len() + extras,
+        rest: continued.len(),
+        footer: footer.map_or(0, str::len),
+        markup: if spoiler { 4 } else { 0 },
+    };
+    let pages = paginate(&words, lengths);
+    if pages.len() > messages.len() {
+        return Err("recompile-too-long");
+    }
+
+    let color = config
+        .embed
+        .color
+        .or_else(|| first.and_then(|e| e.colour).map(|c| c.0));
+    let thumbnail = match config.embed.thumbnail {
+        true => ctx
+            .cache
+            .guild_field(story.guild_id, |g| g.icon_url())
+            .flatten(),
+        false => None,
+    };
+    let download = tr(config.locale, "story-download");
+    let story_id = messages.first().map(|m| m.id);
+    let mut messages = messages.into_iter();
+    for (page, embeds) in pages.iter().enumerate() {
+        let last = embeds.len() - 1;
+        let embeds: Vec<CreateEmbed> = embeds
+            .iter()
+            .enumerate()
+            .map(|(i, words)| {
+                let mut e = match (page, i) {
+                    (0, 0) => kept.clone(),
+                    _ => CreateEmbed::default(),
+                };
+                e.0.remove("footer");
+                e.0.remove("description");
+                if !words.is_empty() {
+                    match spoiler {
+                        true => e.description(format!("||{}||", words.join(" "))),
+                        false => e.description(words.join(" ")),
+                    };
+                }
+                if let Some(color) = color {
+                    e.color(color);
+                }
+                if let (Some(footer), true) = (footer, i == last) {
+                    e.footer(|f| f.text(footer));
+                }
+                if let ((0, 0), Some(thumbnail)) = ((page, i), &thumbnail) {
+                    e.thumbnail(thumbnail);
+                }
+                match (page, i) {
+                    (0, 0) => e.title(&title),
+                    _ => e.title(&continued),
+                };
+                e
+            })
+            .collect();
+        let mut message = match messages.next() {
+            Some(message) => message,
+            None => break,
+        };
+        // Messages of a merged story may have started one of their own.
+        let continues = story_id.filter(|_| page > 0);
+        if message.pinned && page > 0 {
+            if let Err(why) = message.unpin(&ctx.http).await {
+                println!("Error unpinning story message {:?}", why);
+            }
+        }
+        let edited = message
+            .edit(&ctx.http, |m| {
+                m.set_embeds(embeds)
+                    .components(|c| download::button(c, &download, continues))
+            })
+            .await;
+        if let Err(why) = edited {
+            println!("Error editing story {:?}", why);
+        }
+    }
+    // The story got shorter.
+    for message in messages {
+        if let Err(why) = message.delete(&ctx.http).await {
+            println!("Error deleting story message {:?}", why);
+        }
+    }
+    Ok(())
+}
+
+/// Posts the archived story with the number, or posted in the message,
+/// again in the guild's current style, editing the messages it was posted
+/// in. Errors are translation keys.
+pub async fn recompile(ctx: &Context, guild_id: GuildId, id: u64) -> Result<(), &'static str> {
+    let mut story = find_story(&ctx.data, guild_id, id).await?;
+    let messages = posted_messages(ctx, &story).await?;
+    let text = story_text(&ctx.data, &story).await;
+    restyle(ctx, &story, &text, messages).await?;
+    story.text = text;
+    storage(&ctx.data).await.update_story(story).await;
+    Ok(())
+}
+
+/// Makes `edit` by `editor` to the archived story with the number, or
+/// posted in the message, and updates the posted story. Errors are
+/// translation keys.
+pub async fn edit(
+    ctx: &Context,
+    guild_id: GuildId,
+    id: u64,
+    edit: &StoryEdit,
+    editor: &str,
+) -> Result<(), &'static str> {
+    let mut story = find_story(&ctx.data, guild_id, id).await?;
+    if !story.edit(edit, editor) {
+        return Err("archive-edit-no-match");
+    }
+    storage(&ctx.data).await.update_story(story).await;
+    recompile(ctx, guild_id, id).await
+}
+
+/// Joins two archived stories of a channel, split by an accidental end
+/// marker, into the older one, posted in the messages of both. `merger`
+/// is kept as the editor of the text. Errors are translation keys.
+pub async fn merge(
+    ctx: &Context,
+    guild_id: GuildId,
+    ids: (u64, u64),
+    merger: &str,
+) -> Result<(), &'static str> {
+    let a = find_story(&ctx.data, guild_id, ids.0).await?;
+    let b = find_story(&ctx.data, guild_id, ids.1).await?;
+    if a.message_id == b.message_id {
+        return Err("merge-same-story");
+    }
+    if a.channel_id != b.channel_id {
+        return Err("merge-other-channel");
+    }
+    let (mut first, second) = match a.at <= b.at {
+        true => (a, b),
+        false => (b, a),
+    };
+    let mut messages = posted_messages(ctx, &first).await?;
+    messages.extend(posted_messages(ctx, &second).await?);
+
+    let text = format!(
+        "{} {}",
+        story_text(&ctx.data, &first).await,
+        story_text(&ctx.data, &second).await
+    );
+    first.revisions.push(Revision {
+        text: std::mem::replace(&mut first.text, text.clone()),
+        editor: merger.to_string(),
+        at: crate::scheduler::now(),
+    });
+    for contributor in &second.contributors {
+        if !first.contributors.contains(contributor) {
+            first.contributors.push(contributor.clone());
+        }
+    }
+    first.at = second.at;
+    first.ended_at = second.ended_at;
+    first.ender = second.ender.clone();
+
+    restyle(ctx, &first, &text, messages).await?;
+    let storage = storage(&ctx.data).await;
+    storage.update_story(first).await;
+    storage.delete_story(&second).await;
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::command::parse_command;
+use crate::config::get_config;
+
+/// Most messages fetched per request, the API's limit.
+const PAGE_SIZE: u64 = 100;
+
+/// The newest message seen in each story channel, with its guild.
+pub struct SeenContainer;
+impl TypeMapKey for SeenContainer {
+    type Value = Arc<Mutex<HashMap<ChannelId, (GuildId, MessageId)>>>;
+}
+
+async fn seen_messages(
+    data: &RwLock<TypeMap>,
+) -> Arc<Mutex<HashMap<ChannelId, (GuildId, MessageId)>>> {
+    let data = data.read().await;
+    data.get::<SeenContainer>()
+        .expect("Expected SeenContainer in TypeMap")
+        .clone()
+}
+
+pub async fn seen(data: &RwLock<TypeMap>, msg: &Message, guild_id: GuildId) {
+    let seen = seen_messages(data).await;
+    let mut seen = seen.lock().await;
+    let newest = seen.entry(msg.channel_id).or_insert((guild_id, msg.id));
+    newest.1 = newest.1.max(msg.id);
+}
+
+/// Checks the messages posted in story channels while the bot was
+/// disconnected from the gateway, as if they had just been posted.
+pub async fn run(ctx: &Context) {
+    let channels: Vec<_> = seen_messages(&ctx.data)
+        .await
+        .lock()
+        .await
+        .iter()
+        .map(|(channel_id, (guild_id, id))| (*channel_id, *guild_id, *id))
+        .collect();
+
+    for (channel_id, guild_id, mut after) in channels {
+        loop {
+            let mut missed = match channel_id
+                .messages(&ctx.http, |r| r.after(after).limit(PAGE_SIZE))
+                .await
+            {
+                Ok(missed) => missed,
+                Err(why) => {
+                    println!("Error fetching missed messages {:?}", why);
+                    break;
+                }
+            };
+            missed.sort_by_key(|m| m.id);
+            let done = (missed.len() as u64) < PAGE_SIZE;
+            if let Some(newest) = missed.last() {
+                after = newest.id;
+            }
+
+            let prefix = get_config(&ctx.data, guild_id).await.prefix().to_string();
+            for mut msg in missed {
+                // Stale commands are dropped rather than run out of order.
+                if msg.author.bot || parse_command(&msg.content, &prefix).is_some() {
+                    continue;
+                }
+                msg.guild_id = Some(guild_id);
+                crate::parse_message(ctx, &msg).await;
+            }
+            if done {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::env;
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::config::{config_lock, remove_config, set_config, RetentionPolicy};
+use crate::scheduler::now;
+use crate::storage::{storage, HistoryKind};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Seconds the settings and history of a guild that removed the bot are
+/// kept, in case it is invited back, from `REMOVED_GUILD_RETENTION_DAYS`.
+/// Without it, they are kept for good.
+fn removed_guild_retention() -> Option<u64> {
+    let days: u64 = env::var("REMOVED_GUILD_RETENTION_DAYS")
+        .ok()?
+        .parse()
+        .ok()?;
+    Some(days * DAY)
+}
+
+/// Notes when the bot was removed from `guild_id`, if it was set up there.
+pub async fn guild_removed(data: &RwLock<TypeMap>, guild_id: GuildId) {
+    if !config_lock(data)
+        .await
+        .read()
+        .await
+        .guilds
+        .contains_key(&guild_id)
+    {
+        return;
+    }
+    println!("Removed from guild {}", guild_id);
+    set_config(data, guild_id, |config| config.removed_at = Some(now())).await;
+}
+
+/// Keeps the data of `guild_id`, now that the bot is back.
+pub async fn guild_returned(data: &RwLock<TypeMap>, guild_id: GuildId) {
+    let removed = config_lock(data)
+        .await
+        .read()
+        .await
+        .guilds
+        .get(&guild_id)
+        .is_some_and(|c| c.removed_at.is_some());
+    if removed {
+        println!("Invited back to guild {}", guild_id);
+        set_config(data, guild_id, |config| config.removed_at = None).await;
+    }
+}
+
+/// Deletes the records guilds keep for a limited time once it is over.
+/// Returns how many records of each kind were deleted in each guild.
+pub async fn prune_history(data: &RwLock<TypeMap>) -> Vec<(GuildId, HistoryKind, usize)> {
+    let policies: Vec<(GuildId, RetentionPolicy)> = {
+        let lock = config_lock(data).await;
+        let configs = lock.read().await;
+        configs
+            .guilds
+            .iter()
+            .filter(|(_, c)| c.retention != RetentionPolicy::default())
+            .map(|(id, c)| (*id, c.retention))
+            .collect()
+    };
+    let storage = storage(data).await;
+    let mut pruned = Vec::new();
+    for (guild_id, policy) in policies {
+        for kind in [HistoryKind::Contributions, HistoryKind::Audit] {
+            if let Some(keep) = policy.get(kind) {
+                let before = now().saturating_sub(keep);
+                let count = storage.prune(guild_id, kind, before).await;
+                pruned.push((guild_id, kind, count));
+            }
+        }
+    }
+    pruned
+}
+
+/// Deletes everything kept about guilds that removed the bot longer ago
+/// than the retention period.
+pub async fn purge_removed_guilds(data: &RwLock<TypeMap>) {
+    let retention = match removed_guild_retention() {
+        Some(retention) => retention,
+        None => return,
+    };
+    let expired: Vec<GuildId> = {
+        let lock = config_lock(data).await;
+        let configs = lock.read().await;
+        configs
+            .guilds
+            .iter()
+            .filter(|(_, c)| c.removed_at.is_some_and(|at| at + retention <= now()))
+            .map(|(id, _)| *id)
+            .collect()
+    };
+    for guild_id in expired {
+        println!("Purging data of removed guild {}", guild_id);
+        storage(data).await.purge_guild(guild_id).await;
+        remove_config(data, guild_id).await;
+    }
+}
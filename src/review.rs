@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+use serenity::model::application::component::{ActionRowComponent, ButtonStyle};
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::actions;
+use crate::appeal::{decide, is_moderator};
+use crate::bridge;
+use crate::command::Tier;
+use crate::config::{get_config, set_config, Config};
+use crate::storage::storage;
+
+/// `review-approve:<channel id>:<user id>`, on the review channel post.
+const APPROVE: &str = "review-approve";
+/// `review-reject`, on the review channel post.
+const REJECT: &str = "review-reject";
+/// Reaction approving a word, for moderators who'd rather not use buttons.
+const APPROVE_EMOJI: &str = "✅";
+
+pub fn is_review(custom_id: &str) -> bool {
+    custom_id.starts_with("review-")
+}
+
+/// Takes `msg` out of the story and asks the moderators in the guild's
+/// review channel to decide on it. Returns false, leaving `msg` alone, if
+/// the guild has no review channel.
+pub async fn quarantine(ctx: &Context, msg: &Message, config: &Config, reason: &str) -> bool {
+    let review_channel = match config.review_channel {
+        Some(channel_id) => channel_id,
+        None => return false,
+    };
+    actions::delete(ctx, msg.channel_id, msg.id).await;
+
+    let posted = review_channel
+        .send_message(&ctx.http, |m| {
+            m.content(format!(
+                "Review: {} from {} ({}) in <#{}>",
+                reason,
+                msg.author.tag(),
+                msg.author.id,
+                msg.channel_id
+            ))
+            .embed(|e| e.description(&msg.content))
+            .components(|c| {
+                c.create_action_row(|r| {
+                    r.create_button(|b| {
+                        b.custom_id(format!("{}:{}:{}", APPROVE, msg.channel_id, msg.author.id))
+                            .label("Approve")
+                            .style(ButtonStyle::Success)
+                    })
+                    .create_button(|b| {
+                        b.custom_id(REJECT)
+                            .label("Reject")
+                            .style(ButtonStyle::Danger)
+                    })
+                })
+            })
+        })
+        .await;
+    if let Err(why) = posted {
+        println!("Error posting word for review {:?}", why);
+    }
+    true
+}
+
+pub async fn handle(ctx: &Context, component: &MessageComponentInteraction) {
+    let parts: Vec<&str> = component.data.custom_id.split(':').collect();
+    let ids: Vec<u64> = parts[1..].iter().filter_map(|id| id.parse().ok()).collect();
+    match (parts[0], ids.as_slice()) {
+        (APPROVE, [channel_id, user_id]) => {
+            approve(ctx, component, ChannelId(*channel_id), UserId(*user_id)).await
+        }
+        // The word was deleted when it was queued.
+        (REJECT, []) => {
+            if is_moderator(ctx, component).await {
+                decide(ctx, component, "Rejected").await;
+            }
+        }
+        _ => println!("Unknown review button {}", component.data.custom_id),
+    }
+}
+
+/// Whether the words of `user_id` wait for a moderator's approval, as
+/// their first in the guild.
+pub fn needs_approval(user_id: UserId, config: &Config) -> bool {
+    if config.approval_contributions == 0 || config.review_channel.is_none() {
+        return false;
+    }
+    config
+        .approved_words
+        .get(&user_id)
+        .copied()
+        .unwrap_or_default()
+        < config.approval_contributions
+}
+
+/// Counts an accepted word of `user_id` towards those they need approved,
+/// until they have enough.
+pub async fn count_accepted(data: &RwLock<TypeMap>, guild_id: GuildId, user_id: UserId) {
+    let config = get_config(data, guild_id).await;
+    let limit = config.approval_contributions;
+    if config
+        .approved_words
+        .get(&user_id)
+        .copied()
+        .unwrap_or_default()
+        >= limit
+    {
+        return;
+    }
+    set_config(data, guild_id, |config| {
+        let count = config.approved_words.entry(user_id).or_default();
+        *count = (*count + 1).min(limit);
+    })
+    .await;
+}
+
+/// Counts the words already in the history towards those each user needs
+/// approved, as approval is turned on or raised.
+pub async fn count_history(data: &RwLock<TypeMap>, guild_id: GuildId) {
+    let mut counts: HashMap<UserId, usize> = HashMap::new();
+    for contribution in storage(data).await.contributions(guild_id, 0).await {
+        if let Some(user_id) = contribution.author_id {
+            *counts.entry(user_id).or_default() += 1;
+        }
+    }
+    set_config(data, guild_id, |config| {
+        let limit = config.approval_contributions;
+        for (user_id, count) in counts {
+            let approved = config.approved_words.entry(user_id).or_default();
+            *approved = (*approved).max(count.min(limit));
+        }
+    })
+    .await;
+}
+
+/// The story channel and author of the word a review post is about.
+fn reviewed(post: &Message) -> Option<(ChannelId, UserId)> {
+    let custom_id = post
+        .components
+        .iter()
+        .flat_map(|row| &row.components)
+        .find_map(|c| match c {
+            ActionRowComponent::Button(b) => b.custom_id.as_deref()?.strip_prefix(APPROVE),
+            _ => None,
+        })?;
+    let (channel_id, user_id) = custom_id.trim_start_matches(':').split_once(':')?;
+    Some((
+        ChannelId(channel_id.parse().ok()?),
+        UserId(user_id.parse().ok()?),
+    ))
+}
+
+/// Posts the reviewed `word` back in the story channel, in the name of its
+/// author.
+async fn post_word(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    user_id: UserId,
+    word: String,
+) -> bool {
+    let user = match user_id.to_user(&ctx.http).await {
+        Ok(user) => user,
+        Err(why) => {
+            println!("Error fetching reviewed user {:?}", why);
+            return false;
+        }
+    };
+    bridge::post_as(&ctx.http, &ctx.data, guild_id, channel_id, word, &user).await
+}
+
+async fn approve(
+    ctx: &Context,
+    component: &MessageComponentInteraction,
+    channel_id: ChannelId,
+    user_id: UserId,
+) {
+    if !is_moderator(ctx, component).await {
+        return;
+    }
+    let word = component
+        .message
+        .embeds
+        .first()
+        .and_then(|e| e.description.clone());
+    let (guild_id, word) = match (component.guild_id, word) {
+        (Some(guild_id), Some(word)) => (guild_id, word),
+        _ => return,
+    };
+    if post_word(ctx, guild_id, channel_id, user_id, word).await {
+        decide(ctx, component, "Approved").await;
+    }
+}
+
+/// Approves the word on a review post a moderator reacted to with
+/// [`APPROVE_EMOJI`], like the Approve button.
+pub async fn approve_by_reaction(ctx: &Context, reaction: &Reaction) {
+    let (guild_id, moderator) = match (reaction.guild_id, reaction.user_id) {
+        (Some(guild_id), Some(user_id)) => (guild_id, user_id),
+        _ => return,
+    };
+    if reaction.emoji.to_string() != APPROVE_EMOJI {
+        return;
+    }
+    let config = get_config(&ctx.data, guild_id).await;
+    if config.review_channel != Some(reaction.channel_id) {
+        return;
+    }
+    let mut post = match reaction.message(&ctx.http).await {
+        Ok(post) if post.author.id == ctx.cache.current_user_id() => post,
+        Ok(_) => return,
+        Err(why) => {
+            println!("Error fetching review post {:?}", why);
+            return;
+        }
+    };
+    let (channel_id, user_id) = match reviewed(&post) {
+        Some(reviewed) => reviewed,
+        // Decided already.
+        None => return,
+    };
+    let member = guild_id.member(&ctx.http, moderator).await.ok();
+    if crate::user_tier(ctx, guild_id, moderator, member.as_ref(), &config).await < Some(Tier::Mod)
+    {
+        return;
+    }
+    let word = match post.embeds.first().and_then(|e| e.description.clone()) {
+        Some(word) => word,
+        None => return,
+    };
+    if !post_word(ctx, guild_id, channel_id, user_id, word).await {
+        return;
+    }
+    let tag = member.map_or_else(|| moderator.to_string(), |m| m.user.tag());
+    let content = format!("{}\nApproved by {}", post.content, tag);
+    if let Err(why) = post
+        .edit(&ctx.http, |m| m.content(content).components(|c| c))
+        .await
+    {
+        println!("Error recording decision {:?}", why);
+    }
+}
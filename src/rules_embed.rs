@@ -0,0 +1,103 @@
+use serenity::builder::CreateEmbed;
+use serenity::http::Http;
+use serenity::model::prelude::*;
+
+use crate::config::{Config, MatchMode};
+use crate::i18n::{tr, tr_args};
+
+/// Localized names and values of the rules of `channel_id`.
+fn fields(config: &Config, channel_id: ChannelId) -> Vec<(String, String)> {
+    let locale = config.locale;
+    let rules = config.rules(channel_id);
+    let limit = |n: usize| match n {
+        0 => tr(locale, "rules-none"),
+        n => n.to_string(),
+    };
+    let seconds = |s: u64| match s {
+        0 => tr(locale, "rules-none"),
+        s => format!("{}s", s),
+    };
+    let mode = match rules.match_mode {
+        MatchMode::Substring => "substring",
+        MatchMode::WholeWord => "whole-word",
+        MatchMode::WordBoundary => "word-boundary",
+    };
+    let mut end = tr(locale, "rules-end-marker");
+    if let Some(emoji) = &config.end_reaction {
+        end.push('\n');
+        end.push_str(&tr_args(locale, "rules-end-reaction", &[("emoji", emoji)]));
+    }
+    vec![
+        (tr(locale, "rules-words"), rules.max_words.to_string()),
+        (tr(locale, "rules-per-user"), limit(rules.max_per_user)),
+        (
+            tr(locale, "rules-contributors"),
+            limit(rules.min_contributors),
+        ),
+        (tr(locale, "rules-cooldown"), seconds(rules.cooldown)),
+        (tr(locale, "rules-censor-mode"), mode.to_string()),
+        (tr(locale, "rules-end"), end),
+    ]
+}
+
+/// Fills in `e` with how to play in `channel_id`.
+pub fn embed<'a>(
+    e: &'a mut CreateEmbed,
+    config: &Config,
+    channel_id: ChannelId,
+) -> &'a mut CreateEmbed {
+    e.title(tr(config.locale, "rules-title"))
+        .description(tr(config.locale, "rules-intro"));
+    for (name, value) in fields(config, channel_id) {
+        e.field(name, value, true);
+    }
+    e
+}
+
+/// Posts the rules in `channel_id` and pins them, in place of the rules
+/// pinned there before. Returns the new message.
+pub async fn pin(http: &Http, config: &Config, channel_id: ChannelId) -> Option<MessageId> {
+    unpin(http, config, channel_id).await;
+    let posted = channel_id
+        .send_message(http, |m| m.embed(|e| embed(e, config, channel_id)))
+        .await;
+    match posted {
+        Ok(msg) => {
+            if let Err(why) = msg.pin(http).await {
+                println!("Failed to pin rules {:?}", why);
+            }
+            Some(msg.id)
+        }
+        Err(why) => {
+            println!("Error posting rules {:?}", why);
+            None
+        }
+    }
+}
+
+/// Deletes the rules pinned in `channel_id`, if any.
+pub async fn unpin(http: &Http, config: &Config, channel_id: ChannelId) {
+    if let Some(message_id) = config.rules_pins.get(&channel_id) {
+        if let Err(why) = channel_id.delete_message(http, message_id).await {
+            println!("Error deleting pinned rules {:?}", why);
+        }
+    }
+}
+
+/// Updates the pinned rules of the channels whose rules changed from
+/// `before` to `after`.
+pub async fn refresh(http: &Http, before: &Config, after: &Config) {
+    for (channel_id, message_id) in &after.rules_pins {
+        if fields(before, *channel_id) == fields(after, *channel_id) {
+            continue;
+        }
+        let res = channel_id
+            .edit_message(http, message_id, |m| {
+                m.embed(|e| embed(e, after, *channel_id))
+            })
+            .await;
+        if let Err(why) = res {
+            println!("Error updating pinned rules {:?}", why);
+        }
+    }
+}
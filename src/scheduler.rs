@@ -0,0 +1,103 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serenity::http::Http;
+use serenity::prelude::*;
+use serenity::CacheAndHttp;
+
+use crate::chart;
+use crate::config::{config_lock, set_config};
+use crate::digest;
+use crate::i18n::tr_args;
+use crate::nudge;
+use crate::prompt;
+
+const TICK: Duration = Duration::from_secs(10 * 60);
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Runs periodic jobs, like the daily prompt, until the process exits.
+pub async fn run(data: Arc<RwLock<TypeMap>>, cache_and_http: Arc<CacheAndHttp>) {
+    let http = &cache_and_http.http;
+    loop {
+        announce_events(&data, http).await;
+        daily_prompts(&data, http).await;
+        digest::post_due(&data, http).await;
+        nudge::post_due(&data, &cache_and_http.cache, http).await;
+        tokio::time::sleep(TICK).await;
+    }
+}
+
+/// Announces the start of an event in the story channel of its guild, once.
+async fn announce_events(data: &RwLock<TypeMap>, http: &Http) {
+    let changed: Vec<_> = {
+        let lock = config_lock(data).await;
+        let configs = lock.read().await;
+        configs
+            .guilds
+            .iter()
+            .map(|(id, c)| {
+                (
+                    *id,
+                    c,
+                    c.active_event(&c.month_day()).map(|e| e.name.clone()),
+                )
+            })
+            .filter(|(_, c, running)| c.announced_event != *running)
+            .map(|(id, c, running)| (id, c.clone(), running))
+            .collect()
+    };
+
+    for (guild_id, config, running) in changed {
+        if let (Some(name), true) = (&running, config.channel_id.0 != 0) {
+            let prompt = prompt::for_config(&config).await;
+            let announcement = format!(
+                "{}\n📝 {}",
+                tr_args(config.locale, "event-start", &[("event", name)]),
+                prompt
+            );
+            if let Err(why) = config.channel_id.say(http, announcement).await {
+                println!("Error announcing event {:?}", why);
+                continue;
+            }
+        }
+        set_config(data, guild_id, |config| config.announced_event = running).await;
+    }
+}
+
+/// Posts a new story prompt in every story channel that wants one and
+/// hasn't had one yet on the guild's current day.
+async fn daily_prompts(data: &RwLock<TypeMap>, http: &Http) {
+    let due: Vec<_> = {
+        let lock = config_lock(data).await;
+        let configs = lock.read().await;
+        configs
+            .guilds
+            .iter()
+            .filter(|(_, c)| c.daily_prompt && c.channel_id.0 != 0)
+            .filter(|(_, c)| chart::day(c.local(now())) != chart::day(c.local(c.last_prompt_at)))
+            .map(|(id, c)| (*id, c.clone()))
+            .collect()
+    };
+
+    for (guild_id, config) in due {
+        let prompt = prompt::for_config(&config).await;
+        if let Err(why) = config
+            .channel_id
+            .say(
+                http,
+                tr_args(config.locale, "daily-prompt", &[("prompt", &prompt)]),
+            )
+            .await
+        {
+            println!("Error posting daily prompt {:?}", why);
+            continue;
+        }
+        set_config(data, guild_id, |config| config.last_prompt_at = now()).await;
+    }
+}
@@ -0,0 +1,167 @@
+use serenity::model::application::component::{ActionRowComponent, InputTextStyle};
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::modal::ModalSubmitInteraction;
+use serenity::model::application::interaction::{InteractionResponseType, MessageFlags};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::audit;
+use crate::command::{parse_rule, Tier};
+use crate::config::{get_config, set_config, Rules};
+use crate::forum;
+use crate::i18n::tr;
+use crate::rules_embed;
+
+/// `settings:<channel id>`, or `settings` for the guild's own rules, on
+/// the modal opened with `/settings`.
+const SETTINGS: &str = "settings";
+
+/// The rules in the modal, by their `one-word set` key, and their labels.
+/// A modal holds up to five inputs.
+const FIELDS: [(&str, &str); 5] = [
+    ("max-words", "Most words in a message"),
+    ("max-per-user", "Most words per person in a story"),
+    ("min-contributors", "People needed before a story ends"),
+    ("cooldown", "Seconds between a person's words"),
+    ("compile-cooldown", "Seconds between compiled stories"),
+];
+
+pub fn is_settings(custom_id: &str) -> bool {
+    custom_id.starts_with(SETTINGS)
+}
+
+/// `rule` in `rules`, the way `one-word set` takes it.
+fn value(rules: &Rules, rule: &str) -> String {
+    let value = match rule {
+        "max-words" => rules.max_words,
+        "max-per-user" => rules.max_per_user,
+        "min-contributors" => rules.min_contributors,
+        "cooldown" => rules.cooldown as usize,
+        "compile-cooldown" => rules.compile_cooldown as usize,
+        _ => 0,
+    };
+    match (rule, value) {
+        ("cooldown" | "compile-cooldown", 0) => "off".to_string(),
+        (_, 0) => "default".to_string(),
+        (_, value) => value.to_string(),
+    }
+}
+
+async fn reply_to_modal(ctx: &Context, modal: &ModalSubmitInteraction, reply: String) {
+    let res = modal
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(reply).flags(MessageFlags::EPHEMERAL))
+        })
+        .await;
+    if let Err(why) = res {
+        println!("Error replying to settings {:?}", why);
+    }
+}
+
+/// Opens the modal editing the rules of the story channel `/settings` was
+/// used in, or those of the guild elsewhere.
+pub async fn open(ctx: &Context, interaction: &ApplicationCommandInteraction, guild_id: GuildId) {
+    let config = get_config(&ctx.data, guild_id).await;
+    let member = interaction.member.as_ref();
+    let tier = crate::user_tier(ctx, guild_id, interaction.user.id, member, &config).await;
+    let venue = forum::venue(&ctx.cache, guild_id, interaction.channel_id);
+    let (custom_id, rules) = match config.is_story_channel(venue) {
+        true => (format!("{}:{}", SETTINGS, venue), config.rules(venue)),
+        false => (SETTINGS.to_string(), config.rules(config.channel_id)),
+    };
+
+    let res = interaction
+        .create_interaction_response(&ctx.http, |r| {
+            if tier < Some(Tier::Admin) {
+                return r
+                    .kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|d| {
+                        d.content(tr(config.locale, Tier::Admin.denied_key()))
+                            .flags(MessageFlags::EPHEMERAL)
+                    });
+            }
+            r.kind(InteractionResponseType::Modal)
+                .interaction_response_data(|d| {
+                    d.custom_id(custom_id).title("Story rules").components(|c| {
+                        for (rule, label) in FIELDS {
+                            c.create_action_row(|row| {
+                                row.create_input_text(|t| {
+                                    t.custom_id(rule)
+                                        .label(label)
+                                        .style(InputTextStyle::Short)
+                                        .value(value(&rules, rule))
+                                        .required(true)
+                                })
+                            });
+                        }
+                        c
+                    })
+                })
+        })
+        .await;
+    if let Err(why) = res {
+        println!("Error opening settings {:?}", why);
+    }
+}
+
+/// Sets the rules submitted with the modal, all of them or, if any of
+/// them is invalid, none.
+pub async fn submit(ctx: &Context, modal: &ModalSubmitInteraction) {
+    let guild_id = match modal.guild_id {
+        Some(id) => id,
+        None => return,
+    };
+    let before = get_config(&ctx.data, guild_id).await;
+    let member = modal.member.as_ref();
+    if crate::user_tier(ctx, guild_id, modal.user.id, member, &before).await < Some(Tier::Admin) {
+        let reply = tr(before.locale, Tier::Admin.denied_key());
+        return reply_to_modal(ctx, modal, reply).await;
+    }
+    let channel = match modal.data.custom_id.split_once(':') {
+        Some((_, id)) => match id.parse() {
+            Ok(id) => Some(ChannelId(id)),
+            Err(_) => {
+                println!("Unknown settings modal {}", modal.data.custom_id);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let inputs = modal
+        .data
+        .components
+        .iter()
+        .flat_map(|row| &row.components)
+        .filter_map(|component| match component {
+            ActionRowComponent::InputText(input) => Some(input),
+            _ => None,
+        });
+    let mut rules = Vec::new();
+    for input in inputs {
+        match parse_rule(&input.custom_id, input.value.trim()) {
+            Ok(rule) => rules.push(rule),
+            Err(error) => return reply_to_modal(ctx, modal, error.to_string()).await,
+        }
+    }
+    set_config(&ctx.data, guild_id, |config| {
+        for rule in rules {
+            config.set_rule(channel, rule);
+        }
+    })
+    .await;
+
+    let after = get_config(&ctx.data, guild_id).await;
+    audit::record(
+        &ctx.data,
+        guild_id,
+        &modal.user.tag(),
+        Some(modal.user.id),
+        &before,
+        &after,
+    )
+    .await;
+    rules_embed::refresh(&ctx.http, &before, &after).await;
+    reply_to_modal(ctx, modal, tr(after.locale, "settings-updated")).await;
+}
@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serenity::builder::CreateComponents;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::{InteractionResponseType, MessageFlags};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::audit;
+use crate::config::{get_config, set_config, MatchMode};
+use crate::i18n::{tr, tr_args, Locale};
+use crate::rules_embed;
+
+/// Prefix of the custom ids of the wizard's components.
+const SETUP: &str = "setup:";
+const CHANNEL: &str = "setup:channel";
+const MATCH_MODE: &str = "setup:match-mode";
+const PROFANITY: &str = "setup:profanity";
+const PINS: &str = "setup:pins";
+const SAVE: &str = "setup:save";
+const CANCEL: &str = "setup:cancel";
+
+/// What the bot needs to do in a story channel. Pinning takes Manage
+/// Messages, like deleting does.
+const NEEDED_PERMISSIONS: [(Permissions, &str); 5] = [
+    (Permissions::VIEW_CHANNEL, "View Channel"),
+    (Permissions::SEND_MESSAGES, "Send Messages"),
+    (Permissions::MANAGE_MESSAGES, "Manage Messages"),
+    (Permissions::EMBED_LINKS, "Embed Links"),
+    (Permissions::READ_MESSAGE_HISTORY, "Read Message History"),
+];
+
+/// Most options a select menu can have.
+const MAX_OPTIONS: usize = 25;
+
+const MATCH_MODES: [(MatchMode, &str, &str); 3] = [
+    (
+        MatchMode::Substring,
+        "substring",
+        "Banned words anywhere, also when disguised",
+    ),
+    (
+        MatchMode::WholeWord,
+        "whole-word",
+        "Banned words as whole words only",
+    ),
+    (
+        MatchMode::WordBoundary,
+        "word-boundary",
+        "Banned words not inside other words",
+    ),
+];
+
+/// What was picked so far in a setup wizard. Nothing is saved before the
+/// admin presses save.
+#[derive(Debug, Clone)]
+pub struct Setup {
+    /// Who started the wizard. Nobody else can use it.
+    user: UserId,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    match_mode: MatchMode,
+    profanity_filter: bool,
+    pins: bool,
+}
+
+/// Open setup wizards, by the message holding them.
+pub struct SetupContainer;
+impl TypeMapKey for SetupContainer {
+    type Value = Arc<Mutex<HashMap<MessageId, Setup>>>;
+}
+
+async fn wizards(data: &RwLock<TypeMap>) -> Arc<Mutex<HashMap<MessageId, Setup>>> {
+    let data = data.read().await;
+    data.get::<SetupContainer>()
+        .expect("Expected SetupContainer in TypeMap")
+        .clone()
+}
+
+pub fn is_setup(custom_id: &str) -> bool {
+    custom_id.starts_with(SETUP)
+}
+
+/// Text channels of the guild, in the order Discord shows them. The picked
+/// channel is always among them, even in guilds with many channels.
+fn channels(ctx: &Context, guild_id: GuildId, picked: ChannelId) -> Vec<(ChannelId, String)> {
+    let mut channels: Vec<GuildChannel> = ctx
+        .cache
+        .guild_channels(guild_id)
+        .map(|channels| channels.into_iter().map(|(_, c)| c).collect())
+        .unwrap_or_default();
+    channels.retain(|c| c.kind == ChannelType::Text);
+    channels.sort_by_key(|c| (c.position, c.id));
+    if let Some(i) = channels.iter().position(|c| c.id == picked) {
+        if i >= MAX_OPTIONS {
+            let c = channels.remove(i);
+            channels.insert(0, c);
+        }
+    }
+    channels.truncate(MAX_OPTIONS);
+    channels.into_iter().map(|c| (c.id, c.name)).collect()
+}
+
+/// Adds the wizard's menus and buttons, showing the choices in `setup`.
+fn components<'a>(
+    components: &'a mut CreateComponents,
+    setup: &Setup,
+    channels: &[(ChannelId, String)],
+) -> &'a mut CreateComponents {
+    let toggle = |on: bool| match on {
+        true => ButtonStyle::Success,
+        false => ButtonStyle::Secondary,
+    };
+    components
+        .create_action_row(|r| {
+            r.create_select_menu(|m| {
+                m.custom_id(CHANNEL)
+                    .placeholder("Story channel")
+                    .options(|o| {
+                        for (id, name) in channels {
+                            o.create_option(|o| {
+                                o.label(format!("#{}", name))
+                                    .value(id)
+                                    .default_selection(*id == setup.channel_id)
+                            });
+                        }
+                        o
+                    })
+            })
+        })
+        .create_action_row(|r| {
+            r.create_select_menu(|m| {
+                m.custom_id(MATCH_MODE).options(|o| {
+                    for (mode, value, label) in MATCH_MODES {
+                        o.create_option(|o| {
+                            o.label(label)
+                                .value(value)
+                                .default_selection(mode == setup.match_mode)
+                        });
+                    }
+                    o
+                })
+            })
+        })
+        .create_action_row(|r| {
+            r.create_button(|b| {
+                b.custom_id(PROFANITY)
+                    .label("Profanity filter")
+                    .style(toggle(setup.profanity_filter))
+            })
+            .create_button(|b| {
+                b.custom_id(PINS)
+                    .label("Pin stories")
+                    .style(toggle(setup.pins))
+            })
+        })
+        .create_action_row(|r| {
+            r.create_button(|b| b.custom_id(SAVE).label("Save").style(ButtonStyle::Primary))
+                .create_button(|b| {
+                    b.custom_id(CANCEL)
+                        .label("Cancel")
+                        .style(ButtonStyle::Danger)
+                })
+        })
+}
+
+/// Posts a setup wizard for the author of `msg`, starting from the guild's
+/// current settings.
+pub async fn start(ctx: &Context, msg: &Message, guild_id: GuildId) {
+    let config = get_config(&ctx.data, guild_id).await;
+    let setup = Setup {
+        user: msg.author.id,
+        guild_id,
+        channel_id: match config.channel_id.0 {
+            0 => msg.channel_id,
+            _ => config.channel_id,
+        },
+        match_mode: config.match_mode,
+        profanity_filter: config.profanity_filter,
+        pins: !config.skip_pins,
+    };
+    let channels = channels(ctx, guild_id, setup.channel_id);
+
+    let posted = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.reference_message(msg)
+                .content(tr(config.locale, "setup-intro"))
+                .components(|c| components(c, &setup, &channels))
+        })
+        .await;
+    match posted {
+        Ok(posted) => {
+            let wizards = wizards(&ctx.data).await;
+            let mut wizards = wizards.lock().await;
+            // One wizard per admin and guild is plenty; forget abandoned ones.
+            wizards.retain(|_, s| s.user != setup.user || s.guild_id != guild_id);
+            wizards.insert(posted.id, setup);
+        }
+        Err(why) => println!("Error posting setup wizard {:?}", why),
+    }
+}
+
+pub async fn handle(ctx: &Context, component: &MessageComponentInteraction) {
+    let guild_id = match component.guild_id {
+        Some(id) => id,
+        None => return,
+    };
+    let locale = get_config(&ctx.data, guild_id).await.locale;
+    let id = component.message.id;
+    let wizards = wizards(&ctx.data).await;
+    let setup = {
+        let mut wizards = wizards.lock().await;
+        let setup = match wizards.get_mut(&id) {
+            Some(setup) if setup.user == component.user.id => setup,
+            Some(_) => return reply(ctx, component, tr(locale, "setup-not-yours")).await,
+            None => return reply(ctx, component, tr(locale, "setup-expired")).await,
+        };
+        let value = component.data.values.first();
+        match component.data.custom_id.as_str() {
+            CHANNEL => {
+                if let Some(channel_id) = value.and_then(|v| v.parse().ok()) {
+                    setup.channel_id = ChannelId(channel_id);
+                }
+            }
+            MATCH_MODE => {
+                if let Some((mode, _, _)) = MATCH_MODES
+                    .iter()
+                    .find(|m| Some(m.1) == value.map(String::as_str))
+                {
+                    setup.match_mode = *mode;
+                }
+            }
+            PROFANITY => setup.profanity_filter = !setup.profanity_filter,
+            PINS => setup.pins = !setup.pins,
+            SAVE | CANCEL => {}
+            _ => println!("Unknown setup component {}", component.data.custom_id),
+        }
+        let setup = setup.clone();
+        if let SAVE | CANCEL = component.data.custom_id.as_str() {
+            wizards.remove(&id);
+        }
+        setup
+    };
+
+    let content = match component.data.custom_id.as_str() {
+        SAVE => Some(save(ctx, component, &setup).await),
+        CANCEL => Some(tr(locale, "setup-cancelled")),
+        _ => None,
+    };
+    let channels = channels(ctx, guild_id, setup.channel_id);
+    let res = component
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| match content {
+                    Some(content) => d.content(content).components(|c| c),
+                    None => d.components(|c| components(c, &setup, &channels)),
+                })
+        })
+        .await;
+    if let Err(why) = res {
+        println!("Error updating setup wizard {:?}", why);
+    }
+}
+
+/// Names of the permissions the bot lacks in `channel_id`, or `None` if
+/// they can't be told.
+pub async fn missing_permissions(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> Option<Vec<&'static str>> {
+    let member = guild_id
+        .member(ctx, ctx.cache.current_user_id())
+        .await
+        .ok()?;
+    let channel = ctx.cache.guild_channel(channel_id)?;
+    let perms = ctx
+        .cache
+        .guild_field(guild_id, |g| g.user_permissions_in(&channel, &member).ok())
+        .flatten()?;
+    let missing = NEEDED_PERMISSIONS
+        .iter()
+        .filter(|(needed, _)| !perms.contains(*needed))
+        .map(|(_, name)| *name)
+        .collect();
+    Some(missing)
+}
+
+/// `confirmation`, followed by the permissions the bot lacks in
+/// `channel_id`, if any.
+pub async fn with_permission_check(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    locale: Locale,
+    confirmation: String,
+) -> String {
+    match missing_permissions(ctx, guild_id, channel_id).await {
+        Some(missing) if !missing.is_empty() => {
+            let channel = channel_id.mention().to_string();
+            let missing = missing.join(", ");
+            let warning = tr_args(
+                locale,
+                "missing-permissions",
+                &[("channel", &channel), ("permissions", &missing)],
+            );
+            format!("{}\n{}", confirmation, warning)
+        }
+        _ => confirmation,
+    }
+}
+
+/// Writes the choices to the guild's config in one go. Returns the
+/// confirmation to show in place of the wizard.
+async fn save(ctx: &Context, component: &MessageComponentInteraction, setup: &Setup) -> String {
+    let before = get_config(&ctx.data, setup.guild_id).await;
+    set_config(&ctx.data, setup.guild_id, |config| {
+        config.channel_id = setup.channel_id;
+        config.match_mode = setup.match_mode;
+        config.profanity_filter = setup.profanity_filter;
+        config.skip_pins = !setup.pins;
+    })
+    .await;
+    let after = get_config(&ctx.data, setup.guild_id).await;
+    audit::record(
+        &ctx.data,
+        setup.guild_id,
+        &component.user.tag(),
+        Some(component.user.id),
+        &before,
+        &after,
+    )
+    .await;
+    rules_embed::refresh(&ctx.http, &before, &after).await;
+    let confirmation = tr(after.locale, "settings-updated");
+    with_permission_check(
+        ctx,
+        setup.guild_id,
+        setup.channel_id,
+        after.locale,
+        confirmation,
+    )
+    .await
+}
+
+/// Answers only the user pressing a wizard component, leaving the wizard
+/// as it is.
+async fn reply(ctx: &Context, component: &MessageComponentInteraction, content: String) {
+    let res = component
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(content).flags(MessageFlags::EPHEMERAL))
+        })
+        .await;
+    if let Err(why) = res {
+        println!("Error replying to setup wizard {:?}", why);
+    }
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::actions;
+use crate::audit;
+use crate::config::{config_lock, get_config, set_config, Config};
+use crate::scheduler::now;
+
+/// How long the messages of shadow muted users stay up, so deleting them
+/// doesn't give the mute away.
+const DELAY: Duration = Duration::from_secs(10);
+
+pub fn is_muted(config: &Config, user_id: UserId) -> bool {
+    config
+        .shadow_muted
+        .get(&user_id)
+        .is_some_and(|until| *until > now())
+}
+
+/// Deletes `msg` of a shadow muted user after a while, without a word.
+pub fn hide(ctx: &Context, msg: &Message) {
+    let (ctx, channel_id, id) = (ctx.clone(), msg.channel_id, msg.id);
+    tokio::spawn(async move {
+        tokio::time::sleep(DELAY).await;
+        actions::delete(&ctx, channel_id, id).await;
+    });
+}
+
+/// Lifts the shadow mutes that ran out, noting it in the audit log.
+pub async fn lift_expired(data: &RwLock<TypeMap>) {
+    let guilds: Vec<GuildId> = {
+        let lock = config_lock(data).await;
+        let configs = lock.read().await;
+        configs
+            .guilds
+            .iter()
+            .filter(|(_, c)| c.shadow_muted.values().any(|until| *until <= now()))
+            .map(|(id, _)| *id)
+            .collect()
+    };
+    for guild_id in guilds {
+        let before = get_config(data, guild_id).await;
+        set_config(data, guild_id, |config| {
+            config.shadow_muted.retain(|_, until| *until > now());
+        })
+        .await;
+        let after = get_config(data, guild_id).await;
+        audit::record(data, guild_id, "shadow mute expiry", None, &before, &after).await;
+    }
+}
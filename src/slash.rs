@@ -0,0 +1,270 @@
+use serenity::model::application::command::{
+    Command as SlashCommand, CommandOptionType, CommandType,
+};
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::autocomplete::AutocompleteInteraction;
+use serenity::model::application::interaction::{InteractionResponseType, MessageFlags};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::audit;
+use crate::command::{Command, Tier};
+use crate::config::{get_config, set_config, Config};
+use crate::i18n::{tr, tr_args};
+use crate::{cooldown, forum, generate_story, rules_embed, scheduler, settings, StoryEnd};
+
+/// `/unban <word>`, suggesting the banned words and phrases.
+const UNBAN: &str = "unban";
+/// `/admin-remove <user>`, suggesting the users on the admin allowlist.
+const ADMIN_REMOVE: &str = "admin-remove";
+/// `/settings`, opening a modal with the rules of the channel.
+const SETTINGS: &str = "settings";
+/// Message context menu compiling the story up to the message.
+const COMPILE: &str = "Compile story up to here";
+/// Discord shows no more suggestions than this.
+const MAX_CHOICES: usize = 25;
+/// Longest name or value of a suggestion Discord accepts.
+const MAX_CHOICE_LEN: usize = 100;
+
+/// Registers the slash commands with Discord, replacing any old ones.
+pub async fn register(ctx: &Context) {
+    let res = SlashCommand::set_global_application_commands(&ctx.http, |commands| {
+        commands
+            .create_application_command(|c| {
+                c.name(UNBAN)
+                    .description("Allow a banned word or phrase again")
+                    .dm_permission(false)
+                    .create_option(|o| {
+                        o.name("word")
+                            .description("The banned word or phrase")
+                            .kind(CommandOptionType::String)
+                            .required(true)
+                            .set_autocomplete(true)
+                    })
+            })
+            .create_application_command(|c| {
+                c.name(ADMIN_REMOVE)
+                    .description("Stop allowing a user to change settings")
+                    .dm_permission(false)
+                    .create_option(|o| {
+                        o.name("user")
+                            .description("The user on the admin allowlist")
+                            .kind(CommandOptionType::String)
+                            .required(true)
+                            .set_autocomplete(true)
+                    })
+            })
+            .create_application_command(|c| {
+                c.name(SETTINGS)
+                    .description("Edit the story rules of this channel")
+                    .dm_permission(false)
+            })
+            .create_application_command(|c| {
+                c.name(COMPILE)
+                    .kind(CommandType::Message)
+                    .dm_permission(false)
+            })
+    })
+    .await;
+    if let Err(why) = res {
+        println!("Error registering slash commands {:?}", why);
+    }
+}
+
+/// The command the options of a slash command stand for.
+fn command(interaction: &ApplicationCommandInteraction) -> Option<Command> {
+    let value = interaction.data.options.first()?.value.as_ref()?.as_str()?;
+    match interaction.data.name.as_str() {
+        UNBAN => Some(Command::UnbanWord(value.to_string())),
+        ADMIN_REMOVE => value
+            .parse()
+            .ok()
+            .map(|id| Command::RemoveAdmin(UserId(id))),
+        _ => None,
+    }
+}
+
+/// Whether `user` may run `cmd`, going by the same tiers as text commands.
+async fn is_allowed(
+    ctx: &Context,
+    guild_id: GuildId,
+    user: UserId,
+    member: Option<&Member>,
+    config: &Config,
+    cmd: &Command,
+) -> bool {
+    cmd.tier() <= crate::user_tier(ctx, guild_id, user, member, config).await
+}
+
+pub async fn run(ctx: &Context, interaction: &ApplicationCommandInteraction) {
+    let guild_id = match interaction.guild_id {
+        Some(id) => id,
+        None => return,
+    };
+    match interaction.data.name.as_str() {
+        COMPILE => return compile(ctx, interaction, guild_id).await,
+        SETTINGS => return settings::open(ctx, interaction, guild_id).await,
+        _ => {}
+    }
+    let cmd = match command(interaction) {
+        Some(cmd) => cmd,
+        None => {
+            println!("Unknown slash command {}", interaction.data.name);
+            return;
+        }
+    };
+
+    let before = get_config(&ctx.data, guild_id).await;
+    let member = interaction.member.as_ref();
+    let reply = if !is_allowed(ctx, guild_id, interaction.user.id, member, &before, &cmd).await {
+        // Only commands with a tier get this far.
+        tr(before.locale, cmd.tier().unwrap().denied_key())
+    } else {
+        match cmd {
+            Command::UnbanWord(word) => {
+                set_config(&ctx.data, guild_id, |config| config.unban(&word)).await;
+            }
+            Command::RemoveAdmin(user) => {
+                set_config(&ctx.data, guild_id, |config| {
+                    config.admins.remove(&user);
+                })
+                .await;
+            }
+            _ => {}
+        }
+        let after = get_config(&ctx.data, guild_id).await;
+        audit::record(
+            &ctx.data,
+            guild_id,
+            &interaction.user.tag(),
+            Some(interaction.user.id),
+            &before,
+            &after,
+        )
+        .await;
+        rules_embed::refresh(&ctx.http, &before, &after).await;
+        tr(after.locale, "settings-updated")
+    };
+
+    let res = interaction
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(reply).flags(MessageFlags::EPHEMERAL))
+        })
+        .await;
+    if let Err(why) = res {
+        println!("Error replying to slash command {:?}", why);
+    }
+}
+
+/// Compiles the story in the channel up to the message the context menu
+/// was opened on, for when the end marker wasn't posted in time. Only
+/// moderators may, as it cuts the story short.
+async fn compile(ctx: &Context, interaction: &ApplicationCommandInteraction, guild_id: GuildId) {
+    let config = get_config(&ctx.data, guild_id).await;
+    let channel_id = interaction.channel_id;
+    let venue = forum::venue(&ctx.cache, guild_id, channel_id);
+    let member = interaction.member.as_ref();
+    let tier = crate::user_tier(ctx, guild_id, interaction.user.id, member, &config).await;
+    let last = interaction.data.resolved.messages.values().next().cloned();
+    let seconds = config.rules(venue).compile_cooldown;
+    let wait = cooldown::compile_wait(&ctx.data, channel_id, seconds).await;
+    let allowed = tier >= Some(Tier::Mod);
+    let story_channel = config.is_story_channel(venue);
+    let reply = match (allowed, story_channel, wait) {
+        (false, _, _) => tr(config.locale, Tier::Mod.denied_key()),
+        (true, false, _) => tr(config.locale, "not-story-channel"),
+        (true, true, Some(wait)) => {
+            let deadline = format!("<t:{}:R>", scheduler::now() + wait.as_secs() + 1);
+            tr_args(
+                config.locale,
+                "compile-cooldown",
+                &[("deadline", &deadline)],
+            )
+        }
+        (true, true, None) => tr(config.locale, "compile-started"),
+    };
+
+    let res = interaction
+        .create_interaction_response(&ctx.http, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(reply).flags(MessageFlags::EPHEMERAL))
+        })
+        .await;
+    if let Err(why) = res {
+        println!("Error replying to compile command {:?}", why);
+    }
+
+    if let (true, true, None, Some(last)) = (allowed, story_channel, wait, last) {
+        let end = StoryEnd::Through(Box::new(last), interaction.user.name.clone());
+        generate_story(&ctx.http, &ctx.cache, &ctx.data, channel_id, end, guild_id).await;
+    }
+}
+
+/// Suggests the entries the command can remove that contain what was typed
+/// so far. The lists are only shown to those who may change them.
+pub async fn autocomplete(ctx: &Context, interaction: &AutocompleteInteraction) {
+    let guild_id = match interaction.guild_id {
+        Some(id) => id,
+        None => return,
+    };
+    let config = get_config(&ctx.data, guild_id).await;
+    let typed = interaction
+        .data
+        .options
+        .iter()
+        .find(|o| o.focused)
+        .and_then(|o| o.value.as_ref())
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let (cmd, entries): (Command, Vec<(String, String)>) = match interaction.data.name.as_str() {
+        UNBAN => (
+            Command::UnbanWord(String::new()),
+            config
+                .banned_words
+                .iter()
+                .chain(&config.banned_phrases)
+                .map(|word| (word.clone(), word.clone()))
+                .collect(),
+        ),
+        ADMIN_REMOVE => (
+            Command::RemoveAdmin(UserId(0)),
+            config
+                .admins
+                .iter()
+                .map(|id| {
+                    let name = ctx.cache.user(*id).map_or(id.to_string(), |u| u.tag());
+                    (name, id.to_string())
+                })
+                .collect(),
+        ),
+        _ => return,
+    };
+    let member = interaction.member.as_ref();
+    let mut choices =
+        match is_allowed(ctx, guild_id, interaction.user.id, member, &config, &cmd).await {
+            true => entries,
+            false => Vec::new(),
+        };
+    choices.retain(|(name, value)| {
+        name.to_lowercase().contains(&typed)
+            && name.len() <= MAX_CHOICE_LEN
+            && value.len() <= MAX_CHOICE_LEN
+    });
+    choices.sort();
+    choices.truncate(MAX_CHOICES);
+
+    let res = interaction
+        .create_autocomplete_response(&ctx.http, |r| {
+            for (name, value) in &choices {
+                r.add_string_choice(name, value);
+            }
+            r
+        })
+        .await;
+    if let Err(why) = res {
+        println!("Error suggesting slash command options {:?}", why);
+    }
+}
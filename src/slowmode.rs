@@ -0,0 +1,54 @@
+use std::collections::BTreeSet;
+
+use serenity::http::Http;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::config::Config;
+use crate::sprint;
+
+/// Longest slowmode Discord allows, in seconds.
+const MAX_SLOWMODE: u64 = 6 * 60 * 60;
+
+/// The slowmode the story channel `channel_id` should have, if the guild
+/// lets Discord enforce its cooldown.
+fn wanted(config: &Config, channel_id: ChannelId) -> Option<u64> {
+    (config.native_slowmode && config.is_story_channel(channel_id))
+        .then(|| config.rules(channel_id).cooldown.min(MAX_SLOWMODE))
+}
+
+/// Sets the slowmode of `channel_id`.
+pub async fn set(http: &Http, channel_id: ChannelId, seconds: u64) {
+    if let Err(why) = channel_id
+        .edit(http, |c| c.rate_limit_per_user(seconds))
+        .await
+    {
+        println!("Error setting slowmode {:?}", why);
+    }
+}
+
+/// Sets the slowmode of `channel_id` to its cooldown again, after a
+/// sprint.
+pub async fn restore(http: &Http, config: &Config, channel_id: ChannelId) {
+    if let Some(seconds) = wanted(config, channel_id) {
+        set(http, channel_id, seconds).await;
+    }
+}
+
+/// Matches the slowmode of the story channels to their cooldown after
+/// settings changed from `before` to `after`. Slowmode is lifted where the
+/// guild stopped using it, and left alone during sprints.
+pub async fn refresh(data: &RwLock<TypeMap>, http: &Http, before: &Config, after: &Config) {
+    let channels = [before, after]
+        .into_iter()
+        .flat_map(|c| std::iter::once(c.channel_id).chain(c.channels.keys().copied()))
+        .filter(|id| id.0 != 0)
+        .collect::<BTreeSet<_>>();
+    for channel_id in channels {
+        let (had, wants) = (wanted(before, channel_id), wanted(after, channel_id));
+        if had == wants || sprint::is_running(data, channel_id).await {
+            continue;
+        }
+        set(http, channel_id, wants.unwrap_or(0)).await;
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::cache::Cache;
+use serenity::http::Http;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tokio::time::Instant;
+
+use crate::chart;
+use crate::config::get_config;
+use crate::forum;
+use crate::i18n::{tr, tr_args};
+use crate::scheduler::now;
+use crate::slowmode;
+use crate::storage::storage;
+
+/// Minutes left at which a running sprint says how long it has to go.
+const REMINDERS: [u64; 5] = [60, 30, 10, 5, 1];
+
+/// Channels a sprint is running in.
+pub struct SprintContainer;
+impl TypeMapKey for SprintContainer {
+    type Value = Arc<Mutex<HashSet<ChannelId>>>;
+}
+
+async fn sprints(data: &RwLock<TypeMap>) -> Arc<Mutex<HashSet<ChannelId>>> {
+    let data = data.read().await;
+    data.get::<SprintContainer>()
+        .expect("Expected SprintContainer in TypeMap")
+        .clone()
+}
+
+/// Whether a sprint is running in `channel_id`. Authors don't cool down
+/// during sprints.
+pub async fn is_running(data: &RwLock<TypeMap>, channel_id: ChannelId) -> bool {
+    sprints(data).await.lock().await.contains(&channel_id)
+}
+
+/// Starts a sprint of `seconds` in the story channel `msg` was sent in, or
+/// the guild's main one.
+pub async fn start(ctx: &Context, msg: &Message, guild_id: GuildId, seconds: u64) {
+    let config = get_config(&ctx.data, guild_id).await;
+    let venue = forum::venue(&ctx.cache, guild_id, msg.channel_id);
+    let channel_id = match config.is_story_channel(venue) {
+        true => msg.channel_id,
+        false => config.channel_id,
+    };
+
+    let started = sprints(&ctx.data).await.lock().await.insert(channel_id);
+    if !started {
+        if let Err(why) = msg
+            .reply(&ctx.http, tr(config.locale, "sprint-running"))
+            .await
+        {
+            println!("Error replying: {:?}", why);
+        }
+        return;
+    }
+
+    if config.native_slowmode {
+        slowmode::set(&ctx.http, channel_id, 0).await;
+    }
+    let minutes = (seconds / 60).to_string();
+    let announcement = tr_args(config.locale, "sprint-start", &[("minutes", &minutes)]);
+    if let Err(why) = channel_id.say(&ctx.http, announcement).await {
+        println!("Error announcing sprint {:?}", why);
+    }
+    tokio::spawn(run(
+        ctx.http.clone(),
+        ctx.cache.clone(),
+        ctx.data.clone(),
+        channel_id,
+        guild_id,
+        seconds,
+    ));
+}
+
+/// Counts the sprint down, then compiles the story and shows what the
+/// sprint added.
+async fn run(
+    http: Arc<Http>,
+    cache: Arc<Cache>,
+    data: Arc<RwLock<TypeMap>>,
+    channel_id: ChannelId,
+    guild_id: GuildId,
+    seconds: u64,
+) {
+    let started_at = now();
+    let ends = Instant::now() + Duration::from_secs(seconds);
+    for minutes in REMINDERS.iter().filter(|m| **m * 60 < seconds) {
+        tokio::time::sleep_until(ends - Duration::from_secs(minutes * 60)).await;
+        let locale = get_config(&data, guild_id).await.locale;
+        let reminder = tr_args(
+            locale,
+            "sprint-remaining",
+            &[("minutes", &minutes.to_string())],
+        );
+        if let Err(why) = channel_id.say(&http, reminder).await {
+            println!("Error posting sprint reminder {:?}", why);
+        }
+    }
+    tokio::time::sleep_until(ends).await;
+    sprints(&data).await.lock().await.remove(&channel_id);
+    slowmode::restore(&http, &get_config(&data, guild_id).await, channel_id).await;
+
+    // Ends the story like a contributor would.
+    match channel_id.say(&http, ".").await {
+        Ok(end) => {
+            let end = crate::StoryEnd::Marker(end.id);
+            crate::generate_story(&http, &cache, &data, channel_id, end, guild_id).await
+        }
+        Err(why) => println!("Error ending sprint story {:?}", why),
+    }
+
+    let locale = get_config(&data, guild_id).await.locale;
+    let added: Vec<_> = storage(&data)
+        .await
+        .contributions(guild_id, started_at)
+        .await
+        .into_iter()
+        .filter(|c| c.channel_id == channel_id)
+        .collect();
+    let summary = match chart::tally(added.iter().map(|c| c.author.as_str())).first() {
+        Some((top, count)) => tr_args(
+            locale,
+            "sprint-over",
+            &[
+                ("words", &added.len().to_string()),
+                ("top", top),
+                ("top_words", &count.to_string()),
+            ],
+        ),
+        None => tr(locale, "sprint-over-empty"),
+    };
+    if let Err(why) = channel_id.say(&http, summary).await {
+        println!("Error announcing sprint results {:?}", why);
+    }
+}
@@ -0,0 +1,646 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use serenity::async_trait;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::config::{Config, Configs, Rules};
+use crate::game::phrase_words;
+use crate::scheduler::now;
+
+/// An accepted word, as kept in the history.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ContributionRecord {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub author: String,
+    /// The Discord user, also for words posted in their name.
+    pub author_id: Option<UserId>,
+    pub word: String,
+    /// Unix time it was accepted.
+    pub at: u64,
+}
+
+/// A compiled story, as kept in the history.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct StoryRecord {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    /// The first message the story was posted in.
+    pub message_id: Option<MessageId>,
+    /// Counts the guild's stories from 1. Stories archived before it was
+    /// kept are numbered by their place in the history.
+    pub number: Option<u64>,
+    pub title: String,
+    pub text: String,
+    pub contributors: Vec<String>,
+    /// Unix time it was compiled.
+    pub at: u64,
+    /// Unix time of the first word. Stories archived before it was kept
+    /// have none of the fields below.
+    pub started_at: Option<u64>,
+    /// Unix time of the end marker.
+    pub ended_at: Option<u64>,
+    /// Who added the first word.
+    pub starter: Option<String>,
+    /// Who posted the end marker.
+    pub ender: Option<String>,
+    /// The channel's rules when the story was compiled.
+    pub rules: Option<Rules>,
+    /// Earlier versions of the text, oldest first, if mods edited it.
+    #[serde(default)]
+    pub revisions: Vec<Revision>,
+}
+
+/// The text of an archived story before an edit.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct Revision {
+    pub text: String,
+    /// Who made the edit replacing it.
+    pub editor: String,
+    /// Unix time of the edit.
+    pub at: u64,
+}
+
+/// An edit to the words of an archived story, made with
+/// `one-word archive edit`. Words match regardless of case.
+#[derive(Debug, Clone)]
+pub enum StoryEdit {
+    Remove(String),
+    Replace(String, String),
+}
+
+/// A challenge word worked into a story.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ChallengeRecord {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub word: String,
+    pub author: String,
+    pub author_id: Option<UserId>,
+    /// Unix time it was completed.
+    pub at: u64,
+}
+
+/// A setting changed by an admin.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct AuditRecord {
+    pub guild_id: GuildId,
+    pub user: String,
+    pub user_id: Option<UserId>,
+    pub field: String,
+    pub old: String,
+    pub new: String,
+    /// Unix time of the change.
+    pub at: u64,
+}
+
+/// Stats restored from a backup. They stand in for the guild's records
+/// from before.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct StatsRecord {
+    pub guild_id: GuildId,
+    pub stats: Stats,
+    /// Unix time they were restored.
+    pub at: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Record {
+    Contribution(ContributionRecord),
+    Story(StoryRecord),
+    Audit(AuditRecord),
+    Challenge(ChallengeRecord),
+    Stats(StatsRecord),
+}
+
+/// What compacting the history did.
+#[derive(Debug, Default)]
+pub struct Compaction {
+    /// Records kept.
+    pub records: usize,
+    /// Lines dropped, like those left half written by a crash.
+    pub dropped: usize,
+    /// Size of the history afterwards, in bytes.
+    pub bytes: u64,
+}
+
+/// Kinds of records that can be pruned from the history. Compiled stories
+/// are always kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryKind {
+    Contributions,
+    Audit,
+}
+
+impl StoryRecord {
+    /// Whether `id` is the story's number or the ID of its message. Message
+    /// IDs are far too large to be story numbers.
+    pub fn is(&self, id: u64) -> bool {
+        self.number == Some(id) || self.message_id == Some(MessageId(id))
+    }
+
+    /// Makes `edit` by `editor`, keeping the text before as a revision.
+    /// Returns false if no word matched.
+    pub fn edit(&mut self, edit: &StoryEdit, editor: &str) -> bool {
+        let matches = |word: &str, target: &str| word.to_lowercase() == target.to_lowercase();
+        let words: Vec<&str> = self
+            .text
+            .split_whitespace()
+            .filter_map(|word| match edit {
+                StoryEdit::Remove(target) if matches(word, target) => None,
+                StoryEdit::Replace(target, new) if matches(word, target) => Some(new.as_str()),
+                _ => Some(word),
+            })
+            .collect();
+        let text = words.join(" ");
+        if text == self.text.split_whitespace().collect::<Vec<_>>().join(" ") {
+            return false;
+        }
+        let before = std::mem::replace(&mut self.text, text);
+        self.revisions.push(Revision {
+            text: before,
+            editor: editor.to_string(),
+            at: now(),
+        });
+        true
+    }
+
+    /// The text of revision `n`, counting from 1 for the compiled story up
+    /// to the current text.
+    pub fn revision(&self, n: usize) -> Option<&str> {
+        match n.checked_sub(1) {
+            Some(i) if i < self.revisions.len() => Some(&self.revisions[i].text),
+            Some(i) if i == self.revisions.len() => Some(&self.text),
+            _ => None,
+        }
+    }
+}
+
+impl Record {
+    pub fn guild_id(&self) -> GuildId {
+        match self {
+            Record::Contribution(c) => c.guild_id,
+            Record::Story(s) => s.guild_id,
+            Record::Audit(a) => a.guild_id,
+            Record::Challenge(c) => c.guild_id,
+            Record::Stats(s) => s.guild_id,
+        }
+    }
+}
+
+/// A word worth pointing out, and who added it.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct NotableWord {
+    pub word: String,
+    pub author: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Stats {
+    pub stories: usize,
+    pub words: usize,
+    pub contributors: usize,
+    /// Challenge words worked into stories.
+    pub challenges: usize,
+    /// The longest word ever added.
+    pub longest_word: Option<NotableWord>,
+    /// Different words used.
+    pub vocabulary: usize,
+}
+
+/// Where settings and the story history are kept.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn load_configs(&self) -> Option<Configs>;
+    async fn save_configs(&self, configs: &Configs);
+    async fn append_contribution(&self, contribution: ContributionRecord);
+    async fn archive_story(&self, story: StoryRecord);
+    /// Replaces the archived story posted in the same message as `story`.
+    async fn update_story(&self, story: StoryRecord);
+    /// Deletes the archived story posted in the same message as `story`.
+    async fn delete_story(&self, story: &StoryRecord);
+    /// Stats of `guild_id`, or of every guild.
+    async fn stats(&self, guild_id: Option<GuildId>) -> Stats;
+    /// Sets the stats of `guild_id` to `stats`, counting on from there.
+    async fn restore_stats(&self, guild_id: GuildId, stats: Stats);
+    /// Stories compiled in `guild_id` from the unix time `since` on, oldest
+    /// first, all numbered.
+    async fn stories(&self, guild_id: GuildId, since: u64) -> Vec<StoryRecord>;
+    /// Words accepted in `guild_id` from the unix time `since` on, oldest
+    /// first.
+    async fn contributions(&self, guild_id: GuildId, since: u64) -> Vec<ContributionRecord>;
+    /// Every word used in `guild_id` before the unix time `before`,
+    /// lowercased.
+    async fn vocabulary(&self, guild_id: GuildId, before: u64) -> HashSet<String>;
+    async fn record_audit(&self, change: AuditRecord);
+    async fn record_challenge(&self, challenge: ChallengeRecord);
+    /// Up to `limit` of the latest setting changes in `guild_id`, newest
+    /// first.
+    async fn audit_log(&self, guild_id: GuildId, limit: usize) -> Vec<AuditRecord>;
+    /// Deletes the whole history of `guild_id`.
+    async fn purge_guild(&self, guild_id: GuildId);
+    /// Deletes records of `kind` of `guild_id` from before the unix time
+    /// `before`. Returns how many were deleted.
+    async fn prune(&self, guild_id: GuildId, kind: HistoryKind, before: u64) -> usize;
+    /// Tidies up the history, see [`Compaction`].
+    async fn compact(&self) -> Compaction;
+}
+
+pub struct StorageContainer;
+impl TypeMapKey for StorageContainer {
+    type Value = Arc<dyn Storage>;
+}
+
+pub async fn storage(data: &RwLock<TypeMap>) -> Arc<dyn Storage> {
+    let data = data.read().await;
+    data.get::<StorageContainer>()
+        .expect("Expected StorageContainer in TypeMap")
+        .clone()
+}
+
+/// The backend picked with the `STORAGE` env, `file` by default.
+pub fn from_env() -> Arc<dyn Storage> {
+    match env::var("STORAGE").as_deref() {
+        Ok("file") | Err(_) => Arc::new(FileStorage::from_env()),
+        Ok(other) => panic!("Unsupported storage {}, only file is available.", other),
+    }
+}
+
+/// Keeps settings in the JSON `CONFIG_FILE`, and the history as JSON lines
+/// in `HISTORY_FILE`, by default `history.jsonl` next to the config.
+pub struct FileStorage {
+    config_path: Option<String>,
+    history_path: Option<String>,
+    /// Keeps appends to the history from interleaving.
+    history_lock: Mutex<()>,
+}
+
+impl FileStorage {
+    pub fn from_env() -> Self {
+        let config_path = env::var("CONFIG_FILE").ok();
+        let history_path = env::var("HISTORY_FILE").ok().or_else(|| {
+            let config = Path::new(config_path.as_ref()?);
+            let history = config.with_file_name("history.jsonl");
+            Some(history.to_string_lossy().into_owned())
+        });
+        FileStorage {
+            config_path,
+            history_path,
+            history_lock: Mutex::new(()),
+        }
+    }
+
+    async fn append(&self, record: Record) {
+        let path = match &self.history_path {
+            Some(path) => path,
+            None => return,
+        };
+        let _lock = self.history_lock.lock().await;
+        let line = serde_json::to_string(&record).unwrap();
+        let res = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{}", line));
+        if let Err(why) = res {
+            println!("Error writing history {:?}", why);
+        }
+    }
+
+    /// Rewrites the history with each line replaced by what `change`
+    /// returns for it, dropping those it returns `None` for. Returns how
+    /// many lines were changed or dropped.
+    async fn rewrite_lines<F>(&self, change: F) -> usize
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync,
+    {
+        let path = match &self.history_path {
+            Some(path) => path,
+            None => return 0,
+        };
+        let _lock = self.history_lock.lock().await;
+        let contents = fs::read_to_string(path).unwrap_or_default();
+        let mut changed = 0;
+        let mut rewritten = String::new();
+        for line in contents.lines() {
+            match change(line) {
+                Some(new) => {
+                    if new != line {
+                        changed += 1;
+                    }
+                    rewritten += &format!("{}\n", new);
+                }
+                None => changed += 1,
+            }
+        }
+        if changed == 0 {
+            return 0;
+        }
+        // Written next to the history first, so it isn't lost halfway.
+        let tmp = format!("{}.tmp", path);
+        if let Err(why) = fs::write(&tmp, rewritten).and_then(|_| fs::rename(&tmp, path)) {
+            println!("Error rewriting history {:?}", why);
+            return 0;
+        }
+        changed
+    }
+
+    /// Rewrites the history with only the lines `keep` returns true for.
+    /// Returns how many lines were dropped.
+    async fn retain_lines<F>(&self, keep: F) -> usize
+    where
+        F: Fn(&str) -> bool + Send + Sync,
+    {
+        self.rewrite_lines(|line| keep(line).then(|| line.to_string()))
+            .await
+    }
+
+    /// Rewrites the history with only the records `keep` returns true for.
+    /// Returns how many were dropped.
+    async fn retain<F>(&self, keep: F) -> usize
+    where
+        F: Fn(&Record) -> bool + Send + Sync,
+    {
+        // Lines that aren't records are kept as they are.
+        self.retain_lines(|line| {
+            serde_json::from_str::<Record>(line).map_or(true, |record| keep(&record))
+        })
+        .await
+    }
+
+    /// Every record in the history, oldest first.
+    async fn history(&self) -> Vec<Record> {
+        let path = match &self.history_path {
+            Some(path) => path,
+            None => return Vec::new(),
+        };
+        let _lock = self.history_lock.lock().await;
+        let contents = fs::read_to_string(path).unwrap_or_default();
+        contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Storage for FileStorage {
+    async fn load_configs(&self) -> Option<Configs> {
+        let contents = fs::read_to_string(self.config_path.as_ref()?).ok()?;
+
+        if let Ok(configs) = serde_json::from_str::<Configs>(&contents) {
+            return Some(configs);
+        }
+
+        serde_json::from_str::<Config>(&contents)
+            .ok()
+            .map(|legacy| Configs {
+                legacy: Some(legacy),
+                ..Default::default()
+            })
+    }
+
+    async fn save_configs(&self, configs: &Configs) {
+        match &self.config_path {
+            Some(path) => {
+                if let Err(why) = fs::write(path, serde_json::to_string(configs).unwrap()) {
+                    println!("Error wriring config {:?}", why);
+                }
+            }
+            _ => {
+                println!("Mising CONFIG_FILE env. Configuration not saved.");
+            }
+        };
+    }
+
+    async fn append_contribution(&self, contribution: ContributionRecord) {
+        self.append(Record::Contribution(contribution)).await;
+    }
+
+    async fn archive_story(&self, story: StoryRecord) {
+        self.append(Record::Story(story)).await;
+    }
+
+    async fn update_story(&self, story: StoryRecord) {
+        let updated = serde_json::to_string(&Record::Story(story.clone())).unwrap();
+        self.rewrite_lines(|line| match serde_json::from_str::<Record>(line) {
+            Ok(Record::Story(s))
+                if s.guild_id == story.guild_id && s.message_id == story.message_id =>
+            {
+                Some(updated.clone())
+            }
+            _ => Some(line.to_string()),
+        })
+        .await;
+    }
+
+    async fn delete_story(&self, story: &StoryRecord) {
+        self.retain(|record| {
+            !matches!(record, Record::Story(s)
+                if s.guild_id == story.guild_id && s.message_id == story.message_id)
+        })
+        .await;
+    }
+
+    async fn stats(&self, guild_id: Option<GuildId>) -> Stats {
+        let history = self.history().await;
+        // Restored stats replace the records before them.
+        let mut restored: HashMap<GuildId, StatsRecord> = HashMap::new();
+        for record in history.iter() {
+            if let Record::Stats(s) = record {
+                if guild_id.is_none_or(|g| g == s.guild_id) {
+                    restored.insert(s.guild_id, s.clone());
+                }
+            }
+        }
+        let replaced =
+            |guild_id: GuildId, at: u64| restored.get(&guild_id).is_some_and(|s| at < s.at);
+
+        let mut stats = Stats::default();
+        let mut contributors = HashSet::new();
+        let mut vocabulary = HashSet::new();
+        for record in history {
+            match record {
+                Record::Contribution(c) if replaced(c.guild_id, c.at) => {}
+                Record::Story(s) if replaced(s.guild_id, s.at) => {}
+                Record::Challenge(c) if replaced(c.guild_id, c.at) => {}
+                Record::Contribution(c) if guild_id.is_none_or(|g| g == c.guild_id) => {
+                    stats.words += 1;
+                    for word in phrase_words(&c.word) {
+                        let longest = stats
+                            .longest_word
+                            .as_ref()
+                            .map_or(0, |l| l.word.chars().count());
+                        if word.chars().count() > longest {
+                            stats.longest_word = Some(NotableWord {
+                                word: word.clone(),
+                                author: c.author.clone(),
+                            });
+                        }
+                        vocabulary.insert(word);
+                    }
+                    contributors.insert(c.author);
+                }
+                Record::Story(s) if guild_id.is_none_or(|g| g == s.guild_id) => {
+                    stats.stories += 1;
+                }
+                Record::Challenge(c) if guild_id.is_none_or(|g| g == c.guild_id) => {
+                    stats.challenges += 1;
+                }
+                _ => {}
+            }
+        }
+        stats.contributors = contributors.len();
+        stats.vocabulary = vocabulary.len();
+        // People and words from before can't be told apart from those
+        // since, so they add up.
+        for base in restored.into_values().map(|s| s.stats) {
+            stats.stories += base.stories;
+            stats.words += base.words;
+            stats.contributors += base.contributors;
+            stats.challenges += base.challenges;
+            stats.vocabulary += base.vocabulary;
+            let longest =
+                |w: &Option<NotableWord>| w.as_ref().map_or(0, |w| w.word.chars().count());
+            if longest(&base.longest_word) > longest(&stats.longest_word) {
+                stats.longest_word = base.longest_word;
+            }
+        }
+        stats
+    }
+
+    async fn restore_stats(&self, guild_id: GuildId, stats: Stats) {
+        self.append(Record::Stats(StatsRecord {
+            guild_id,
+            stats,
+            at: now(),
+        }))
+        .await;
+    }
+
+    async fn stories(&self, guild_id: GuildId, since: u64) -> Vec<StoryRecord> {
+        self.history()
+            .await
+            .into_iter()
+            .filter_map(|record| match record {
+                Record::Story(s) if s.guild_id == guild_id => Some(s),
+                _ => None,
+            })
+            .zip(1..)
+            .map(|(mut s, place)| {
+                s.number.get_or_insert(place);
+                s
+            })
+            .filter(|s| s.at >= since)
+            .collect()
+    }
+
+    async fn contributions(&self, guild_id: GuildId, since: u64) -> Vec<ContributionRecord> {
+        self.history()
+            .await
+            .into_iter()
+            .filter_map(|record| match record {
+                Record::Contribution(c) if c.guild_id == guild_id && c.at >= since => Some(c),
+                _ => None,
+            })
+            .collect()
+    }
+
+    async fn vocabulary(&self, guild_id: GuildId, before: u64) -> HashSet<String> {
+        self.history()
+            .await
+            .into_iter()
+            .filter_map(|record| match record {
+                Record::Contribution(c) if c.guild_id == guild_id && c.at < before => Some(c),
+                _ => None,
+            })
+            .flat_map(|c| phrase_words(&c.word))
+            .collect()
+    }
+
+    async fn record_audit(&self, change: AuditRecord) {
+        self.append(Record::Audit(change)).await;
+    }
+
+    async fn record_challenge(&self, challenge: ChallengeRecord) {
+        self.append(Record::Challenge(challenge)).await;
+    }
+
+    async fn audit_log(&self, guild_id: GuildId, limit: usize) -> Vec<AuditRecord> {
+        let mut log: Vec<AuditRecord> = self
+            .history()
+            .await
+            .into_iter()
+            .filter_map(|record| match record {
+                Record::Audit(change) if change.guild_id == guild_id => Some(change),
+                _ => None,
+            })
+            .collect();
+        log.reverse();
+        log.truncate(limit);
+        log
+    }
+
+    async fn purge_guild(&self, guild_id: GuildId) {
+        self.retain(|record| record.guild_id() != guild_id).await;
+    }
+
+    async fn compact(&self) -> Compaction {
+        // Records of kinds this version doesn't know are still kept.
+        let dropped = self
+            .retain_lines(|line| serde_json::from_str::<serde_json::Value>(line).is_ok())
+            .await;
+        let bytes = self
+            .history_path
+            .as_ref()
+            .and_then(|path| fs::metadata(path).ok())
+            .map_or(0, |m| m.len());
+        Compaction {
+            records: self.history().await.len(),
+            dropped,
+            bytes,
+        }
+    }
+
+    async fn prune(&self, guild_id: GuildId, kind: HistoryKind, before: u64) -> usize {
+        self.retain(|record| match (record, kind) {
+            (Record::Contribution(c), HistoryKind::Contributions) => {
+                c.guild_id != guild_id || c.at >= before
+            }
+            (Record::Audit(a), HistoryKind::Audit) => a.guild_id != guild_id || a.at >= before,
+            _ => true,
+        })
+        .await
+    }
+}
+
+/// Records the accepted word `msg` in the history. `author` is who it was
+/// posted for, if it was posted in someone's name.
+pub async fn record_contribution(
+    data: &RwLock<TypeMap>,
+    guild_id: GuildId,
+    msg: &Message,
+    author: Option<&User>,
+) {
+    let user = author.unwrap_or(&msg.author);
+    let contribution = ContributionRecord {
+        guild_id,
+        channel_id: msg.channel_id,
+        message_id: msg.id,
+        author: user.name.clone(),
+        author_id: (!user.bot).then_some(user.id),
+        word: msg.content.clone(),
+        at: now(),
+    };
+    storage(data).await.append_contribution(contribution).await;
+}
@@ -0,0 +1,125 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+/// Marks where deleted words were left out of a compiled story.
+pub const GAP: &str = "…";
+
+/// Most recent contributions kept per channel.
+const RECENT_LIMIT: usize = 50;
+
+/// An accepted contribution, as posted.
+#[derive(Debug, Clone)]
+pub struct Contribution {
+    pub id: MessageId,
+    pub word: String,
+}
+
+/// Contributions to the story in progress in a channel.
+#[derive(Default)]
+pub struct StoryState {
+    /// Accepted contributions, with their authors.
+    accepted: HashMap<MessageId, UserId>,
+    /// How many of the accepted contributions each author added.
+    by_author: HashMap<UserId, usize>,
+    /// Accepted contributions that were deleted afterwards.
+    deleted: Vec<MessageId>,
+    /// The latest contributions, oldest first.
+    recent: VecDeque<Contribution>,
+    /// Whether the bot saw the story start, so `recent` has all of it up
+    /// to [`RECENT_LIMIT`].
+    complete: bool,
+}
+
+/// The story in progress in each story channel, since the bot started.
+pub struct StoryContainer;
+impl TypeMapKey for StoryContainer {
+    type Value = Arc<Mutex<HashMap<ChannelId, StoryState>>>;
+}
+
+async fn stories(data: &RwLock<TypeMap>) -> Arc<Mutex<HashMap<ChannelId, StoryState>>> {
+    let data = data.read().await;
+    data.get::<StoryContainer>()
+        .expect("Expected StoryContainer in TypeMap")
+        .clone()
+}
+
+pub async fn accept(data: &RwLock<TypeMap>, msg: &Message) {
+    let stories = stories(data).await;
+    let mut stories = stories.lock().await;
+    let story = stories.entry(msg.channel_id).or_default();
+    if story.accepted.insert(msg.id, msg.author.id).is_none() {
+        *story.by_author.entry(msg.author.id).or_default() += 1;
+    }
+    story.recent.push_back(Contribution {
+        id: msg.id,
+        word: msg.content.clone(),
+    });
+    if story.recent.len() > RECENT_LIMIT {
+        story.recent.pop_front();
+    }
+}
+
+/// Up to `count` of the latest contributions to the story in `channel_id`,
+/// oldest first, or `None` if they aren't all known.
+pub async fn recent(
+    data: &RwLock<TypeMap>,
+    channel_id: ChannelId,
+    count: usize,
+) -> Option<Vec<Contribution>> {
+    let stories = stories(data).await;
+    let stories = stories.lock().await;
+    let story = stories.get(&channel_id)?;
+    if !story.complete && story.recent.len() < count {
+        return None;
+    }
+    let skip = story.recent.len().saturating_sub(count);
+    Some(story.recent.iter().skip(skip).cloned().collect())
+}
+
+/// How many contributions `user` added to the story in `channel_id`, or
+/// `None` if the bot didn't see the story start.
+pub async fn added_by(
+    data: &RwLock<TypeMap>,
+    channel_id: ChannelId,
+    user: UserId,
+) -> Option<usize> {
+    let stories = stories(data).await;
+    let stories = stories.lock().await;
+    let story = stories.get(&channel_id).filter(|s| s.complete)?;
+    Some(story.by_author.get(&user).copied().unwrap_or_default())
+}
+
+/// Notes which of the deleted messages `ids` were words of the story.
+pub async fn delete(data: &RwLock<TypeMap>, channel_id: ChannelId, ids: &[MessageId]) {
+    let stories = stories(data).await;
+    let mut stories = stories.lock().await;
+    if let Some(story) = stories.get_mut(&channel_id) {
+        for id in ids {
+            if let Some(author) = story.accepted.remove(id) {
+                if let Some(count) = story.by_author.get_mut(&author) {
+                    *count = count.saturating_sub(1);
+                }
+                story.deleted.push(*id);
+                story.recent.retain(|c| c.id != *id);
+            }
+        }
+    }
+}
+
+/// Starts a new story in `channel_id`, returning the words deleted from
+/// the finished one.
+pub async fn finish(data: &RwLock<TypeMap>, channel_id: ChannelId) -> Vec<MessageId> {
+    let stories = stories(data).await;
+    let mut stories = stories.lock().await;
+    let story = StoryState {
+        complete: true,
+        ..Default::default()
+    };
+    stories
+        .insert(channel_id, story)
+        .map(|story| story.deleted)
+        .unwrap_or_default()
+}
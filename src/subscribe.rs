@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serenity::http::Http;
+use serenity::model::prelude::*;
+
+use crate::i18n::{tr_args, Locale};
+
+/// Longest embed description Discord accepts.
+const MAX_EMBED_LEN: usize = 4096;
+
+/// A compiled story, as sent to the guild's subscribers.
+pub struct Delivery {
+    pub guild: String,
+    pub title: String,
+    pub text: String,
+    pub link: String,
+    pub locale: Locale,
+}
+
+/// DMs the full text of a compiled story to every subscriber. Stories too
+/// long for one embed are sent in several.
+pub async fn notify(http: Arc<Http>, subscribers: HashSet<UserId>, story: Delivery) {
+    let intro = tr_args(
+        story.locale,
+        "subscription-story",
+        &[("guild", &story.guild), ("link", &story.link)],
+    );
+    let parts = split(&story.text, MAX_EMBED_LEN);
+    for user in subscribers {
+        let dm = match user.create_dm_channel(&http).await {
+            Ok(dm) => dm,
+            Err(why) => {
+                println!("Error opening DM with subscriber {:?}", why);
+                continue;
+            }
+        };
+        for (i, part) in parts.iter().enumerate() {
+            let sent = dm
+                .send_message(&http, |m| {
+                    if i == 0 {
+                        m.content(&intro);
+                    }
+                    m.embed(|e| {
+                        if i == 0 {
+                            e.title(&story.title);
+                        }
+                        e.description(part)
+                    })
+                })
+                .await;
+            if let Err(why) = sent {
+                println!("Error sending story to subscriber {:?}", why);
+                break;
+            }
+        }
+    }
+}
+
+/// `text` in parts of at most `max` bytes, split between words.
+fn split(text: &str, max: usize) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    for word in text.split_whitespace() {
+        let part = parts.last_mut().unwrap();
+        if !part.is_empty() && part.len() + 1 + word.len() > max {
+            parts.push(word.to_string());
+        } else {
+            if !part.is_empty() {
+                part.push(' ');
+            }
+            part.push_str(word);
+        }
+    }
+    parts
+}
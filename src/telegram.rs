@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::RwLock;
+
+use serde_json::json;
+use serenity::prelude::*;
+
+use crate::command::{parse_command, with_prefix, Command};
+use crate::config::{
+    find_bridged_channel, find_guild, get_telegram_config, set_telegram_config, Config, Rule,
+};
+use crate::feed::{publish, subscribe, FeedEvent, Source};
+use crate::game::{check_message, check_phrases, story_chunks, story_word, WordFilter};
+use crate::gist;
+use crate::i18n::{tr, tr_args};
+
+/// Telegram caps messages at 4096 characters.
+const MESSAGE_LIMIT: usize = 4096;
+
+#[derive(serde::Deserialize)]
+struct ApiResponse<T> {
+    ok: bool,
+    result: Option<T>,
+    description: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct Update {
+    update_id: i64,
+    message: Option<TgMessage>,
+}
+
+#[derive(serde::Deserialize)]
+struct TgMessage {
+    message_id: i64,
+    chat: Chat,
+    from: Option<User>,
+    text: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct Chat {
+    id: i64,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(serde::Deserialize)]
+struct User {
+    id: i64,
+    is_bot: bool,
+    first_name: String,
+}
+
+#[derive(serde::Deserialize)]
+struct ChatMember {
+    status: String,
+}
+
+struct Telegram {
+    api: String,
+    http: reqwest::Client,
+    data: Arc<RwLock<TypeMap>>,
+    /// Words of the story in progress in each group. Telegram bots can't read
+    /// chat history, so unlike on Discord the story has to be tracked here.
+    stories: HashMap<i64, Vec<String>>,
+}
+
+/// Runs the Telegram frontend until the process exits.
+pub async fn run(data: Arc<RwLock<TypeMap>>) {
+    let token = env::var("TELEGRAM_TOKEN").expect("Missing telegram token.");
+
+    let mut bot = Telegram {
+        api: format!("https://api.telegram.org/bot{}", token),
+        http: reqwest::Client::new(),
+        data,
+        stories: HashMap::new(),
+    };
+    tokio::spawn(
+        Telegram {
+            api: bot.api.clone(),
+            http: bot.http.clone(),
+            data: bot.data.clone(),
+            stories: HashMap::new(),
+        }
+        .forward_bridged(),
+    );
+
+    let mut offset = 0;
+    loop {
+        let updates: Vec<Update> = match bot
+            .call("getUpdates", json!({ "offset": offset, "timeout": 30 }))
+            .await
+        {
+            Some(updates) => updates,
+            None => {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        for update in updates {
+            offset = update.update_id + 1;
+            if let Some(msg) = update.message {
+                bot.handle(msg).await;
+            }
+        }
+    }
+}
+
+impl Telegram {
+    async fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Option<T> {
+        let res = self
+            .http
+            .post(format!("{}/{}", self.api, method))
+            .json(&params)
+            .send()
+            .await;
+
+        match res {
+            Ok(res) => match res.json::<ApiResponse<T>>().await {
+                Ok(ApiResponse {
+                    ok: true,
+                    result: Some(result),
+                    ..
+                }) => Some(result),
+                Ok(res) => {
+                    println!("Telegram {} failed: {:?}", method, res.description);
+                    None
+                }
+                Err(why) => {
+                    println!("Error reading Telegram response {:?}", why);
+                    None
+                }
+            },
+            Err(why) => {
+                println!("Error calling Telegram {:?}", why);
+                None
+            }
+        }
+    }
+
+    async fn send(&self, chat_id: i64, text: &str) {
+        let _: Option<serde_json::Value> = self
+            .call("sendMessage", json!({ "chat_id": chat_id, "text": text }))
+            .await;
+    }
+
+    async fn handle(&mut self, msg: TgMessage) {
+        let (from, text) = match (&msg.from, &msg.text) {
+            (Some(from), Some(text)) if !from.is_bot => (from, text),
+            _ => return,
+        };
+        if msg.chat.kind != "group" && msg.chat.kind != "supergroup" {
+            return;
+        }
+
+        let prefix = get_telegram_config(&self.data, msg.chat.id)
+            .await
+            .prefix()
+            .to_string();
+        if let Some(cmd) = parse_command(text, &prefix) {
+            match cmd {
+                Ok(cmd) => self.run_command(cmd, msg.chat.id, from.id).await,
+                Err(error) => self.send(msg.chat.id, &with_prefix(error, &prefix)).await,
+            }
+            return;
+        }
+
+        let chat_id = msg.chat.id;
+        let bridged = find_guild(&self.data, |config| {
+            config
+                .bridge
+                .as_ref()
+                .is_some_and(|b| b.telegram == Some(chat_id))
+        })
+        .await;
+        if let Some((_, config)) = bridged {
+            self.handle_bridged(&msg, &from.first_name, text, config)
+                .await;
+            return;
+        }
+
+        if "." == text {
+            self.generate_story(msg.chat.id).await;
+            return;
+        }
+
+        let config = get_telegram_config(&self.data, msg.chat.id).await;
+        let censor = WordFilter::new(&config);
+        let rules = config.rules(config.channel_id);
+        let story = self.stories.get(&msg.chat.id).map(Vec::as_slice);
+        if check_message(text, &censor, &rules).await.is_err()
+            || check_phrases(story.unwrap_or_default(), text, &config.banned_phrases).is_err()
+        {
+            self.delete(&msg).await;
+            return;
+        }
+
+        self.stories
+            .entry(msg.chat.id)
+            .or_default()
+            .push(story_word(text, rules.emoji_policy));
+    }
+
+    async fn delete(&self, msg: &TgMessage) {
+        let _: Option<bool> = self
+            .call(
+                "deleteMessage",
+                json!({ "chat_id": msg.chat.id, "message_id": msg.message_id }),
+            )
+            .await;
+    }
+
+    /// Handles a message in a group bridged to a Discord story channel. The
+    /// story lives on Discord, so accepted words are only passed along.
+    async fn handle_bridged(&self, msg: &TgMessage, author: &str, text: &str, config: Config) {
+        let channel_id = config.channel_id;
+        if "." == text {
+            publish(&self.data, FeedEvent::Compile { channel_id }).await;
+            return;
+        }
+
+        let censor = WordFilter::new(&config);
+        let rules = config.rules(config.channel_id);
+        if check_message(text, &censor, &rules).await.is_err() {
+            self.delete(msg).await;
+            return;
+        }
+
+        publish(
+            &self.data,
+            FeedEvent::Word {
+                channel_id,
+                word: text.to_string(),
+                author: author.to_string(),
+                source: Source::Telegram,
+            },
+        )
+        .await;
+    }
+
+    /// Echoes words and compiled stories from bridged platforms into their group.
+    async fn forward_bridged(self) {
+        let mut rx = subscribe(&self.data).await;
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return,
+            };
+
+            let chat_id = match find_bridged_channel(&self.data, event.channel_id()).await {
+                Some((_, config)) => match config.bridge.and_then(|b| b.telegram) {
+                    Some(chat_id) => chat_id,
+                    None => continue,
+                },
+                None => continue,
+            };
+
+            match event {
+                FeedEvent::Word {
+                    word,
+                    author,
+                    source,
+                    ..
+                } if source != Source::Telegram => {
+                    self.send(chat_id, &format!("{} ({}): {}", author, source, word))
+                        .await;
+                }
+                FeedEvent::End { story, .. } => {
+                    let words: Vec<String> = story.split_whitespace().map(str::to_string).collect();
+                    for chunk in story_chunks(&words, MESSAGE_LIMIT) {
+                        self.send(chat_id, &chunk).await;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    async fn generate_story(&mut self, chat_id: i64) {
+        let words = match self.stories.remove(&chat_id) {
+            Some(words) if !words.is_empty() => words,
+            _ => return,
+        };
+
+        let config = get_telegram_config(&self.data, chat_id).await;
+        if let Some(token) = &config.gist_token {
+            if let Some(url) = gist::publish(token, "Story", &words.join(" ")).await {
+                self.send(chat_id, &url).await;
+            }
+        }
+
+        for chunk in story_chunks(&words, MESSAGE_LIMIT) {
+            self.send(chat_id, &chunk).await;
+        }
+    }
+
+    async fn is_admin(&self, chat_id: i64, user_id: i64) -> bool {
+        let member: Option<ChatMember> = self
+            .call(
+                "getChatMember",
+                json!({ "chat_id": chat_id, "user_id": user_id }),
+            )
+            .await;
+        matches!(member, Some(m) if m.status == "creator" || m.status == "administrator")
+    }
+
+    async fn run_command(&self, cmd: Command, chat_id: i64, user_id: i64) {
+        let locale = get_telegram_config(&self.data, chat_id).await.locale;
+        if !self.is_admin(chat_id, user_id).await {
+            self.send(chat_id, &tr(locale, "admins-only")).await;
+            return;
+        }
+
+        match cmd {
+            Command::BanWord(word) => {
+                set_telegram_config(&self.data, chat_id, |config| config.ban(word)).await;
+            }
+            Command::UnbanWord(word) => {
+                set_telegram_config(&self.data, chat_id, |config| config.unban(&word)).await;
+            }
+            Command::SetEmojiPolicy(policy) => {
+                set_telegram_config(&self.data, chat_id, |config| {
+                    config.emoji_policy = policy;
+                })
+                .await;
+            }
+            Command::SetMatchMode(mode) => {
+                set_telegram_config(&self.data, chat_id, |config| {
+                    config.match_mode = mode;
+                })
+                .await;
+            }
+            Command::SetRule(
+                None,
+                rule @ (Rule::MatchMode(_) | Rule::MaxWords(_) | Rule::EmojiPolicy(_)),
+            ) => {
+                set_telegram_config(&self.data, chat_id, |config| config.set_rule(None, rule))
+                    .await;
+            }
+            Command::SetPrefix(prefix) => {
+                set_telegram_config(&self.data, chat_id, |config| {
+                    config.prefix = Some(prefix);
+                })
+                .await;
+            }
+            Command::SetLocale(locale) => {
+                set_telegram_config(&self.data, chat_id, |config| {
+                    config.locale = locale;
+                })
+                .await;
+            }
+            _ => {
+                let reply = tr_args(locale, "setting-unavailable", &[("platform", "Telegram")]);
+                self.send(chat_id, &reply).await;
+                return;
+            }
+        }
+
+        self.send(chat_id, &tr(locale, "settings-updated")).await;
+    }
+}
@@ -0,0 +1,53 @@
+/// Server specific wording of the bot's messages. Unset templates use the
+/// bot's own, translated, wording.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Templates {
+    /// DM sent to the author of a deleted contribution. `{user}`, `{word}`
+    /// and `{reason}` are filled in.
+    pub deletion_dm: Option<String>,
+    /// Title of compiled stories that aren't given one. `{word_count}` and
+    /// `{contributors}` are filled in.
+    pub story_title: Option<String>,
+    /// Posted after a story is compiled. `{title}`, `{word_count}`,
+    /// `{contributors}` and `{link}` are filled in.
+    pub completion: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TemplateKind {
+    DeletionDm,
+    StoryTitle,
+    Completion,
+}
+
+impl TemplateKind {
+    pub fn parse(name: &str) -> Option<TemplateKind> {
+        match name {
+            "deletion-dm" => Some(TemplateKind::DeletionDm),
+            "title" => Some(TemplateKind::StoryTitle),
+            "completion" => Some(TemplateKind::Completion),
+            _ => None,
+        }
+    }
+}
+
+impl Templates {
+    pub fn set(&mut self, kind: TemplateKind, template: Option<String>) {
+        let slot = match kind {
+            TemplateKind::DeletionDm => &mut self.deletion_dm,
+            TemplateKind::StoryTitle => &mut self.story_title,
+            TemplateKind::Completion => &mut self.completion,
+        };
+        *slot = template;
+    }
+}
+
+/// Replaces each `{name}` in `template` with its value from `vars`.
+pub fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in vars {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
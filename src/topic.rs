@@ -0,0 +1,86 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::CacheAndHttp;
+
+use crate::config::get_config;
+use crate::forum;
+use crate::i18n::tr_args;
+
+/// Time between topic updates. Discord allows two edits of a channel's
+/// name or topic per 10 minutes.
+const INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Story channels whose progress changed since their topic was updated.
+pub struct TopicContainer;
+impl TypeMapKey for TopicContainer {
+    type Value = Arc<Mutex<HashSet<(GuildId, ChannelId)>>>;
+}
+
+async fn changed(data: &RwLock<TypeMap>) -> Arc<Mutex<HashSet<(GuildId, ChannelId)>>> {
+    let data = data.read().await;
+    data.get::<TopicContainer>()
+        .expect("Expected TopicContainer in TypeMap")
+        .clone()
+}
+
+/// Notes that the story in `channel_id` changed, so its topic gets
+/// updated on the next round.
+pub async fn touch(data: &RwLock<TypeMap>, guild_id: GuildId, channel_id: ChannelId) {
+    if get_config(data, guild_id).await.topic_updates {
+        changed(data)
+            .await
+            .lock()
+            .await
+            .insert((guild_id, channel_id));
+    }
+}
+
+/// Updates the topics of changed story channels every few minutes, until
+/// the process exits.
+pub async fn run(data: Arc<RwLock<TypeMap>>, cache_and_http: Arc<CacheAndHttp>) {
+    loop {
+        tokio::time::sleep(INTERVAL).await;
+        let channels = std::mem::take(&mut *changed(&data).await.lock().await);
+        for (guild_id, channel_id) in channels {
+            update(&data, &cache_and_http, guild_id, channel_id).await;
+        }
+    }
+}
+
+async fn update(
+    data: &RwLock<TypeMap>,
+    cache_and_http: &CacheAndHttp,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) {
+    let cache = &cache_and_http.cache;
+    let http = &cache_and_http.http;
+    let config = get_config(data, guild_id).await;
+    let venue = forum::venue(cache, guild_id, channel_id);
+    // Forum posts have no topic.
+    if !config.topic_updates || !config.is_story_channel(venue) || venue != channel_id {
+        return;
+    }
+
+    let compiled = config.compiled.get(&channel_id).copied();
+    let messages = crate::story_messages(http, channel_id, None, compiled).await;
+    let rules = config.rules(venue);
+    let words = crate::story_words(&messages, &config, rules.emoji_policy, &[], cache, guild_id);
+    let count = words.join(" ").split_whitespace().count().to_string();
+    let max = rules.max_words.to_string();
+    let topic = match rules.max_words {
+        1 => tr_args(config.locale, "topic-one-word", &[("count", &count)]),
+        _ => tr_args(
+            config.locale,
+            "topic-words",
+            &[("max", &max), ("count", &count)],
+        ),
+    };
+    if let Err(why) = channel_id.edit(http, |c| c.topic(topic)).await {
+        println!("Error updating channel topic {:?}", why);
+    }
+}
@@ -0,0 +1,82 @@
+use serde_json::json;
+
+/// Scoring of contributions with the Perspective API.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ToxicityConfig {
+    pub api_key: String,
+    /// Score from 0 to 1 at which a contribution is acted on.
+    pub threshold: f32,
+    pub action: ToxicityAction,
+}
+
+/// What happens to a contribution scoring above the threshold.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToxicityAction {
+    Delete,
+    /// React with a warning sign and report it to the mod log.
+    Flag,
+    /// Only report it to the mod log.
+    Log,
+    /// Take it out of the story until a moderator reviews it, or flag it
+    /// if the guild has no review channel.
+    Review,
+}
+
+/// Number of preceding contributions sent along with a word, so it is judged
+/// in context rather than on its own.
+pub const CONTEXT_WORDS: u8 = 10;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnalyzeResponse {
+    attribute_scores: AttributeScores,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct AttributeScores {
+    toxicity: AttributeScore,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttributeScore {
+    summary_score: Score,
+}
+
+#[derive(serde::Deserialize)]
+struct Score {
+    value: f32,
+}
+
+/// Toxicity score of `text` from 0 to 1.
+pub async fn score(config: &ToxicityConfig, text: &str) -> Option<f32> {
+    let body = json!({
+        "comment": { "text": text },
+        "requestedAttributes": { "TOXICITY": {} },
+        "doNotStore": true,
+    });
+
+    let res = reqwest::Client::new()
+        .post("https://commentanalyzer.googleapis.com/v1alpha1/comments:analyze")
+        .query(&[("key", &config.api_key)])
+        .json(&body)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status());
+
+    match res {
+        Ok(res) => match res.json::<AnalyzeResponse>().await {
+            Ok(analysis) => Some(analysis.attribute_scores.toxicity.summary_score.value),
+            Err(why) => {
+                println!("Error reading toxicity score {:?}", why);
+                None
+            }
+        },
+        Err(why) => {
+            println!("Error scoring toxicity {:?}", why);
+            None
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use serde_json::json;
+
+/// An OpenAI compatible text-to-speech API.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct TtsConfig {
+    /// Base URL of the API, e.g. `https://api.openai.com/v1`.
+    pub endpoint: String,
+    pub api_key: String,
+    pub voice: Option<String>,
+}
+
+const MODEL: &str = "tts-1";
+const DEFAULT_VOICE: &str = "alloy";
+
+/// Longest input the speech endpoint accepts.
+const MAX_INPUT_CHARS: usize = 4096;
+
+/// Reads `text` aloud and returns the recording as MP3.
+pub async fn speak(config: &TtsConfig, text: &str) -> Option<Vec<u8>> {
+    let text: String = text.chars().take(MAX_INPUT_CHARS).collect();
+    let body = json!({
+        "model": MODEL,
+        "voice": config.voice.as_deref().unwrap_or(DEFAULT_VOICE),
+        "input": text,
+        "response_format": "mp3",
+    });
+
+    let res = reqwest::Client::new()
+        .post(format!(
+            "{}/audio/speech",
+            config.endpoint.trim_end_matches('/')
+        ))
+        .bearer_auth(&config.api_key)
+        .json(&body)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status());
+
+    match res {
+        Ok(res) => match res.bytes().await {
+            Ok(audio) => Some(audio.to_vec()),
+            Err(why) => {
+                println!("Error reading speech {:?}", why);
+                None
+            }
+        },
+        Err(why) => {
+            println!("Error synthesizing speech {:?}", why);
+            None
+        }
+    }
+}
@@ -0,0 +1,1097 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use async_tungstenite::tokio::TokioAdapter;
+use async_tungstenite::tungstenite::handshake::derive_accept_key;
+use async_tungstenite::tungstenite::protocol::Role;
+use async_tungstenite::tungstenite::Message as WsMessage;
+use async_tungstenite::WebSocketStream;
+use futures::{SinkExt, StreamExt};
+use hyper::header::{self, HeaderValue};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::upgrade::Upgraded;
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use rand::distributions::{Alphanumeric, DistString};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::CacheAndHttp;
+use tokio::sync::broadcast;
+
+use crate::actions;
+use crate::audit;
+use crate::command::parse_rule;
+use crate::config::{get_config, set_config, Config};
+use crate::cooldown;
+use crate::feed::{subscribe, FeedEvent};
+use crate::forum;
+use crate::modstats;
+use crate::rules_embed;
+use crate::slowmode;
+use crate::storage::storage;
+use crate::{generate_story, StoryEnd};
+
+const DISCORD_API: &str = "https://discord.com/api/v10";
+/// How long a dashboard login lasts.
+const SESSION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// OAuth2 application credentials, read from the environment.
+struct OAuth {
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+}
+
+impl OAuth {
+    fn from_env() -> Option<OAuth> {
+        let base_url = env::var("DASHBOARD_URL").ok()?;
+        Some(OAuth {
+            client_id: env::var("DISCORD_CLIENT_ID").ok()?,
+            client_secret: env::var("DISCORD_CLIENT_SECRET").ok()?,
+            redirect_uri: format!("{}/callback", base_url.trim_end_matches('/')),
+        })
+    }
+}
+
+/// A logged in dashboard user and the guilds they administrated when they
+/// logged in.
+struct Session {
+    user_id: UserId,
+    user_name: String,
+    guilds: HashMap<GuildId, String>,
+    expires_at: Instant,
+}
+
+struct Dashboard {
+    data: Arc<RwLock<TypeMap>>,
+    cache_and_http: Arc<CacheAndHttp>,
+    oauth: Option<OAuth>,
+    http: reqwest::Client,
+    sessions: RwLock<HashMap<String, Session>>,
+}
+
+#[derive(serde::Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(serde::Deserialize)]
+struct DiscordUser {
+    id: UserId,
+    username: String,
+}
+
+#[derive(serde::Deserialize)]
+struct DiscordGuild {
+    id: GuildId,
+    name: String,
+    owner: bool,
+    permissions: String,
+}
+
+/// The settings form of the dashboard. Rules are named `rule:<key>` for
+/// the guild and `rule:<channel id>:<key>` for a story channel, by their
+/// `one-word set` key.
+#[derive(Default)]
+struct SettingsForm {
+    channel_id: u64,
+    banned_words: String,
+    /// Story channels besides `channel_id`.
+    extra_channels: Vec<ChannelId>,
+    rules: Vec<(Option<ChannelId>, String, String)>,
+}
+
+impl SettingsForm {
+    fn parse(body: &[u8]) -> Option<SettingsForm> {
+        let fields: Vec<(String, String)> = serde_urlencoded::from_bytes(body).ok()?;
+        let mut form = SettingsForm::default();
+        for (name, value) in fields {
+            match name.split(':').collect::<Vec<&str>>().as_slice() {
+                ["channel_id"] => form.channel_id = value.parse().ok()?,
+                ["banned_words"] => form.banned_words = value,
+                ["extra_channel"] => form.extra_channels.push(ChannelId(value.parse().ok()?)),
+                ["rule", key] => form.rules.push((None, key.to_string(), value)),
+                ["rule", channel, key] => {
+                    let channel = ChannelId(channel.parse().ok()?);
+                    form.rules.push((Some(channel), key.to_string(), value));
+                }
+                _ => {}
+            }
+        }
+        Some(form)
+    }
+}
+
+/// Rules changed with `PATCH /guilds/<id>/settings`, by their
+/// `one-word set` key, for `channel_id` or the whole guild.
+#[derive(serde::Deserialize)]
+struct SettingsUpdate {
+    #[serde(default)]
+    channel_id: Option<ChannelId>,
+    rules: HashMap<String, String>,
+}
+
+/// The story in progress in a channel, served at `/api/channels/<id>/current`.
+#[derive(serde::Serialize)]
+struct CurrentStory {
+    channel_id: ChannelId,
+    text: String,
+    word_count: usize,
+    last_contributor: Option<String>,
+}
+
+/// Serves the admin dashboard and the live story feed until the process exits.
+///
+/// The dashboard is only available when `DASHBOARD_URL`, `DISCORD_CLIENT_ID`
+/// and `DISCORD_CLIENT_SECRET` are set.
+pub async fn serve(data: Arc<RwLock<TypeMap>>, cache_and_http: Arc<CacheAndHttp>) {
+    let oauth = OAuth::from_env();
+    if oauth.is_none() {
+        println!("Missing dashboard OAuth2 env. Dashboard disabled.");
+    }
+    let port = env::var("PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(8080);
+
+    let dashboard = Arc::new(Dashboard {
+        data,
+        cache_and_http,
+        oauth,
+        http: reqwest::Client::new(),
+        sessions: RwLock::new(HashMap::new()),
+    });
+
+    let make_svc = make_service_fn(move |_| {
+        let dashboard = dashboard.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let dashboard = dashboard.clone();
+                async move { Ok::<_, Infallible>(dashboard.route(req).await) }
+            }))
+        }
+    });
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    println!("Web server listening on {}", addr);
+    if let Err(why) = Server::bind(&addr).serve(make_svc).await {
+        println!("Dashboard error: {:?}", why);
+    }
+}
+
+impl Dashboard {
+    async fn route(&self, req: Request<Body>) -> Response<Body> {
+        let path: Vec<&str> = req
+            .uri()
+            .path()
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect();
+        if let (&Method::GET, ["metrics"]) = (req.method(), path.as_slice()) {
+            let queue = actions::queue(&self.data).await;
+            let metrics = actions::render(&queue.metrics) + &modstats::metrics(&self.data).await;
+            return Response::new(Body::from(metrics));
+        }
+        if let (&Method::GET, ["guilds", id, "stories"]) = (req.method(), path.as_slice()) {
+            return match id.parse() {
+                Ok(id) => self.stories(&req, GuildId(id)).await,
+                _ => not_found(),
+            };
+        }
+        if let (&Method::GET, ["guilds", id, "stories", number]) = (req.method(), path.as_slice()) {
+            return match (id.parse(), number.parse()) {
+                (Ok(id), Ok(number)) => self.story(&req, GuildId(id), number).await,
+                _ => not_found(),
+            };
+        }
+        if let (&Method::GET, ["api", "channels", id, "current"]) = (req.method(), path.as_slice())
+        {
+            return match id.parse() {
+                Ok(id) => self.current(&req, ChannelId(id)).await,
+                _ => not_found(),
+            };
+        }
+        if let (&Method::PUT, ["guilds", id, "banned-words"]) = (req.method(), path.as_slice()) {
+            return match id.parse() {
+                Ok(id) => self.put_banned_words(req, GuildId(id)).await,
+                _ => not_found(),
+            };
+        }
+        if let (&Method::PATCH, ["guilds", id, "settings"]) = (req.method(), path.as_slice()) {
+            return match id.parse() {
+                Ok(id) => self.patch_settings(req, GuildId(id)).await,
+                _ => not_found(),
+            };
+        }
+        if let (&Method::POST, ["channels", id, "end"]) = (req.method(), path.as_slice()) {
+            return match id.parse() {
+                Ok(id) => self.end_story(&req, ChannelId(id)).await,
+                _ => not_found(),
+            };
+        }
+        if let (&Method::GET, ["channels", id, "feed"]) = (req.method(), path.as_slice()) {
+            return match id.parse() {
+                Ok(id) => self.feed(req, ChannelId(id)).await,
+                _ => not_found(),
+            };
+        }
+
+        let oauth = match &self.oauth {
+            Some(oauth) => oauth,
+            None => return not_found(),
+        };
+        match (req.method(), path.as_slice()) {
+            (&Method::GET, []) => self.index(&req).await,
+            (&Method::GET, ["login"]) => login(oauth),
+            (&Method::GET, ["callback"]) => self.callback(oauth, &req).await,
+            (&Method::GET, ["logout"]) => self.logout(&req).await,
+            (&Method::GET, ["guilds", id]) => match id.parse() {
+                Ok(id) => self.guild(&req, GuildId(id)).await,
+                _ => not_found(),
+            },
+            (&Method::POST, ["guilds", id]) => match id.parse() {
+                Ok(id) => self.update_guild(req, GuildId(id)).await,
+                _ => not_found(),
+            },
+            _ => not_found(),
+        }
+    }
+
+    /// The guild's archived stories as JSON, oldest first. Without a login
+    /// or the API token, only those from channels everyone can see.
+    async fn stories(&self, req: &Request<Body>, guild_id: GuildId) -> Response<Body> {
+        let mut stories = storage(&self.data).await.stories(guild_id, 0).await;
+        if self.api_user(req, guild_id).await.is_none() {
+            stories.retain(|s| self.is_public(guild_id, s.channel_id));
+        }
+        Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(&stories).unwrap()))
+            .unwrap()
+    }
+
+    /// The guild's archived story with the number as JSON.
+    async fn story(&self, req: &Request<Body>, guild_id: GuildId, number: u64) -> Response<Body> {
+        let story = storage(&self.data)
+            .await
+            .stories(guild_id, 0)
+            .await
+            .into_iter()
+            .find(|s| s.number == Some(number));
+        match story {
+            Some(story) if !self.can_read(req, guild_id, story.channel_id).await => unauthorized(),
+            Some(story) => Response::builder()
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_vec(&story).unwrap()))
+                .unwrap(),
+            None => not_found(),
+        }
+    }
+
+    /// The story in progress in a story channel as JSON, for widgets
+    /// showing it live.
+    async fn current(&self, req: &Request<Body>, channel_id: ChannelId) -> Response<Body> {
+        let cache = &self.cache_and_http.cache;
+        let http = &self.cache_and_http.http;
+        let guild_id = match self.channel_guild(channel_id).await {
+            Some(guild_id) => guild_id,
+            None => return not_found(),
+        };
+        let config = get_config(&self.data, guild_id).await;
+        let venue = forum::venue(cache, guild_id, channel_id);
+        if !config.is_story_channel(venue) {
+            return not_found();
+        }
+        if !self.can_read(req, guild_id, channel_id).await {
+            return unauthorized();
+        }
+
+        let compiled = config.compiled.get(&channel_id).copied();
+        let messages = crate::story_messages(http, channel_id, None, compiled).await;
+        let emoji = config.rules(venue).emoji_policy;
+        let words = crate::story_words(&messages, &config, emoji, &[], cache, guild_id);
+        let text = words.iter().rev().cloned().collect::<Vec<_>>().join(" ");
+        let bridge = config.bridge.as_ref();
+        let last_contributor = messages
+            .iter()
+            .take_while(|m| "." != m.content)
+            .find(|m| crate::is_contribution(m, bridge))
+            .map(|m| m.author.name.clone());
+
+        let current = CurrentStory {
+            channel_id,
+            word_count: text.split_whitespace().count(),
+            text,
+            last_contributor,
+        };
+        Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(&current).unwrap()))
+            .unwrap()
+    }
+
+    /// Whether everyone in the guild can see `channel_id`, or the forum it
+    /// is a post in.
+    fn is_public(&self, guild_id: GuildId, channel_id: ChannelId) -> bool {
+        let cache = &self.cache_and_http.cache;
+        let venue = forum::venue(cache, guild_id, channel_id);
+        let (guild, channel) = match (cache.guild(guild_id), cache.guild_channel(venue)) {
+            (Some(guild), Some(channel)) => (guild, channel),
+            _ => return false,
+        };
+        // The @everyone role has the id of the guild.
+        match guild.roles.get(&RoleId(guild_id.0)) {
+            Some(everyone) => guild
+                .role_permissions_in(&channel, everyone)
+                .is_ok_and(|p| p.contains(Permissions::VIEW_CHANNEL)),
+            None => false,
+        }
+    }
+
+    /// Whether `req` may read the stories of `channel_id`: it is public, or
+    /// `req` manages the guild.
+    async fn can_read(
+        &self,
+        req: &Request<Body>,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+    ) -> bool {
+        self.is_public(guild_id, channel_id) || self.api_user(req, guild_id).await.is_some()
+    }
+
+    async fn channel_guild(&self, channel_id: ChannelId) -> Option<GuildId> {
+        let cache = &self.cache_and_http.cache;
+        if let Some(guild_id) = cache.guild_channel_field(channel_id, |c| c.guild_id) {
+            return Some(guild_id);
+        }
+        // Forum posts are threads, which aren't cached as channels.
+        match channel_id.to_channel(&self.cache_and_http.http).await {
+            Ok(Channel::Guild(channel)) => Some(channel.guild_id),
+            _ => None,
+        }
+    }
+
+    /// Who `req` manages `guild_id` as, for the audit log: an admin of the
+    /// guild logged in to the dashboard, or anyone with the guild's API
+    /// token.
+    async fn api_user(&self, req: &Request<Body>, guild_id: GuildId) -> Option<String> {
+        if let Some(user_name) = self.session_admin(req, guild_id).await {
+            return Some(format!("{} (dashboard)", user_name));
+        }
+        let token = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))?;
+        let config = get_config(&self.data, guild_id).await;
+        match config.api_token {
+            Some(api_token) if constant_time_eq(api_token.as_bytes(), token.as_bytes()) => {
+                Some("API token".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Replaces the guild's banned words and phrases with the JSON array of
+    /// them in the body.
+    async fn put_banned_words(&self, req: Request<Body>, guild_id: GuildId) -> Response<Body> {
+        let user = match self.api_user(&req, guild_id).await {
+            Some(user) => user,
+            None => return unauthorized(),
+        };
+        let words: Vec<String> = match json_body(req).await {
+            Some(words) => words,
+            None => return bad_request("Expected a JSON array of words"),
+        };
+
+        let before = get_config(&self.data, guild_id).await;
+        set_config(&self.data, guild_id, |config| {
+            config.banned_words.clear();
+            config.banned_phrases.clear();
+            for word in words.iter().map(|w| w.trim()) {
+                if !word.is_empty() {
+                    config.ban(word.to_string());
+                }
+            }
+        })
+        .await;
+        let after = get_config(&self.data, guild_id).await;
+        audit::record(&self.data, guild_id, &user, None, &before, &after).await;
+        no_content()
+    }
+
+    /// Sets the rules in the body, all of them or, if any of them is
+    /// invalid, none, like `/settings` does.
+    async fn patch_settings(&self, req: Request<Body>, guild_id: GuildId) -> Response<Body> {
+        let user = match self.api_user(&req, guild_id).await {
+            Some(user) => user,
+            None => return unauthorized(),
+        };
+        let update: SettingsUpdate = match json_body(req).await {
+            Some(update) => update,
+            None => return bad_request("Expected a JSON object of rules"),
+        };
+
+        let before = get_config(&self.data, guild_id).await;
+        if let Some(channel_id) = update.channel_id {
+            if !before.is_story_channel(channel_id) {
+                return bad_request("Not a story channel");
+            }
+        }
+        let mut rules = Vec::new();
+        for (key, value) in update.rules.iter() {
+            match parse_rule(key, value.trim()) {
+                Ok(rule) => rules.push(rule),
+                Err(error) => return bad_request(error),
+            }
+        }
+        set_config(&self.data, guild_id, |config| {
+            for rule in rules {
+                config.set_rule(update.channel_id, rule);
+            }
+        })
+        .await;
+        let after = get_config(&self.data, guild_id).await;
+        audit::record(&self.data, guild_id, &user, None, &before, &after).await;
+        rules_embed::refresh(&self.cache_and_http.http, &before, &after).await;
+        no_content()
+    }
+
+    /// Ends the story in progress in a story channel and compiles it. The
+    /// compile cooldown and the contributors needed apply as they do to an
+    /// end marker posted by hand.
+    async fn end_story(&self, req: &Request<Body>, channel_id: ChannelId) -> Response<Body> {
+        let guild_id = match self.channel_guild(channel_id).await {
+            Some(guild_id) => guild_id,
+            None => return not_found(),
+        };
+        if self.api_user(req, guild_id).await.is_none() {
+            return unauthorized();
+        }
+        let config = get_config(&self.data, guild_id).await;
+        let cache = &self.cache_and_http.cache;
+        let venue = forum::venue(cache, guild_id, channel_id);
+        if !config.is_story_channel(venue) {
+            return not_found();
+        }
+
+        let http = &self.cache_and_http.http;
+        let rules = config.rules(venue);
+        if let Some(wait) =
+            cooldown::compile_wait(&self.data, channel_id, rules.compile_cooldown).await
+        {
+            return conflict(&format!(
+                "The story can be ended in {} seconds",
+                wait.as_secs().max(1)
+            ));
+        }
+        let compiled = config.compiled.get(&channel_id).copied();
+        let story = crate::story_messages(http, channel_id, None, compiled).await;
+        let bridge = config.bridge.as_ref();
+        if crate::story_contributors(&story, bridge).len() < rules.min_contributors {
+            return conflict("The story needs more contributors to end");
+        }
+
+        match channel_id.say(http, ".").await {
+            Ok(end) => {
+                let end = StoryEnd::Marker(end.id);
+                generate_story(http, cache, &self.data, channel_id, end, guild_id).await;
+                no_content()
+            }
+            Err(why) => {
+                println!("Error ending story from the API {:?}", why);
+                Response::builder()
+                    .status(StatusCode::BAD_GATEWAY)
+                    .body(Body::from("Could not end the story"))
+                    .unwrap()
+            }
+        }
+    }
+
+    async fn index(&self, req: &Request<Body>) -> Response<Body> {
+        let sessions = self.sessions.read().await;
+        let session = match session_id(req).and_then(|id| live(&sessions, &id)) {
+            Some(session) => session,
+            None => return page("Login", "<a href=\"/login\">Login with Discord</a>"),
+        };
+
+        let bot_guilds: HashSet<GuildId> = self.cache_and_http.cache.guilds().into_iter().collect();
+        let mut body = format!(
+            "<p>Logged in as {} (<a href=\"/logout\">logout</a>)</p><ul>",
+            escape(&session.user_name)
+        );
+        for (id, name) in session.guilds.iter() {
+            if bot_guilds.contains(id) {
+                body += &format!("<li><a href=\"/guilds/{}\">{}</a></li>", id, escape(name));
+            }
+        }
+        body += "</ul>";
+
+        page("Guilds", &body)
+    }
+
+    async fn callback(&self, oauth: &OAuth, req: &Request<Body>) -> Response<Body> {
+        let query: HashMap<String, String> =
+            serde_urlencoded::from_str(req.uri().query().unwrap_or("")).unwrap_or_default();
+        let code = match query.get("code") {
+            Some(code) => code,
+            None => return bad_request("Missing code"),
+        };
+        match (query.get("state"), cookie(req, "state")) {
+            (Some(state), Some(expected)) if *state == expected => {}
+            _ => return bad_request("Invalid state"),
+        }
+
+        let session = match self.exchange_code(oauth, code).await {
+            Ok(session) => session,
+            Err(why) => {
+                println!("Error logging in to dashboard: {:?}", why);
+                return bad_request("Login failed");
+            }
+        };
+
+        let id = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+        {
+            let mut sessions = self.sessions.write().await;
+            sessions.retain(|_, s| s.expires_at > Instant::now());
+            sessions.insert(id.clone(), session);
+        }
+
+        let mut res = redirect("/");
+        set_cookie(&mut res, "session", &id);
+        clear_cookie(&mut res, "state");
+        res
+    }
+
+    async fn exchange_code(&self, oauth: &OAuth, code: &str) -> reqwest::Result<Session> {
+        let token: TokenResponse = self
+            .http
+            .post(format!("{}/oauth2/token", DISCORD_API))
+            .form(&[
+                ("client_id", oauth.client_id.as_str()),
+                ("client_secret", oauth.client_secret.as_str()),
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", oauth.redirect_uri.as_str()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let user: DiscordUser = self
+            .http
+            .get(format!("{}/users/@me", DISCORD_API))
+            .bearer_auth(&token.access_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let guilds: Vec<DiscordGuild> = self
+            .http
+            .get(format!("{}/users/@me/guilds", DISCORD_API))
+            .bearer_auth(&token.access_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let guilds = guilds
+            .into_iter()
+            .filter(|g| {
+                let perms = Permissions::from_bits_truncate(g.permissions.parse().unwrap_or(0));
+                g.owner || perms.contains(Permissions::ADMINISTRATOR)
+            })
+            .map(|g| (g.id, g.name))
+            .collect();
+
+        Ok(Session {
+            user_id: user.id,
+            user_name: user.username,
+            guilds,
+            expires_at: Instant::now() + SESSION_TTL,
+        })
+    }
+
+    async fn logout(&self, req: &Request<Body>) -> Response<Body> {
+        if let Some(id) = session_id(req) {
+            self.sessions.write().await.remove(&id);
+        }
+        redirect("/")
+    }
+
+    async fn is_admin(&self, req: &Request<Body>, guild_id: GuildId) -> bool {
+        let sessions = self.sessions.read().await;
+        match session_id(req).and_then(|id| live(&sessions, &id)) {
+            Some(session) => session.guilds.contains_key(&guild_id),
+            None => false,
+        }
+    }
+
+    /// The name of the user logged in with `req`, if they still administrate
+    /// `guild_id`. Their permissions are checked again, as they may have
+    /// lost them since they logged in.
+    async fn session_admin(&self, req: &Request<Body>, guild_id: GuildId) -> Option<String> {
+        let (user_id, user_name) = {
+            let sessions = self.sessions.read().await;
+            let session = live(&sessions, &session_id(req)?)?;
+            if !session.guilds.contains_key(&guild_id) {
+                return None;
+            }
+            (session.user_id, session.user_name.clone())
+        };
+        let guild = self.cache_and_http.cache.guild(guild_id)?;
+        match guild
+            .member_permissions(&self.cache_and_http, user_id)
+            .await
+        {
+            Ok(perms) if perms.contains(Permissions::ADMINISTRATOR) => Some(user_name),
+            Ok(_) => None,
+            Err(why) => {
+                println!("Error checking dashboard permissions {:?}", why);
+                None
+            }
+        }
+    }
+
+    async fn guild(&self, req: &Request<Body>, guild_id: GuildId) -> Response<Body> {
+        if !self.is_admin(req, guild_id).await {
+            return redirect("/");
+        }
+        let cache = &self.cache_and_http.cache;
+        let guild_name = match cache.guild_field(guild_id, |g| g.name.clone()) {
+            Some(name) => name,
+            None => return not_found(),
+        };
+        let config = get_config(&self.data, guild_id).await;
+
+        let mut channels: Vec<GuildChannel> = cache
+            .guild_channels(guild_id)
+            .map(|channels| channels.into_iter().map(|(_, c)| c).collect())
+            .unwrap_or_default();
+        channels.retain(|c| c.kind == ChannelType::Text);
+        channels.sort_by_key(|c| c.position);
+
+        let mut body = String::from(
+            "<p><a href=\"/\">Back</a></p><form method=\"post\"><label>Story channel <select name=\"channel_id\"><option value=\"0\">None</option>",
+        );
+        for channel in channels.iter() {
+            let selected = if channel.id == config.channel_id {
+                " selected"
+            } else {
+                ""
+            };
+            body += &format!(
+                "<option value=\"{}\"{}>#{}</option>",
+                channel.id,
+                selected,
+                escape(&channel.name)
+            );
+        }
+        body += "</select></label><fieldset><legend>More story channels</legend>";
+        for channel in channels.iter().filter(|c| c.id != config.channel_id) {
+            let checked = match config.channels.contains_key(&channel.id) {
+                true => " checked",
+                false => "",
+            };
+            body += &format!(
+                "<label><input type=\"checkbox\" name=\"extra_channel\" value=\"{}\"{}> #{}</label><br>",
+                channel.id,
+                checked,
+                escape(&channel.name)
+            );
+        }
+        body += "</fieldset><fieldset><legend>Rules</legend>";
+        // No channel has overrides of its own as 0, so these are the guild's.
+        body += &rule_inputs(None, &config.rules(ChannelId(0)));
+        body += "</fieldset>";
+        // Rules set to default follow the guild's.
+        for channel in channels.iter().filter(|c| config.is_story_channel(c.id)) {
+            let overrides = config
+                .channels
+                .get(&channel.id)
+                .cloned()
+                .unwrap_or_default();
+            body += &format!(
+                "<fieldset><legend>Rules in #{}</legend>{}</fieldset>",
+                escape(&channel.name),
+                rule_inputs(Some(channel.id), &overrides)
+            );
+        }
+        let mut banned_words: Vec<&String> = config
+            .banned_words
+            .iter()
+            .chain(config.banned_phrases.iter())
+            .collect();
+        banned_words.sort();
+        body += &format!(
+            "<p><label>Banned words and phrases (one per line)<br><textarea name=\"banned_words\" rows=\"10\">{}</textarea></label></p><button>Save</button></form>",
+            escape(&banned_words.into_iter().cloned().collect::<Vec<String>>().join("\n"))
+        );
+
+        body += "<h2>Archived stories</h2>";
+        body += &self.archived_stories(guild_id, &config).await;
+
+        page(&guild_name, &body)
+    }
+
+    /// The guild's archived stories, newest first, linking to their
+    /// permalinks.
+    async fn archived_stories(&self, guild_id: GuildId, config: &Config) -> String {
+        let mut stories = storage(&self.data).await.stories(guild_id, 0).await;
+        stories.sort_by_key(|s| std::cmp::Reverse(s.number));
+
+        let cache = &self.cache_and_http.cache;
+        let channel_name = |id: ChannelId| {
+            cache
+                .guild_channel_field(id, |c| format!("#{}", c.name))
+                .unwrap_or_else(|| id.to_string())
+        };
+        let mut html = String::new();
+        for story in stories.iter() {
+            let number = story.number.unwrap_or_default();
+            html += &format!(
+                "<article><h3><a href=\"/guilds/{}/stories/{}\">{}</a> <small>{} in {}</small></h3><p>{}</p>",
+                guild_id,
+                number,
+                escape(&crate::embed_title(config, &story.title, number)),
+                config.local_time(story.at),
+                escape(&channel_name(story.channel_id)),
+                escape(&story.text)
+            );
+            if !story.contributors.is_empty() {
+                html += &format!(
+                    "<p><b>Contributors</b>: {}</p>",
+                    escape(&story.contributors.join(", "))
+                );
+            }
+            html += "</article>";
+        }
+        if html.is_empty() {
+            html = "<p>No stories yet.</p>".to_string();
+        }
+        html
+    }
+
+    async fn update_guild(&self, req: Request<Body>, guild_id: GuildId) -> Response<Body> {
+        let user_name = match self.session_admin(&req, guild_id).await {
+            Some(user_name) => user_name,
+            None => return redirect("/"),
+        };
+        let body = match hyper::body::to_bytes(req.into_body()).await {
+            Ok(body) => body,
+            _ => return bad_request("Invalid body"),
+        };
+        let form = match SettingsForm::parse(&body) {
+            Some(form) => form,
+            None => return bad_request("Invalid form"),
+        };
+
+        let cache = &self.cache_and_http.cache;
+        let in_guild =
+            |id: ChannelId| cache.guild_channel(id).map(|c| c.guild_id) == Some(guild_id);
+        let channel_id = ChannelId(form.channel_id);
+        if channel_id.0 != 0 && !in_guild(channel_id)
+            || !form.extra_channels.iter().all(|id| in_guild(*id))
+        {
+            return bad_request("Invalid channel");
+        }
+        let is_story_channel =
+            |id: ChannelId| id == channel_id || form.extra_channels.contains(&id);
+
+        let before = get_config(&self.data, guild_id).await;
+        // Only rules changed in the form are set, like `one-word set` would.
+        let mut rules = Vec::new();
+        for (channel, key, value) in form.rules.iter() {
+            let shown = match channel {
+                Some(channel) => {
+                    rule_values(&before.channels.get(channel).cloned().unwrap_or_default())
+                }
+                None => rule_values(&before.rules(ChannelId(0))),
+            };
+            let value = value.trim();
+            if shown.iter().any(|(k, v)| k == key && v == value)
+                || channel.is_some_and(|c| !is_story_channel(c))
+            {
+                continue;
+            }
+            match parse_rule(key, value) {
+                Ok(rule) => rules.push((*channel, rule)),
+                Err(error) => return bad_request(error),
+            }
+        }
+
+        set_config(&self.data, guild_id, |config| {
+            config.channel_id = channel_id;
+            // The story channel's own overrides are kept with it.
+            config.channels.retain(|id, _| is_story_channel(*id));
+            for id in form.extra_channels.iter() {
+                config.channels.entry(*id).or_default();
+            }
+            for (channel, rule) in rules {
+                config.set_rule(channel, rule);
+            }
+            config.banned_words.clear();
+            config.banned_phrases.clear();
+            for word in form.banned_words.lines().map(str::trim) {
+                if !word.is_empty() {
+                    config.ban(word.to_string());
+                }
+            }
+        })
+        .await;
+        let after = get_config(&self.data, guild_id).await;
+        let user = format!("{} (dashboard)", user_name);
+        audit::record(&self.data, guild_id, &user, None, &before, &after).await;
+        let http = &self.cache_and_http.http;
+        rules_embed::refresh(http, &before, &after).await;
+        slowmode::refresh(&self.data, http, &before, &after).await;
+
+        redirect(&format!("/guilds/{}", guild_id))
+    }
+
+    /// Upgrades to a WebSocket streaming accepted words in the story channel
+    /// `channel_id` as JSON, if `req` may read it.
+    async fn feed(&self, mut req: Request<Body>, channel_id: ChannelId) -> Response<Body> {
+        let guild_id = match self.channel_guild(channel_id).await {
+            Some(guild_id) => guild_id,
+            None => return not_found(),
+        };
+        let config = get_config(&self.data, guild_id).await;
+        let venue = forum::venue(&self.cache_and_http.cache, guild_id, channel_id);
+        if !config.is_story_channel(venue) || !self.can_read(&req, guild_id, channel_id).await {
+            return not_found();
+        }
+        let key = match req.headers().get(header::SEC_WEBSOCKET_KEY) {
+            Some(key) => derive_accept_key(key.as_bytes()),
+            None => return bad_request("Expected a WebSocket upgrade"),
+        };
+        let rx = subscribe(&self.data).await;
+
+        tokio::spawn(async move {
+            match hyper::upgrade::on(&mut req).await {
+                Ok(upgraded) => stream_feed(upgraded, rx, channel_id).await,
+                Err(why) => println!("Error upgrading feed connection: {:?}", why),
+            }
+        });
+
+        Response::builder()
+            .status(StatusCode::SWITCHING_PROTOCOLS)
+            .header(header::CONNECTION, "upgrade")
+            .header(header::UPGRADE, "websocket")
+            .header(header::SEC_WEBSOCKET_ACCEPT, key)
+            .body(Body::empty())
+            .unwrap()
+    }
+}
+
+async fn stream_feed(
+    upgraded: Upgraded,
+    mut rx: broadcast::Receiver<FeedEvent>,
+    channel_id: ChannelId,
+) {
+    let ws =
+        WebSocketStream::from_raw_socket(TokioAdapter::new(upgraded), Role::Server, None).await;
+    let (mut tx, mut incoming) = ws.split();
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(event) if event.channel_id() == channel_id => {
+                    let json = serde_json::to_string(&event).unwrap();
+                    if tx.send(WsMessage::Text(json)).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            msg = incoming.next() => match msg {
+                Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => return,
+                _ => {}
+            },
+        }
+    }
+}
+
+fn login(oauth: &OAuth) -> Response<Body> {
+    let state = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let url = format!(
+        "https://discord.com/oauth2/authorize?response_type=code&scope=identify%20guilds&client_id={}&redirect_uri={}&state={}",
+        oauth.client_id,
+        urlencode(&oauth.redirect_uri),
+        state
+    );
+
+    let mut res = redirect(&url);
+    set_cookie(&mut res, "state", &state);
+    res
+}
+
+fn cookie(req: &Request<Body>, name: &str) -> Option<String> {
+    req.headers()
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|c| c.trim().split_once('='))
+        .find(|(k, _)| *k == name)
+        .map(|(_, v)| v.to_string())
+}
+
+fn session_id(req: &Request<Body>) -> Option<String> {
+    cookie(req, "session")
+}
+
+/// The session with the id, unless it expired.
+fn live<'a>(sessions: &'a HashMap<String, Session>, id: &str) -> Option<&'a Session> {
+    sessions.get(id).filter(|s| s.expires_at > Instant::now())
+}
+
+fn set_cookie(res: &mut Response<Body>, name: &str, value: &str) {
+    let cookie = format!("{}={}; Path=/; HttpOnly; Secure; SameSite=Lax", name, value);
+    if let Ok(value) = HeaderValue::from_str(&cookie) {
+        res.headers_mut().append(header::SET_COOKIE, value);
+    }
+}
+
+fn clear_cookie(res: &mut Response<Body>, name: &str) {
+    let cookie = format!(
+        "{}=; Path=/; Max-Age=0; HttpOnly; Secure; SameSite=Lax",
+        name
+    );
+    if let Ok(value) = HeaderValue::from_str(&cookie) {
+        res.headers_mut().append(header::SET_COOKIE, value);
+    }
+}
+
+/// `rules`, the rules of the guild or the overrides of a channel, by their
+/// `one-word set` key, the way the command takes them.
+fn rule_values(rules: &impl serde::Serialize) -> Vec<(String, String)> {
+    let fields = match serde_json::to_value(rules) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => return Vec::new(),
+    };
+    fields
+        .into_iter()
+        .map(|(field, value)| {
+            let key = match field.as_str() {
+                "emoji_policy" => "emoji".to_string(),
+                field => field.replace('_', "-"),
+            };
+            let value = match value {
+                serde_json::Value::Null => "default".to_string(),
+                serde_json::Value::String(value) => value.replace('_', "-"),
+                value if key.ends_with("cooldown") && value.as_u64() == Some(0) => {
+                    "off".to_string()
+                }
+                value => value.to_string(),
+            };
+            (key, value)
+        })
+        .collect()
+}
+
+/// Inputs for `rules`, of the guild or of `channel`.
+fn rule_inputs(channel: Option<ChannelId>, rules: &impl serde::Serialize) -> String {
+    let prefix = match channel {
+        Some(channel) => format!("rule:{}:", channel),
+        None => "rule:".to_string(),
+    };
+    rule_values(rules)
+        .into_iter()
+        .map(|(key, value)| {
+            format!(
+                "<label>{0} <input name=\"{1}{0}\" value=\"{2}\"></label><br>",
+                key,
+                prefix,
+                escape(&value)
+            )
+        })
+        .collect()
+}
+
+/// Compares secrets in time independent of where they differ.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn urlencode(s: &str) -> String {
+    form_urlencoded::byte_serialize(s.as_bytes()).collect()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn page(title: &str, body: &str) -> Response<Body> {
+    let html = format!(
+        "<!doctype html><html><head><meta charset=\"utf-8\"><title>{0} - One Word Story</title></head><body><h1>{0}</h1>{1}</body></html>",
+        escape(title),
+        body
+    );
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .body(Body::from(html))
+        .unwrap()
+}
+
+fn redirect(location: &str) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::SEE_OTHER)
+        .header(header::LOCATION, location)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn bad_request(reason: &'static str) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .body(Body::from(reason))
+        .unwrap()
+}
+
+fn conflict(reason: &str) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::CONFLICT)
+        .body(Body::from(reason.to_string()))
+        .unwrap()
+}
+
+/// The body of `req` parsed as JSON, if it is valid.
+async fn json_body<T: serde::de::DeserializeOwned>(req: Request<Body>) -> Option<T> {
+    let body = hyper::body::to_bytes(req.into_body()).await.ok()?;
+    serde_json::from_slice(&body).ok()
+}
+
+fn no_content() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn unauthorized() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::UNAUTHORIZED)
+        .body(Body::from("Unauthorized"))
+        .unwrap()
+}
+
+fn not_found() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::from("Not found"))
+        .unwrap()
+}
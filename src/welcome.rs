@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::actions;
+use crate::config::{set_config, Config};
+use crate::i18n::tr_args;
+use crate::rules_embed;
+
+/// How long a welcome posted in the story channel stays.
+const CHANNEL_WELCOME: Duration = Duration::from_secs(60);
+
+/// How users are told how to play when they first contribute.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WelcomeMode {
+    #[default]
+    Off,
+    /// By DM.
+    Dm,
+    /// In a reply removed again after a minute.
+    Channel,
+}
+
+/// Welcomes the author of the accepted contribution `msg` with the rules
+/// of `venue`, unless they were welcomed in the guild before.
+pub async fn greet(
+    ctx: &Context,
+    msg: &Message,
+    guild_id: GuildId,
+    config: &Config,
+    venue: ChannelId,
+) {
+    if config.welcome == WelcomeMode::Off || config.welcomed.contains(&msg.author.id) {
+        return;
+    }
+    set_config(&ctx.data, guild_id, |config| {
+        config.welcomed.insert(msg.author.id);
+    })
+    .await;
+
+    match config.welcome {
+        WelcomeMode::Off => {}
+        WelcomeMode::Dm => {
+            let guild = ctx
+                .cache
+                .guild_field(guild_id, |g| g.name.clone())
+                .unwrap_or_default();
+            let text = tr_args(config.locale, "welcome-dm", &[("guild", &guild)]);
+            let sent = msg
+                .author
+                .direct_message(&ctx.http, |m| {
+                    m.content(text)
+                        .embed(|e| rules_embed::embed(e, config, venue))
+                })
+                .await;
+            if let Err(why) = sent {
+                println!("Error sending welcome DM {:?}", why);
+            }
+        }
+        WelcomeMode::Channel => {
+            let user = msg.author.mention().to_string();
+            let text = tr_args(config.locale, "welcome-channel", &[("user", &user)]);
+            let sent = msg
+                .channel_id
+                .send_message(&ctx.http, |m| {
+                    m.content(text)
+                        .embed(|e| rules_embed::embed(e, config, venue))
+                })
+                .await;
+            let welcome = match sent {
+                Ok(welcome) => welcome,
+                Err(why) => {
+                    println!("Error posting welcome {:?}", why);
+                    return;
+                }
+            };
+            let ctx = ctx.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(CHANNEL_WELCOME).await;
+                actions::delete(&ctx, welcome.channel_id, welcome.id).await;
+            });
+        }
+    }
+}
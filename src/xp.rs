@@ -0,0 +1,253 @@
+use serenity::http::Http;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::card::{line_height, Canvas, INK, MUTED};
+use crate::config::{set_config, Config};
+use crate::i18n::tr_args;
+use crate::storage::{storage, ContributionRecord};
+
+const DAY: u64 = 24 * 60 * 60;
+/// Days in a row past which a streak earns no more bonus.
+const MAX_STREAK_DAYS: u64 = 7;
+
+const WIDTH: usize = 800;
+const PADDING: usize = 40;
+const NAME_SCALE: usize = 4;
+const TEXT_SCALE: usize = 2;
+const BAR_HEIGHT: usize = 24;
+
+const BAR: [u8; 3] = [0x4f, 0x7c, 0xa8];
+const TRACK: [u8; 3] = [0xe4, 0xdc, 0xcb];
+
+/// XP earned with accepted contributions. Guilds opt in.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct XpConfig {
+    /// XP for each accepted contribution.
+    pub per_word: u64,
+    /// Extra XP per contribution for each day in a row before it the
+    /// author contributed on.
+    pub streak_bonus: u64,
+    /// Level-ups are announced when the level is a multiple of this.
+    pub milestone: u64,
+}
+
+impl Default for XpConfig {
+    fn default() -> Self {
+        XpConfig {
+            per_word: 10,
+            streak_bonus: 2,
+            milestone: 5,
+        }
+    }
+}
+
+/// Where a user stands in a guild.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Progress {
+    pub xp: u64,
+    pub level: u64,
+    pub words: usize,
+    /// Days in a row the user has contributed on, up to today.
+    pub streak: u64,
+}
+
+/// Total XP needed to reach `level`. Each level takes 100 XP more than the
+/// one before.
+pub fn xp_for_level(level: u64) -> u64 {
+    50 * level * (level + 1)
+}
+
+fn level(xp: u64) -> u64 {
+    let mut level = 0;
+    while xp_for_level(level + 1) <= xp {
+        level += 1;
+    }
+    level
+}
+
+/// XP of a user in a guild, kept up as they contribute.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct UserXp {
+    /// Their name when they last contributed.
+    pub name: String,
+    pub xp: u64,
+    pub words: usize,
+    /// Days in a row they contributed on, up to `last_day`.
+    pub streak: u64,
+    /// Day of their newest contribution, in days since the unix epoch.
+    pub last_day: Option<u64>,
+}
+
+impl UserXp {
+    /// Adds a contribution made at the unix time `at`.
+    fn add(&mut self, config: &XpConfig, at: u64) {
+        let day = at / DAY;
+        self.streak = match self.last_day {
+            Some(last) if last == day => self.streak,
+            Some(last) if last + 1 == day => self.streak + 1,
+            _ => 1,
+        };
+        self.last_day = Some(day);
+        self.words += 1;
+        self.xp += config.per_word + config.streak_bonus * (self.streak.min(MAX_STREAK_DAYS) - 1);
+    }
+
+    /// Where the user stands as of the unix time `now`.
+    pub fn progress(&self, now: u64) -> Progress {
+        // A streak not kept up yesterday or today is broken.
+        let streak = match self.last_day {
+            Some(day) if day + 1 >= now / DAY => self.streak,
+            _ => 0,
+        };
+        Progress {
+            xp: self.xp,
+            level: level(self.xp),
+            words: self.words,
+            streak,
+        }
+    }
+}
+
+/// The XP earned with `contributions` of a single user, oldest first.
+fn from_history(config: &XpConfig, contributions: &[ContributionRecord]) -> UserXp {
+    let mut user = UserXp::default();
+    for c in contributions {
+        user.add(config, c.at);
+        user.name = c.author.clone();
+    }
+    user
+}
+
+/// Contributions of `user_id` in `guild_id`, oldest first.
+async fn contributions(
+    data: &RwLock<TypeMap>,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> Vec<ContributionRecord> {
+    storage(data)
+        .await
+        .contributions(guild_id, 0)
+        .await
+        .into_iter()
+        .filter(|c| c.author_id == Some(user_id))
+        .collect()
+}
+
+/// The XP of `user_id` in the guild, if they contributed. Users who last
+/// contributed before it was kept are counted from the history once.
+pub async fn user(
+    data: &RwLock<TypeMap>,
+    guild_id: GuildId,
+    user_id: UserId,
+    config: &Config,
+) -> Option<UserXp> {
+    if let Some(user) = config.xp_progress.get(&user_id) {
+        return Some(user.clone());
+    }
+    let xp = config.xp.as_ref()?;
+    let history = contributions(data, guild_id, user_id).await;
+    (!history.is_empty()).then(|| from_history(xp, &history))
+}
+
+/// Adds the XP of the just recorded `msg` to its author, and announces the
+/// level they reached with it, if it is a milestone.
+pub async fn award(
+    data: &RwLock<TypeMap>,
+    http: &Http,
+    msg: &Message,
+    guild_id: GuildId,
+    config: &Config,
+) {
+    let xp = match &config.xp {
+        Some(xp) => xp,
+        None => return,
+    };
+    let user_id = msg.author.id;
+    let seed = match config.xp_progress.contains_key(&user_id) {
+        true => None,
+        // The history already has `msg`.
+        false => {
+            let history = contributions(data, guild_id, user_id).await;
+            Some(from_history(
+                xp,
+                &history[..history.len().saturating_sub(1)],
+            ))
+        }
+    };
+    let (mut before, mut after) = (0, 0);
+    set_config(data, guild_id, |config| {
+        let user = config
+            .xp_progress
+            .entry(user_id)
+            .or_insert_with(|| seed.unwrap_or_default());
+        before = level(user.xp);
+        user.add(xp, crate::scheduler::now());
+        user.name = msg.author.name.clone();
+        after = level(user.xp);
+    })
+    .await;
+    if after <= before || !after.is_multiple_of(xp.milestone.max(1)) {
+        return;
+    }
+    let announcement = tr_args(
+        config.locale,
+        "xp-level-up",
+        &[
+            ("user", &msg.author.mention().to_string()),
+            ("level", &after.to_string()),
+        ],
+    );
+    if let Err(why) = msg.channel_id.say(http, announcement).await {
+        println!("Error announcing level up {:?}", why);
+    }
+}
+
+/// Renders the level card of `name` as a PNG: their level, the XP towards
+/// the next one, and their streak.
+pub fn card(name: &str, progress: &Progress) -> Vec<u8> {
+    let name_height = line_height(NAME_SCALE);
+    let text_height = line_height(TEXT_SCALE);
+    let height = PADDING * 2 + name_height + text_height * 4 + BAR_HEIGHT;
+    let mut canvas = Canvas::new(WIDTH, height);
+
+    let mut y = PADDING;
+    canvas.text(PADDING, y, name, NAME_SCALE, INK);
+    y += name_height;
+    canvas.text(
+        PADDING,
+        y,
+        &format!("Level {}", progress.level),
+        TEXT_SCALE,
+        INK,
+    );
+    y += text_height;
+
+    let floor = xp_for_level(progress.level);
+    let next = xp_for_level(progress.level + 1);
+    let width = WIDTH - PADDING * 2;
+    let filled = ((progress.xp - floor) * width as u64 / (next - floor)) as usize;
+    canvas.rect(PADDING, y, width, BAR_HEIGHT, TRACK);
+    canvas.rect(PADDING, y, filled, BAR_HEIGHT, BAR);
+    y += BAR_HEIGHT + text_height / 2;
+
+    canvas.text(
+        PADDING,
+        y,
+        &format!("{} / {} XP", progress.xp, next),
+        TEXT_SCALE,
+        MUTED,
+    );
+    y += text_height;
+    canvas.text(
+        PADDING,
+        y,
+        &format!("{} words, {} day streak", progress.words, progress.streak),
+        TEXT_SCALE,
+        MUTED,
+    );
+
+    canvas.png()
+}